@@ -0,0 +1,89 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_chess_engine::core::{movegen, Board, Move, Position};
+
+// Legal moves of a few positions, to be played and taken back.
+fn positions() -> Vec<(Board, Vec<Move>)> {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+    ];
+
+    fens.iter()
+        .map(|fen| {
+            let mut board = Board::from(fen).unwrap();
+            let color = board.color_to_move;
+            let mut moves = [Move(0); 218];
+            let moves_count = board.get_moves(&mut moves);
+            let legal_moves = moves[..moves_count]
+                .iter()
+                .copied()
+                .filter(|m| {
+                    board.make_move(m);
+                    let is_legal = !board.is_king_in_check(color);
+                    board.undo_move(m);
+
+                    is_legal
+                })
+                .collect();
+
+            (board, legal_moves)
+        })
+        .collect()
+}
+
+fn make_benchmark(c: &mut Criterion) {
+    movegen::init();
+
+    let positions = positions();
+
+    c.bench_function("board make/undo", |b| {
+        let mut boards: Vec<(Board, &Vec<Move>)> = positions
+            .iter()
+            .map(|(board, moves)| (board.clone(), moves))
+            .collect();
+
+        b.iter(|| {
+            for (board, moves) in boards.iter_mut() {
+                for m in moves.iter() {
+                    board.make_move(black_box(m));
+                    board.undo_move(black_box(m));
+                }
+            }
+        })
+    });
+
+    c.bench_function("position make/unmake", |b| {
+        let mut copies: Vec<(Position, &Vec<Move>)> = positions
+            .iter()
+            .map(|(board, moves)| (Position::from_board(board), moves))
+            .collect();
+
+        b.iter(|| {
+            for (position, moves) in copies.iter_mut() {
+                for m in moves.iter() {
+                    let undo = position.make(black_box(m));
+                    position.unmake(black_box(m), &undo);
+                }
+            }
+        })
+    });
+
+    c.bench_function("position copy-make", |b| {
+        let copies: Vec<(Position, &Vec<Move>)> = positions
+            .iter()
+            .map(|(board, moves)| (Position::from_board(board), moves))
+            .collect();
+
+        b.iter(|| {
+            for (position, moves) in copies.iter() {
+                for m in moves.iter() {
+                    black_box(position.make_move(black_box(m)));
+                }
+            }
+        })
+    });
+}
+
+criterion_group!(benches, make_benchmark);
+criterion_main!(benches);
@@ -9,7 +9,10 @@ fn movegen_benchmark(c: &mut Criterion) {
         let mut index: usize = 0;
 
         b.iter(|| {
-            movegen::get_rook_attacks(black_box(Square((index % 64) as u8)), black_box(bb));
+            movegen::get_rook_attacks(
+                black_box(Square::try_from((index % 64) as u8).unwrap()),
+                black_box(bb),
+            );
 
             bb = bb + BitBoard(1);
             index += 1;
@@ -21,7 +24,10 @@ fn movegen_benchmark(c: &mut Criterion) {
         let mut index: usize = 0;
 
         b.iter(|| {
-            movegen::get_bishop_attacks(black_box(Square((index % 64) as u8)), black_box(bb));
+            movegen::get_bishop_attacks(
+                black_box(Square::try_from((index % 64) as u8).unwrap()),
+                black_box(bb),
+            );
 
             bb = bb + BitBoard(1);
             index += 1;
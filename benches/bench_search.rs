@@ -0,0 +1,56 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use rust_chess_engine::core::movegen;
+use rust_chess_engine::core::Board;
+use rust_chess_engine::search::tt::DEFAULT_HASH_SIZE;
+use rust_chess_engine::search::{
+    self, SearchLimits, SearchOptions, TimeManager, TranspositionTable,
+};
+use std::sync::atomic::AtomicBool;
+
+const NODES: u64 = 500_000;
+
+// Searches a fixed number of nodes with more and more threads, so the
+// throughput shows how the nodes per second scale with the threads.
+fn search_benchmark(c: &mut Criterion) {
+    movegen::init();
+
+    let board = Board::from("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+        .unwrap();
+    let limits = SearchLimits {
+        nodes: Some(NODES),
+        ..Default::default()
+    };
+    let time_manager = TimeManager::new(&limits, board.color_to_move);
+
+    let mut group = c.benchmark_group("search_threads");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(NODES));
+
+    let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+    for threads in [1, 2, 4, 8]
+        .into_iter()
+        .filter(|&threads| threads <= cores.max(2))
+    {
+        let options = SearchOptions {
+            threads,
+            quiet: true,
+            ..Default::default()
+        };
+
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, _| {
+            b.iter_batched(
+                || TranspositionTable::new(DEFAULT_HASH_SIZE),
+                |tt| {
+                    let stop = AtomicBool::new(false);
+                    search::run(&board, &limits, &options, &time_manager, &tt, &stop)
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, search_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,138 @@
+use rust_chess_engine::core::movegen;
+use rust_chess_engine::evaluation::kpk;
+use rust_chess_engine::tuning::datagen::{self, DatagenSettings};
+use std::fs::File;
+use std::io::BufWriter;
+use std::process;
+use std::thread;
+use std::time::Instant;
+
+const USAGE: &str = "\
+Usage: datagen [options]
+
+Plays self-play games from random openings and writes every quiet position with
+its search score and the game result to a binary file.
+
+Options:
+    --games <n>           Number of games (default: 100)
+    --threads <n>         Number of games played at the same time (default: all cores)
+    --nodes <n>           Nodes searched for every move (default: 5000)
+    --random-plies <n>    Random moves played at the start of every game (default: 8)
+    --max-opening <cp>    Discard openings scored beyond this (default: 300)
+    --max-moves <n>       Draw games after this many moves (default: 300)
+    --hash <mb>           Hash table size of every thread (default: 8)
+    --seed <n>            Seed of the random openings (default: 0)
+    --output <path>       Binary output file (default: data.bin)
+    --text <path>         Also export the positions as `<fen> <score> [<result>]` lines
+    --convert <path>      Only export the binary file at <path> to the --text file";
+
+struct Options {
+    settings: DatagenSettings,
+    output: String,
+    text: Option<String>,
+    convert: Option<String>,
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, USAGE);
+        process::exit(1);
+    });
+
+    movegen::init();
+    kpk::init();
+
+    let binary = match &options.convert {
+        Some(path) => path.clone(),
+        None => {
+            generate(&options);
+            options.output.clone()
+        }
+    };
+
+    if let Some(text) = &options.text {
+        match datagen::export_text(&binary, text) {
+            Ok(count) => println!("Exported {} positions to {}", count, text),
+            Err(error) => {
+                eprintln!("Could not export the positions: {}", error);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn generate(options: &Options) {
+    let file = File::create(&options.output).unwrap_or_else(|error| {
+        eprintln!("Could not create {}: {}", options.output, error);
+        process::exit(1);
+    });
+
+    let start = Instant::now();
+    let result = datagen::generate(&options.settings, BufWriter::new(file), |progress| {
+        let elapsed = start.elapsed().as_secs_f64();
+        println!(
+            "Game {}/{}: {} positions ({:.0} positions/s)",
+            progress.games,
+            options.settings.games,
+            progress.positions,
+            progress.positions as f64 / elapsed.max(0.001)
+        );
+    });
+
+    match result {
+        Ok(count) => println!("Wrote {} positions to {}", count, options.output),
+        Err(error) => {
+            eprintln!("Could not generate the positions: {}", error);
+            process::exit(1);
+        }
+    }
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut args = std::env::args().skip(1);
+    let mut options = Options {
+        settings: DatagenSettings {
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            ..Default::default()
+        },
+        output: "data.bin".to_string(),
+        text: None,
+        convert: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        let settings = &mut options.settings;
+
+        match arg.as_str() {
+            "--games" => settings.games = parse(&value()?)?,
+            "--threads" => settings.threads = parse(&value()?)?,
+            "--nodes" => settings.nodes = parse(&value()?)?,
+            "--random-plies" => settings.random_plies = parse(&value()?)?,
+            "--max-opening" => settings.max_opening_score = parse(&value()?)?,
+            "--max-moves" => settings.max_moves = parse(&value()?)?,
+            "--hash" => settings.hash = parse(&value()?)?,
+            "--seed" => settings.seed = parse(&value()?)?,
+            "--output" => options.output = value()?,
+            "--text" => options.text = Some(value()?),
+            "--convert" => options.convert = Some(value()?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+
+    if options.convert.is_some() && options.text.is_none() {
+        return Err("--convert needs a --text output".to_string());
+    }
+
+    Ok(options)
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {}", value))
+}
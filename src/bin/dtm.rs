@@ -0,0 +1,98 @@
+use rust_chess_engine::core::{movegen, Board};
+use rust_chess_engine::dtm::{self, Dtm, DtmTables, Material};
+use std::path::Path;
+use std::process;
+use std::time::Instant;
+
+const USAGE: &str = "\
+Usage: dtm generate <material> [--output <dir>]
+       dtm probe <dir> <fen>
+
+Generates the distance to mate of every position of a material, such as KRvK or
+KQvKR, with the tables of the materials it turns into by captures and promotions,
+and writes them to <material>.dtm files. Probing prints the distance to mate of a
+position from the tables of a directory.
+
+Options:
+    --output <dir>    Directory of the generated tables (default: .)";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    movegen::init();
+
+    let result = match args.first().map(String::as_str) {
+        Some("generate") => generate(&args[1..]),
+        Some("probe") => probe(&args[1..]),
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            return;
+        }
+        _ => Err("Missing command".to_string()),
+    };
+
+    if let Err(error) = result {
+        eprintln!("{}\n\n{}", error, USAGE);
+        process::exit(1);
+    }
+}
+
+fn generate(args: &[String]) -> Result<(), String> {
+    let mut material = None;
+    let mut output = ".".to_string();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => {
+                output = args
+                    .next()
+                    .ok_or(format!("Missing value for {}", arg))?
+                    .clone()
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => material = Some(Material::parse(arg)?),
+        }
+    }
+    let material = material.ok_or("Missing material")?;
+
+    let start = Instant::now();
+    let tables = dtm::generate(&material)?;
+    tables.save(&output)?;
+
+    let table = tables.get(&material).unwrap();
+    println!(
+        "Generated {} tables in {:.1}s, the longest mate of {} is in {} plies",
+        tables.len(),
+        start.elapsed().as_secs_f64(),
+        material.name(),
+        table.longest_win()
+    );
+    println!(
+        "Written to {}",
+        Path::new(&output)
+            .join(format!("{}.{}", material.name(), dtm::EXTENSION))
+            .display()
+    );
+
+    Ok(())
+}
+
+fn probe(args: &[String]) -> Result<(), String> {
+    let (directory, fen) = match args {
+        [directory, fen @ ..] if !fen.is_empty() => (directory, fen.join(" ")),
+        _ => return Err("Missing directory or position".to_string()),
+    };
+
+    let tables = DtmTables::load(directory)?;
+    let board = Board::from(&fen)?;
+
+    match tables.probe(&board) {
+        Some(Dtm::Win(plies)) => println!("Win, mate in {} plies", plies),
+        Some(Dtm::Loss(plies)) => println!("Loss, mated in {} plies", plies),
+        Some(Dtm::Draw) => println!("Draw"),
+        None => println!("Not in the tables"),
+    }
+
+    Ok(())
+}
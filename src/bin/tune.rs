@@ -0,0 +1,140 @@
+use rust_chess_engine::core::movegen;
+use rust_chess_engine::evaluation::{kpk, DEFAULT_PARAMS};
+use rust_chess_engine::tuning::{datagen, dataset, Method, Tuner};
+use std::fs;
+use std::process;
+use std::thread;
+use std::time::Instant;
+
+const USAGE: &str = "\
+Usage: tune <dataset> [options]
+
+The dataset is either a PGN file (*.pgn), a binary file written by datagen (*.bin)
+or a file of `<fen> <result>` lines.
+
+Options:
+    --method <local|gradient>  Optimisation method (default: local)
+    --iterations <n>           Number of iterations (default: 100)
+    --rate <r>                 Learning rate of the gradient descent (default: 1.0)
+    --threads <n>              Number of threads (default: all cores)
+    --k <k>                    Sigmoid scaling constant (default: fitted)
+    --output <path>            Where to write the tuned weights (default: weights.rs)";
+
+struct Options {
+    dataset: String,
+    method: String,
+    iterations: usize,
+    rate: f64,
+    threads: usize,
+    k: Option<f64>,
+    output: String,
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, USAGE);
+        process::exit(1);
+    });
+
+    movegen::init();
+    kpk::init();
+
+    let start = Instant::now();
+    let positions = if options.dataset.ends_with(".pgn") {
+        dataset::load_pgn(&options.dataset)
+    } else if options.dataset.ends_with(".bin") {
+        datagen::load_positions(&options.dataset)
+    } else {
+        dataset::load_fens(&options.dataset)
+    }
+    .unwrap_or_else(|error| {
+        eprintln!("Could not load the dataset: {}", error);
+        process::exit(1);
+    });
+    println!(
+        "Loaded {} positions in {:.1}s",
+        positions.len(),
+        start.elapsed().as_secs_f64()
+    );
+
+    let mut tuner = Tuner::new(positions, DEFAULT_PARAMS, options.threads);
+    let initial_error = match options.k {
+        Some(k) => {
+            tuner.k = k;
+            tuner.error(&tuner.params)
+        }
+        None => tuner.fit_k(),
+    };
+    println!("K = {:.6}, initial error = {:.8}", tuner.k, initial_error);
+
+    let method = match options.method.as_str() {
+        "gradient" => Method::GradientDescent(options.rate),
+        _ => Method::LocalSearch,
+    };
+
+    let start = Instant::now();
+    tuner.tune(method, options.iterations, |iteration, params, error| {
+        println!(
+            "Iteration {}: error = {:.8} ({:.1}s)",
+            iteration,
+            error,
+            start.elapsed().as_secs_f64()
+        );
+
+        // Save after every iteration so a long run can be interrupted.
+        if let Err(error) = fs::write(&options.output, params.to_rust_source()) {
+            eprintln!("Could not write {}: {}", options.output, error);
+        }
+    });
+
+    println!("Tuned weights written to {}", options.output);
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut args = std::env::args().skip(1);
+    let mut options = Options {
+        dataset: String::new(),
+        method: "local".to_string(),
+        iterations: 100,
+        rate: 1.0,
+        threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        k: None,
+        output: "weights.rs".to_string(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+
+        match arg.as_str() {
+            "--method" => {
+                options.method = value()?;
+                if options.method != "local" && options.method != "gradient" {
+                    return Err(format!("Unknown method {}", options.method));
+                }
+            }
+            "--iterations" => options.iterations = parse(&value()?)?,
+            "--rate" => options.rate = parse(&value()?)?,
+            "--threads" => options.threads = parse(&value()?)?,
+            "--k" => options.k = Some(parse(&value()?)?),
+            "--output" => options.output = value()?,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => options.dataset = arg,
+        }
+    }
+
+    if options.dataset.is_empty() {
+        return Err("Missing dataset".to_string());
+    }
+
+    Ok(options)
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {}", value))
+}
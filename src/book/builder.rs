@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use super::{encode_move, polyglot_key, Book, BookEntry};
+use crate::core::pgn::PgnGame;
+use crate::core::Color;
+
+/// Collects the moves of games into a Polyglot book. Every move played in the
+/// first `depth` plies of a game is counted, weighted as Polyglot does with
+/// two points for a win and one for a draw of the side which played it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookBuilder {
+    /// Number of plies of every game which are added to the book.
+    pub depth: usize,
+    /// Moves played fewer times than this are left out of the book.
+    pub min_frequency: u32,
+    moves: HashMap<(u64, u16), MoveStats>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct MoveStats {
+    games: u32,
+    points: u32,
+}
+
+impl BookBuilder {
+    pub fn new(depth: usize, min_frequency: u32) -> Self {
+        BookBuilder {
+            depth,
+            min_frequency,
+            moves: HashMap::new(),
+        }
+    }
+
+    /// Adds the moves of `game`. Games without a result still count towards
+    /// the frequency of their moves, but score no points.
+    pub fn add_game(&mut self, game: &PgnGame) {
+        let white_points = match game.result() {
+            "1-0" => 2,
+            "1/2-1/2" => 1,
+            _ => 0,
+        };
+        let black_points = match game.result() {
+            "0-1" => 2,
+            "1/2-1/2" => 1,
+            _ => 0,
+        };
+
+        let mut board = game.start.clone();
+        for m in game.moves.iter().take(self.depth) {
+            let points = if board.color_to_move == Color::White {
+                white_points
+            } else {
+                black_points
+            };
+
+            let stats = self
+                .moves
+                .entry((polyglot_key(&board), encode_move(*m)))
+                .or_default();
+            stats.games += 1;
+            stats.points += points;
+
+            board.make_move(m);
+        }
+    }
+
+    /// Builds the book of every move played at least `min_frequency` times.
+    /// Weights are scaled down to fit in 16 bits, moves which never scored
+    /// keep a weight of zero so that they are never picked.
+    pub fn build(&self) -> Book {
+        let stats: Vec<_> = self
+            .moves
+            .iter()
+            .filter(|(_, stats)| stats.games >= self.min_frequency.max(1))
+            .collect();
+
+        let max_points = stats
+            .iter()
+            .map(|(_, stats)| stats.points)
+            .max()
+            .unwrap_or(0);
+        let scale = (max_points as f64 / u16::MAX as f64).max(1.0);
+
+        Book::from_entries(
+            stats
+                .into_iter()
+                .map(|(&(key, raw_move), stats)| BookEntry {
+                    key,
+                    raw_move,
+                    weight: (stats.points as f64 / scale).ceil() as u16,
+                    learn: 0,
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,254 @@
+pub mod builder;
+mod keys;
+
+use std::fs;
+
+use crate::core::board::CastlingRights;
+use crate::core::{square, BitBoard, Board, Color, Move, MoveFlags, Piece, PieceType, Square};
+use keys::RANDOM64;
+
+pub use builder::BookBuilder;
+
+/// Size in bytes of an entry of a Polyglot book.
+pub const ENTRY_SIZE: usize = 16;
+
+const CASTLING_OFFSET: usize = 768;
+const EN_PASSANT_OFFSET: usize = 772;
+const TURN_OFFSET: usize = 780;
+
+/// An entry of a Polyglot book: the key of a position, a move in Polyglot
+/// encoding and its weight. The learn field is kept but not used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    pub raw_move: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl BookEntry {
+    pub fn to_bytes(&self) -> [u8; ENTRY_SIZE] {
+        let mut bytes = [0; ENTRY_SIZE];
+        bytes[0..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.raw_move.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.learn.to_be_bytes());
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        BookEntry {
+            key: u64::from_be_bytes(bytes[0..8].try_into().unwrap()),
+            raw_move: u16::from_be_bytes([bytes[8], bytes[9]]),
+            weight: u16::from_be_bytes([bytes[10], bytes[11]]),
+            learn: u32::from_be_bytes(bytes[12..16].try_into().unwrap()),
+        }
+    }
+}
+
+/// A Polyglot opening book, with its entries sorted by key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Book {
+    entries: Vec<BookEntry>,
+}
+
+impl Book {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+
+        Book::from_bytes(&bytes).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err("Invalid book size".to_string());
+        }
+
+        let entries: Vec<BookEntry> = bytes
+            .chunks(ENTRY_SIZE)
+            .map(BookEntry::from_bytes)
+            .collect();
+
+        if entries.windows(2).any(|pair| pair[0].key > pair[1].key) {
+            return Err("Book entries are not sorted".to_string());
+        }
+
+        Ok(Book { entries })
+    }
+
+    /// Creates a book from entries in any order. Entries of the same position
+    /// are ordered by decreasing weight, as Polyglot writes them.
+    pub fn from_entries(mut entries: Vec<BookEntry>) -> Self {
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(b.weight.cmp(&a.weight)));
+
+        Book { entries }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.entries
+            .iter()
+            .flat_map(|entry| entry.to_bytes())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entries of the position with `key`, found by binary search.
+    pub fn entries(&self, key: u64) -> &[BookEntry] {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = start + self.entries[start..].partition_point(|entry| entry.key == key);
+
+        &self.entries[start..end]
+    }
+
+    /// Returns the legal book moves of `board` with their weights. Entries
+    /// whose move is not legal, which can happen on key collisions, are
+    /// skipped.
+    pub fn moves(&self, board: &Board) -> Vec<(Move, u16)> {
+        self.entries(polyglot_key(board))
+            .iter()
+            .filter_map(|entry| decode_move(entry.raw_move, board).map(|m| (m, entry.weight)))
+            .collect()
+    }
+
+    /// Picks a book move of `board` at random, with a probability proportional
+    /// to its weight. Moves with a weight of zero are never picked.
+    pub fn pick(&self, board: &Board, rng: &fastrand::Rng) -> Option<Move> {
+        let moves = self.moves(board);
+        let total: u32 = moves.iter().map(|&(_, weight)| weight as u32).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut choice = rng.u32(..total);
+        for (m, weight) in moves {
+            if choice < weight as u32 {
+                return Some(m);
+            }
+            choice -= weight as u32;
+        }
+
+        None
+    }
+}
+
+/// Computes the Polyglot key of `board`. The en passant file only counts if a
+/// pawn of the side to move can capture en passant.
+pub fn polyglot_key(board: &Board) -> u64 {
+    let mut key = 0;
+
+    let mut occupancy = board.all_occupancy();
+    while occupancy.is_not_empty() {
+        let square = Square(occupancy.bit_scan());
+        let piece = board.piece_at_square(square).unwrap();
+        let kind = 2 * piece.kind().to_usize() + if piece.color() == Color::White { 1 } else { 0 };
+        key ^= RANDOM64[64 * kind + square.to_usize()];
+
+        occupancy = occupancy.pop_lsb();
+    }
+
+    let castling = [
+        CastlingRights::WHITE_SHORT_CASTLE,
+        CastlingRights::WHITE_LONG_CASTLE,
+        CastlingRights::BLACK_SHORT_CASTLE,
+        CastlingRights::BLACK_LONG_CASTLE,
+    ];
+    for (index, rights) in castling.iter().enumerate() {
+        if board.castling_rights.contains(*rights) {
+            key ^= RANDOM64[CASTLING_OFFSET + index];
+        }
+    }
+
+    if board.en_passant.is_not_empty() {
+        let target = Square(board.en_passant.bit_scan());
+        let (pawn, rank) = if board.color_to_move == Color::White {
+            (Piece::WP, 4)
+        } else {
+            (Piece::BP, 3)
+        };
+
+        let can_capture = [target.file() as i8 - 1, target.file() as i8 + 1]
+            .iter()
+            .filter(|file| (0..8).contains(*file))
+            .any(|&file| {
+                let square = Square(rank * 8 + file as u8);
+                (board.pieces[pawn.to_usize()] & BitBoard::new(square)).is_not_empty()
+            });
+
+        if can_capture {
+            key ^= RANDOM64[EN_PASSANT_OFFSET + target.file() as usize];
+        }
+    }
+
+    if board.color_to_move == Color::White {
+        key ^= RANDOM64[TURN_OFFSET];
+    }
+
+    key
+}
+
+/// Converts a move in Polyglot encoding to the legal move of `board` it
+/// stands for. Polyglot writes castling as the king taking its own rook, so
+/// `e1h1` is short castling.
+pub fn decode_move(raw_move: u16, board: &Board) -> Option<Move> {
+    let to = Square((raw_move & 0x3f) as u8);
+    let from = Square(((raw_move >> 6) & 0x3f) as u8);
+    let promotion = match (raw_move >> 12) & 0x7 {
+        0 => None,
+        1 => Some('n'),
+        2 => Some('b'),
+        3 => Some('r'),
+        4 => Some('q'),
+        _ => return None,
+    };
+
+    let castling = match (from, to) {
+        (square::E1, square::H1) | (square::E8, square::H8) => Some(MoveFlags::SHORT_CASTLE),
+        (square::E1, square::A1) | (square::E8, square::A8) => Some(MoveFlags::LONG_CASTLE),
+        _ => None,
+    };
+    let is_king = board.piece_at_square(from).map(Piece::kind) == Some(PieceType::King);
+
+    let mut moves = [Move(0); 218];
+    let moves_count = board.get_moves(&mut moves);
+    let m = *moves[..moves_count].iter().find(|m| match castling {
+        Some(flags) if is_king => m.from() == from && m.flags() == flags,
+        _ => m.from() == from && m.to() == to && m.promotion_char() == promotion,
+    })?;
+
+    let mut board = board.clone();
+    let color = board.color_to_move;
+    board.make_move(&m);
+
+    if board.is_king_in_check(color) {
+        None
+    } else {
+        Some(m)
+    }
+}
+
+/// Converts a move to Polyglot encoding.
+pub fn encode_move(m: Move) -> u16 {
+    let from = m.from();
+    let to = match m.flags() {
+        MoveFlags::SHORT_CASTLE => Square(from.to_u8() + 3),
+        MoveFlags::LONG_CASTLE => Square(from.to_u8() - 4),
+        _ => m.to(),
+    };
+    let promotion = match m.promotion_char() {
+        None => 0,
+        Some('n') => 1,
+        Some('b') => 2,
+        Some('r') => 3,
+        Some(_) => 4,
+    };
+
+    (promotion << 12) | (from.to_u16() << 6) | to.to_u16()
+}
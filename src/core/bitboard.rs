@@ -1,23 +1,58 @@
+use super::movegen::Direction;
 use super::square::Square;
 use super::utils::*;
 use core::fmt;
 use std::ops::*;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
 pub struct BitBoard(pub u64);
 
 impl BitBoard {
-    pub fn new(square: Square) -> Self {
-        BitBoard(1u64 << square.to_usize())
+    pub const fn new(square: Square) -> Self {
+        BitBoard(1u64 << square.0)
     }
 
-    pub fn from_squares(squares: &[Square]) -> Self {
-        let mut bb = BitBoard::EMPTY;
-        for sq in squares {
-            bb |= BitBoard::new(*sq);
+    pub const fn from_squares(squares: &[Square]) -> Self {
+        let mut bb = 0;
+        let mut index = 0;
+        while index < squares.len() {
+            bb |= 1u64 << squares[index].0;
+            index += 1;
         }
 
-        bb
+        BitBoard(bb)
+    }
+
+    /// Returns the squares of file `file`, 0 being the a-file.
+    pub const fn file(file: u8) -> Self {
+        BitBoard(Self::FILE_A.0 << file)
+    }
+
+    /// Returns the squares of rank `rank`, 0 being the first rank.
+    pub const fn rank(rank: u8) -> Self {
+        BitBoard(Self::RANK_1.0 << (8 * rank))
+    }
+
+    /// Returns the diagonal through `square` which runs from its lower left
+    /// to its upper right, like a1-h8.
+    pub const fn diagonal(square: Square) -> Self {
+        let offset = (square.0 % 8) as i32 - (square.0 / 8) as i32;
+        if offset >= 0 {
+            BitBoard(Self::DIAGONAL_A1_H8.0 >> (8 * offset))
+        } else {
+            BitBoard(Self::DIAGONAL_A1_H8.0 << (-8 * offset))
+        }
+    }
+
+    /// Returns the diagonal through `square` which runs from its upper left
+    /// to its lower right, like a8-h1.
+    pub const fn anti_diagonal(square: Square) -> Self {
+        let offset = (square.0 % 8 + square.0 / 8) as i32 - 7;
+        if offset >= 0 {
+            BitBoard(Self::DIAGONAL_A8_H1.0 << (8 * offset))
+        } else {
+            BitBoard(Self::DIAGONAL_A8_H1.0 >> (-8 * offset))
+        }
     }
 
     pub fn is_empty(&self) -> bool {
@@ -32,6 +67,70 @@ impl BitBoard {
         (self.0 >> square.to_usize()) & 1 != 0
     }
 
+    /// Returns true if every square of `other` is set.
+    pub fn contains(&self, other: BitBoard) -> bool {
+        other.0 & !self.0 == 0
+    }
+
+    pub fn more_than_one(&self) -> bool {
+        self.0 & self.0.wrapping_sub(1) != 0
+    }
+
+    /// Moves every square one step in `direction`, dropping those which
+    /// would leave the board.
+    pub fn shift(self, direction: Direction) -> Self {
+        match direction {
+            Direction::North => self << 8u8,
+            Direction::NorthEast => (self & !Self::FILE_H) << 9u8,
+            Direction::East => (self & !Self::FILE_H) << 1u8,
+            Direction::SouthEast => (self & !Self::FILE_H) >> 7u8,
+            Direction::South => self >> 8u8,
+            Direction::SouthWest => (self & !Self::FILE_A) >> 9u8,
+            Direction::West => (self & !Self::FILE_A) >> 1u8,
+            Direction::NorthWest => (self & !Self::FILE_A) << 7u8,
+        }
+    }
+
+    /// Sets every square north of a set square.
+    pub fn north_fill(self) -> Self {
+        let mut bb = self.0;
+        bb |= bb << 8;
+        bb |= bb << 16;
+        bb |= bb << 32;
+
+        BitBoard(bb)
+    }
+
+    /// Sets every square south of a set square.
+    pub fn south_fill(self) -> Self {
+        let mut bb = self.0;
+        bb |= bb >> 8;
+        bb |= bb >> 16;
+        bb |= bb >> 32;
+
+        BitBoard(bb)
+    }
+
+    /// Sets the whole file of every set square.
+    pub fn file_fill(self) -> Self {
+        self.north_fill() | self.south_fill()
+    }
+
+    /// Mirrors the ranks, so that a1 becomes a8.
+    pub fn flip_vertical(self) -> Self {
+        BitBoard(self.0.swap_bytes())
+    }
+
+    /// Mirrors the files, so that a1 becomes h1.
+    pub fn mirror_horizontal(self) -> Self {
+        BitBoard(self.0.reverse_bits().swap_bytes())
+    }
+
+    /// Turns the board by 180 degrees, so that a1 becomes h8.
+    pub fn rotate(self) -> Self {
+        BitBoard(self.0.reverse_bits())
+    }
+
     pub fn pop_edges(self) -> Self {
         self & !Self::EDGES
     }
@@ -69,6 +168,47 @@ impl BitBoard {
     }
 }
 
+/// Iterates over the set squares of a bitboard, from a1 to h8.
+pub struct BitBoardIter(BitBoard);
+
+impl Iterator for BitBoardIter {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let square = Square(self.0.bit_scan());
+        self.0 = self.0.pop_lsb();
+        Some(square)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.0.pop_count() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for BitBoardIter {}
+
+impl IntoIterator for BitBoard {
+    type Item = Square;
+    type IntoIter = BitBoardIter;
+
+    fn into_iter(self) -> BitBoardIter {
+        BitBoardIter(self)
+    }
+}
+
+impl FromIterator<Square> for BitBoard {
+    fn from_iter<I: IntoIterator<Item = Square>>(squares: I) -> Self {
+        squares
+            .into_iter()
+            .fold(BitBoard::EMPTY, |bb, square| bb | BitBoard::new(square))
+    }
+}
+
 impl Shl<usize> for BitBoard {
     type Output = BitBoard;
 
@@ -243,6 +383,9 @@ impl BitBoard {
     #[allow(dead_code)]
     pub const EDGES: BitBoard = BitBoard(0xFF81_8181_8181_81FF);
 
+    pub const DIAGONAL_A1_H8: BitBoard = BitBoard(0x8040_2010_0804_0201);
+    pub const DIAGONAL_A8_H1: BitBoard = BitBoard(0x0102_0408_1020_4080);
+
     #[allow(dead_code)]
     pub const WHITE_OCCUPANY_INIT: BitBoard = BitBoard(0x0000_0000_0000_FFFF);
     #[allow(dead_code)]
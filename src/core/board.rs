@@ -1,23 +1,54 @@
 use bitflags::bitflags;
 use std::fmt;
 
-use super::movegen::{self, movescan, Move, MoveFlags};
+use super::movegen::{self, movescan, unmove, Move, MoveFlags, Unmove};
 use super::utils::grid_to_string;
-use super::{fen, square, BitBoard, Square};
+use super::{fen, square, validate, zobrist, BitBoard, Square};
+use crate::evaluation::nnue::{Accumulators, Network};
+use std::sync::Arc;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Board {
-    pub state: [Piece; 64],
+    pub state: [Option<Piece>; 64],
     pub pieces: [BitBoard; 12],
     pub occupancy: [BitBoard; 2],
     pub color_to_move: Color,
     pub en_passant: BitBoard,
     pub castling_rights: CastlingRights,
-    pub captured_pieces_stack: Vec<Piece>,
-    pub en_passant_stack: Vec<BitBoard>,
-    pub castling_rights_stack: Vec<CastlingRights>,
+    pub states: Vec<StateInfo>,
     pub half_move_clock: usize,
     pub full_move_number: usize,
+    pub hash: u64,
+    pub pawn_hash: u64,
+    pub check_info: CheckInfo,
+    pub nnue: Option<Accumulators>,
+}
+
+/// What a move overwrites, pushed by `Board::make_move` and popped by
+/// `Board::undo_move` to restore the position before it exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateInfo {
+    pub captured: Option<Piece>,
+    pub castling_rights: CastlingRights,
+    pub en_passant: BitBoard,
+    pub half_move_clock: usize,
+    pub hash: u64,
+    pub pawn_hash: u64,
+    pub check_info: CheckInfo,
+}
+
+/// Checks and pins of a position, computed once after each move by
+/// `Board::update_check_info`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CheckInfo {
+    /// Pieces which give check to the king of the side to move.
+    pub checkers: BitBoard,
+    /// For each colour, the pieces of either colour which are alone between
+    /// its king and an enemy slider.
+    pub blockers_for_king: [BitBoard; 2],
+    /// For each kind of piece of the side to move, the squares from which it
+    /// would check the enemy king.
+    pub check_squares: [BitBoard; 6],
 }
 
 impl Default for Board {
@@ -30,16 +61,16 @@ impl Board {
     #[allow(dead_code)]
     #[rustfmt::skip]
     pub fn new() -> Self {
-        Board {
+        let mut board = Board {
             state: [
-                Piece::WR, Piece::WN, Piece::WB, Piece::WQ, Piece::WK, Piece::WB, Piece::WN, Piece::WR,
-                Piece::WP, Piece::WP, Piece::WP, Piece::WP, Piece::WP, Piece::WP, Piece::WP, Piece::WP,
-                Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY,
-                Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY,
-                Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY,
-                Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY, Piece::EMPTY,
-                Piece::BP, Piece::BP, Piece::BP, Piece::BP, Piece::BP, Piece::BP, Piece::BP, Piece::BP,
-                Piece::BR, Piece::BN, Piece::BB, Piece::BQ, Piece::BK, Piece::BB, Piece::BN, Piece::BR,
+                Some(Piece::WR), Some(Piece::WN), Some(Piece::WB), Some(Piece::WQ), Some(Piece::WK), Some(Piece::WB), Some(Piece::WN), Some(Piece::WR),
+                Some(Piece::WP), Some(Piece::WP), Some(Piece::WP), Some(Piece::WP), Some(Piece::WP), Some(Piece::WP), Some(Piece::WP), Some(Piece::WP),
+                None, None, None, None, None, None, None, None,
+                None, None, None, None, None, None, None, None,
+                None, None, None, None, None, None, None, None,
+                None, None, None, None, None, None, None, None,
+                Some(Piece::BP), Some(Piece::BP), Some(Piece::BP), Some(Piece::BP), Some(Piece::BP), Some(Piece::BP), Some(Piece::BP), Some(Piece::BP),
+                Some(Piece::BR), Some(Piece::BN), Some(Piece::BB), Some(Piece::BQ), Some(Piece::BK), Some(Piece::BB), Some(Piece::BN), Some(Piece::BR),
             ],
             pieces: [
                 BitBoard::WHITE_PAWN_INIT,
@@ -59,30 +90,40 @@ impl Board {
                 BitBoard::WHITE_OCCUPANY_INIT,
                 BitBoard::BLACK_OCCUPANY_INIT,
             ],
-            color_to_move: Color::WHITE,
+            color_to_move: Color::White,
             en_passant: BitBoard::EMPTY,
             castling_rights: CastlingRights::ALL,
-            captured_pieces_stack: Vec::with_capacity(16),
-            en_passant_stack: Vec::with_capacity(16),
-            castling_rights_stack: Vec::with_capacity(16),
+            states: Vec::with_capacity(16),
             half_move_clock: 0,
             full_move_number: 1,
-        }
+            hash: 0,
+            pawn_hash: 0,
+            check_info: CheckInfo::default(),
+            nnue: None,
+        };
+
+        movegen::init();
+        board.hash = board.compute_hash();
+        board.pawn_hash = board.compute_pawn_hash();
+        board.update_check_info();
+        board
     }
 
     pub fn new_empty() -> Self {
         Board {
-            state: [Piece::EMPTY; 64],
+            state: [None; 64],
             pieces: [BitBoard::EMPTY; 12],
             occupancy: [BitBoard::EMPTY; 2],
-            color_to_move: Color::WHITE,
+            color_to_move: Color::White,
             en_passant: BitBoard::EMPTY,
             castling_rights: CastlingRights::NONE,
-            captured_pieces_stack: Vec::with_capacity(16),
-            en_passant_stack: Vec::with_capacity(16),
-            castling_rights_stack: Vec::with_capacity(16),
+            states: Vec::with_capacity(16),
             half_move_clock: 0,
             full_move_number: 0,
+            hash: 0,
+            pawn_hash: 0,
+            check_info: CheckInfo::default(),
+            nnue: None,
         }
     }
 
@@ -91,6 +132,12 @@ impl Board {
         fen::fen_to_board(fen)
     }
 
+    /// Reads `fen` like `from`, rejecting positions which cannot arise in a
+    /// game, see `validate`.
+    pub fn from_validated(fen: &str) -> Result<Self, Vec<String>> {
+        fen::fen_to_validated_board(fen)
+    }
+
     #[allow(dead_code)]
     pub fn from_moves(moves: &[&str]) -> Result<Self, &'static str> {
         let mut board = Board::new();
@@ -119,7 +166,7 @@ impl Board {
 
         index = movescan::scan_pawn_moves(self, color, moves, index);
 
-        if color == Color::WHITE {
+        if color == Color::White {
             index = movescan::scan_piece_moves(self, Piece::WN, moves, index);
             index = movescan::scan_piece_moves(self, Piece::WB, moves, index);
             index = movescan::scan_piece_moves(self, Piece::WR, moves, index);
@@ -141,11 +188,20 @@ impl Board {
         let from = m.from();
         let to = m.to();
         let flags = m.flags();
-        let piece = self.piece_at_square(from);
-        let is_white = piece.color() == Color::WHITE;
-
-        self.castling_rights_stack.push(self.castling_rights);
-        self.en_passant_stack.push(self.en_passant);
+        let piece = self.piece_at_square(from).expect("No piece to move");
+        let is_white = piece.color() == Color::White;
+
+        self.states.push(StateInfo {
+            captured: None,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            half_move_clock: self.half_move_clock,
+            hash: self.hash,
+            pawn_hash: self.pawn_hash,
+            check_info: self.check_info,
+        });
+        self.hash ^= zobrist::castling_key(self.castling_rights);
+        self.hash ^= zobrist::en_passant_key(self.en_passant);
         self.en_passant = BitBoard::EMPTY;
 
         match flags {
@@ -158,10 +214,8 @@ impl Board {
                     BitBoard::new(Square((to.to_i8() + if is_white { -8 } else { 8 }) as u8))
             }
             MoveFlags::CAPTURE => {
-                let captured_piece = self.piece_at_square(to);
-                assert_ne!(captured_piece, Piece::EMPTY);
-
-                self.captured_pieces_stack.push(captured_piece);
+                let captured_piece = self.piece_at_square(to).expect("No piece to capture");
+                self.states.last_mut().unwrap().captured = Some(captured_piece);
 
                 self.remove_piece_from_square(captured_piece, to);
                 self.move_piece(from, to, piece);
@@ -194,15 +248,11 @@ impl Board {
                 }
             },
             _ => {
-                // All promotions contains this bit
-                if flags.contains(MoveFlags::PROMOTE_TO_KNIGHT) {
-                    let promotion = m.promotion(is_white);
-
+                if let Some(promotion) = m.promotion(piece.color()) {
                     // All promotion captures contains this bit
                     if flags.contains(MoveFlags::CAPTURE) {
-                        let captured_piece = self.piece_at_square(to);
-                        assert_ne!(captured_piece, Piece::EMPTY);
-                        self.captured_pieces_stack.push(captured_piece);
+                        let captured_piece = self.piece_at_square(to).expect("No piece to capture");
+                        self.states.last_mut().unwrap().captured = Some(captured_piece);
                         self.remove_piece_from_square(captured_piece, to);
                     }
 
@@ -242,8 +292,28 @@ impl Board {
                 _ => {}
             }
         }
+        match to {
+            square::A1 => self.castling_rights &= !CastlingRights::WHITE_LONG_CASTLE,
+            square::H1 => self.castling_rights &= !CastlingRights::WHITE_SHORT_CASTLE,
+            square::A8 => self.castling_rights &= !CastlingRights::BLACK_LONG_CASTLE,
+            square::H8 => self.castling_rights &= !CastlingRights::BLACK_SHORT_CASTLE,
+            _ => {}
+        }
+
+        self.hash ^= zobrist::castling_key(self.castling_rights);
+        self.hash ^= zobrist::en_passant_key(self.en_passant);
+        self.hash ^= zobrist::SIDE_KEY;
 
+        if piece == Piece::WP || piece == Piece::BP || m.is_capture() {
+            self.half_move_clock = 0;
+        } else {
+            self.half_move_clock += 1;
+        }
+        if !is_white {
+            self.full_move_number += 1;
+        }
         self.color_to_move = self.color_to_move.enemy();
+        self.update_check_info();
     }
 
     #[allow(dead_code)]
@@ -251,8 +321,9 @@ impl Board {
         let from = m.from();
         let to = m.to();
         let flags = m.flags();
-        let piece = self.piece_at_square(to);
-        let is_white = piece.color() == Color::WHITE;
+        let piece = self.piece_at_square(to).expect("No piece to undo");
+        let is_white = piece.color() == Color::White;
+        let state = self.states.pop().unwrap();
 
         match flags {
             MoveFlags::QUIET => {
@@ -264,8 +335,7 @@ impl Board {
             MoveFlags::CAPTURE => {
                 self.move_piece(to, from, piece);
 
-                let captured_piece = self.captured_pieces_stack.pop().unwrap();
-                assert_ne!(captured_piece, Piece::EMPTY);
+                let captured_piece = state.captured.expect("No captured piece");
                 self.add_piece_to_square(captured_piece, to);
             }
             MoveFlags::EN_PASSANT => {
@@ -304,8 +374,7 @@ impl Board {
 
                     // All promotion captures contains this bit
                     if flags.contains(MoveFlags::CAPTURE) {
-                        let captured_piece = self.captured_pieces_stack.pop().unwrap();
-                        assert_ne!(captured_piece, Piece::EMPTY);
+                        let captured_piece = state.captured.expect("No captured piece");
                         self.add_piece_to_square(captured_piece, to);
                     }
                 } else {
@@ -314,15 +383,341 @@ impl Board {
             }
         }
 
-        self.castling_rights = self.castling_rights_stack.pop().unwrap();
-        self.en_passant = self.en_passant_stack.pop().unwrap();
+        self.castling_rights = state.castling_rights;
+        self.en_passant = state.en_passant;
+        self.half_move_clock = state.half_move_clock;
+        self.hash = state.hash;
+        self.pawn_hash = state.pawn_hash;
+        self.check_info = state.check_info;
+        if !is_white {
+            self.full_move_number -= 1;
+        }
         self.color_to_move = self.color_to_move.enemy();
     }
 
+    /// Checks that the position can arise in a game, returning every reason
+    /// why it cannot.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        validate::validate(self)
+    }
+
+    /// Returns every move which can have led to this position, see
+    /// `unmove::scan_unmoves` for the castling rights and en passant squares
+    /// of the positions before them.
+    pub fn get_unmoves(&self, castling_rights: CastlingRights) -> Vec<Unmove> {
+        let mut unmoves = Vec::new();
+        unmove::scan_unmoves(self, castling_rights, &mut unmoves);
+
+        unmoves
+    }
+
+    /// Takes back the move of `unmove`, which `make_move` plays again. The
+    /// half-move clock before a capture or a pawn move is not known, and is
+    /// set to 0.
+    pub fn unmake(&mut self, unmove: &Unmove) {
+        let m = &unmove.m;
+        let from = m.from();
+        let to = m.to();
+        let flags = m.flags();
+        let piece = self.piece_at_square(to).expect("No piece to unmake");
+        let is_white = piece.color() == Color::White;
+
+        match flags {
+            MoveFlags::QUIET | MoveFlags::DOUBLE_PUSH => {
+                self.move_piece(to, from, piece);
+            }
+            MoveFlags::CAPTURE => {
+                self.move_piece(to, from, piece);
+                self.add_piece_to_square(unmove.captured.expect("No captured piece"), to);
+            }
+            MoveFlags::EN_PASSANT => {
+                self.move_piece(to, from, piece);
+                self.add_piece_to_square(
+                    unmove.captured.expect("No captured pawn"),
+                    Square((to.to_i8() + if is_white { -8 } else { 8 }) as u8),
+                );
+            }
+            MoveFlags::SHORT_CASTLE => match is_white {
+                true => {
+                    self.move_piece(square::G1, square::E1, Piece::WK);
+                    self.move_piece(square::F1, square::H1, Piece::WR);
+                }
+                false => {
+                    self.move_piece(square::G8, square::E8, Piece::BK);
+                    self.move_piece(square::F8, square::H8, Piece::BR);
+                }
+            },
+            MoveFlags::LONG_CASTLE => match is_white {
+                true => {
+                    self.move_piece(square::C1, square::E1, Piece::WK);
+                    self.move_piece(square::D1, square::A1, Piece::WR);
+                }
+                false => {
+                    self.move_piece(square::C8, square::E8, Piece::BK);
+                    self.move_piece(square::D8, square::A8, Piece::BR);
+                }
+            },
+            _ => {
+                if flags.contains(MoveFlags::PROMOTE_TO_KNIGHT) {
+                    self.remove_piece_from_square(piece, to);
+                    self.add_piece_to_square(if is_white { Piece::WP } else { Piece::BP }, from);
+
+                    if flags.contains(MoveFlags::CAPTURE) {
+                        self.add_piece_to_square(unmove.captured.expect("No captured piece"), to);
+                    }
+                } else {
+                    panic!("Ivalid flag: {:?}", flags);
+                }
+            }
+        }
+
+        self.hash ^= zobrist::castling_key(self.castling_rights);
+        self.hash ^= zobrist::en_passant_key(self.en_passant);
+        self.castling_rights = unmove.castling_rights;
+        self.en_passant = unmove.en_passant;
+        self.hash ^= zobrist::castling_key(self.castling_rights);
+        self.hash ^= zobrist::en_passant_key(self.en_passant);
+        self.hash ^= zobrist::SIDE_KEY;
+
+        let is_pawn_move = piece == Piece::WP || piece == Piece::BP || m.is_promotion();
+        if is_pawn_move || m.is_capture() {
+            self.half_move_clock = 0;
+        } else {
+            self.half_move_clock = self.half_move_clock.saturating_sub(1);
+        }
+        if !is_white {
+            self.full_move_number = self.full_move_number.saturating_sub(1);
+        }
+        self.color_to_move = self.color_to_move.enemy();
+        self.update_check_info();
+    }
+
+    #[allow(dead_code)]
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+
+        for (index, piece) in self.state.iter().enumerate() {
+            if let Some(piece) = piece {
+                hash ^= zobrist::piece_key(*piece, Square(index as u8));
+            }
+        }
+
+        hash ^= zobrist::castling_key(self.castling_rights);
+        hash ^= zobrist::en_passant_key(self.en_passant);
+        hash ^= zobrist::side_key(self.color_to_move);
+
+        hash
+    }
+
+    /// Hashes only the pawns, for caching pawn structure evaluation.
+    pub fn compute_pawn_hash(&self) -> u64 {
+        let mut hash = 0;
+
+        for piece in [Piece::WP, Piece::BP] {
+            let mut pawns_bb = self.pieces[piece.to_usize()];
+            while pawns_bb.is_not_empty() {
+                hash ^= zobrist::piece_key(piece, Square(pawns_bb.bit_scan()));
+                pawns_bb = pawns_bb.pop_lsb();
+            }
+        }
+
+        hash
+    }
+
+    /// Recomputes `check_info`, which `make_move` does after every move.
+    /// Boards set up piece by piece need it before they are searched.
+    /// Requires `movegen::init`.
+    pub fn update_check_info(&mut self) {
+        let color = self.color_to_move;
+        let occupancy = self.all_occupancy();
+        let mut check_info = CheckInfo::default();
+
+        if let Some(king) = self.king_square(color) {
+            check_info.checkers =
+                self.attackers_to(king, occupancy) & self.occupancy[color.enemy().to_usize()];
+        }
+
+        for color in [Color::White, Color::Black] {
+            if let Some(king) = self.king_square(color) {
+                check_info.blockers_for_king[color.to_usize()] = self.blockers(king, color.enemy());
+            }
+        }
+
+        if let Some(king) = self.king_square(color.enemy()) {
+            let bishop = movegen::get_bishop_attacks(king, occupancy);
+            let rook = movegen::get_rook_attacks(king, occupancy);
+            check_info.check_squares = [
+                movegen::get_pawn_attacks(king, color.enemy()),
+                movegen::get_knight_attacks(king),
+                bishop,
+                rook,
+                bishop | rook,
+                BitBoard::EMPTY,
+            ];
+        }
+
+        self.check_info = check_info;
+    }
+
+    /// Returns the pieces which give check to the king of the side to move.
+    pub fn checkers(&self) -> BitBoard {
+        self.check_info.checkers
+    }
+
+    /// Returns the pieces of `color` which are pinned to their king.
+    pub fn pinned(&self, color: Color) -> BitBoard {
+        self.check_info.blockers_for_king[color.to_usize()] & self.occupancy[color.to_usize()]
+    }
+
+    /// Returns the pieces of either colour which are alone between the king
+    /// of `color` and an enemy slider. Moving those of the enemy gives a
+    /// discovered check.
+    pub fn blockers_for_king(&self, color: Color) -> BitBoard {
+        self.check_info.blockers_for_king[color.to_usize()]
+    }
+
+    /// Returns true if the pseudo-legal `m` checks the enemy king, without
+    /// making it.
+    pub fn gives_check(&self, m: &Move) -> bool {
+        let color = self.color_to_move;
+        let Some(king) = self.king_square(color.enemy()) else {
+            return false;
+        };
+        let from = m.from();
+        let to = m.to();
+        let flags = m.flags();
+        let Some(piece) = self.piece_at_square(from) else {
+            return false;
+        };
+        let kind = piece.kind().to_usize();
+
+        if !m.is_promotion() && self.check_info.check_squares[kind].is_set(to) {
+            return true;
+        }
+
+        // A blocker which leaves the line to the king discovers a check.
+        if self.blockers_for_king(color.enemy()).is_set(from)
+            && !movegen::line(from, king).is_set(to)
+        {
+            return true;
+        }
+
+        let occupancy = self.all_occupancy() ^ BitBoard::new(from) | BitBoard::new(to);
+        let own = |kind: usize| self.pieces[color.to_usize() * 6 + kind];
+        match flags {
+            MoveFlags::QUIET | MoveFlags::DOUBLE_PUSH | MoveFlags::CAPTURE => false,
+            MoveFlags::EN_PASSANT => {
+                // The captured pawn can uncover the king too.
+                let captured =
+                    Square((to.to_i8() + if color == Color::White { -8 } else { 8 }) as u8);
+                let occupancy = occupancy ^ BitBoard::new(captured);
+                (movegen::get_rook_attacks(king, occupancy) & (own(3) | own(4))).is_not_empty()
+                    || (movegen::get_bishop_attacks(king, occupancy) & (own(2) | own(4)))
+                        .is_not_empty()
+            }
+            MoveFlags::SHORT_CASTLE | MoveFlags::LONG_CASTLE => {
+                let (rook_from, rook_to) = match (flags, color == Color::White) {
+                    (MoveFlags::SHORT_CASTLE, true) => (square::H1, square::F1),
+                    (MoveFlags::SHORT_CASTLE, false) => (square::H8, square::F8),
+                    (_, true) => (square::A1, square::D1),
+                    (_, false) => (square::A8, square::D8),
+                };
+                let occupancy = occupancy ^ BitBoard::new(rook_from) | BitBoard::new(rook_to);
+                movegen::get_rook_attacks(rook_to, occupancy).is_set(king)
+            }
+            _ => {
+                let attacks = match m.promotion_kind() {
+                    Some(PieceType::Knight) => movegen::get_knight_attacks(to),
+                    Some(PieceType::Bishop) => movegen::get_bishop_attacks(to, occupancy),
+                    Some(PieceType::Rook) => movegen::get_rook_attacks(to, occupancy),
+                    _ => movegen::get_queen_attacks(to, occupancy),
+                };
+                attacks.is_set(king)
+            }
+        }
+    }
+
+    fn king_square(&self, color: Color) -> Option<Square> {
+        let king = self.pieces[Piece::new(color, PieceType::King).to_usize()];
+        king.is_not_empty().then(|| Square(king.bit_scan()))
+    }
+
+    // Pieces alone between `king` and a slider of `enemy`: those which the
+    // slider attacks through.
+    fn blockers(&self, king: Square, enemy: Color) -> BitBoard {
+        let pieces = |kind: usize| self.pieces[enemy.to_usize() * 6 + kind];
+        let occupancy = self.all_occupancy();
+
+        let mut snipers = (movegen::get_rook_xray_attacks(king, occupancy, occupancy)
+            & (pieces(3) | pieces(4)))
+            | (movegen::get_bishop_xray_attacks(king, occupancy, occupancy)
+                & (pieces(2) | pieces(4)));
+        let mut blockers = BitBoard::EMPTY;
+        while snipers.is_not_empty() {
+            let sniper = Square(snipers.bit_scan());
+            snipers = snipers.pop_lsb();
+            blockers |= movegen::between(sniper, king) & occupancy;
+        }
+
+        blockers
+    }
+
+    /// Returns the pieces of both colours which attack `square`, with the
+    /// sliders seeing through everything but `occupancy`.
+    pub fn attackers_to(&self, square: Square, occupancy: BitBoard) -> BitBoard {
+        let pieces = |piece: Piece| self.pieces[piece.to_usize()];
+        let bishops = pieces(Piece::WB) | pieces(Piece::BB) | pieces(Piece::WQ) | pieces(Piece::BQ);
+        let rooks = pieces(Piece::WR) | pieces(Piece::BR) | pieces(Piece::WQ) | pieces(Piece::BQ);
+
+        (movegen::get_pawn_attacks(square, Color::Black) & pieces(Piece::WP))
+            | (movegen::get_pawn_attacks(square, Color::White) & pieces(Piece::BP))
+            | (movegen::get_knight_attacks(square) & (pieces(Piece::WN) | pieces(Piece::BN)))
+            | (movegen::get_bishop_attacks(square, occupancy) & bishops)
+            | (movegen::get_rook_attacks(square, occupancy) & rooks)
+            | (movegen::get_king_attacks(square) & (pieces(Piece::WK) | pieces(Piece::BK)))
+    }
+
+    /// Returns the squares attacked by the pawns of `color`.
+    pub fn pawn_attacks(&self, color: Color) -> BitBoard {
+        let pawns = self.pieces[color.to_usize() * 6];
+        let west = pawns & !BitBoard::FILE_A;
+        let east = pawns & !BitBoard::FILE_H;
+
+        if color == Color::White {
+            (west << 7u8) | (east << 9u8)
+        } else {
+            (west >> 9u8) | (east >> 7u8)
+        }
+    }
+
+    /// Returns the squares attacked by any piece of `color`.
+    pub fn attacks_by(&self, color: Color) -> BitBoard {
+        let occupancy = self.all_occupancy();
+        let mut attacks = self.pawn_attacks(color);
+
+        for kind in 1..6 {
+            let mut pieces = self.pieces[color.to_usize() * 6 + kind];
+            while pieces.is_not_empty() {
+                let square = Square(pieces.bit_scan());
+                pieces = pieces.pop_lsb();
+
+                attacks |= match kind {
+                    1 => movegen::get_knight_attacks(square),
+                    2 => movegen::get_bishop_attacks(square, occupancy),
+                    3 => movegen::get_rook_attacks(square, occupancy),
+                    4 => movegen::get_queen_attacks(square, occupancy),
+                    _ => movegen::get_king_attacks(square),
+                };
+            }
+        }
+
+        attacks
+    }
+
     #[allow(dead_code)]
     pub fn is_king_in_check(&self, color: Color) -> bool {
         assert_eq!(color.enemy(), self.color_to_move);
-        let king_square = if color == Color::WHITE {
+        let king_square = if color == Color::White {
             Square(self.pieces[Piece::WK.to_usize()].bit_scan())
         } else {
             Square(self.pieces[Piece::BK.to_usize()].bit_scan())
@@ -330,9 +725,49 @@ impl Board {
         self.is_sqaure_attacked(king_square, color)
     }
 
+    /// Returns true if the side to move is in check, from `checkers`.
+    #[allow(dead_code)]
+    pub fn is_in_check(&self) -> bool {
+        self.check_info.checkers.is_not_empty()
+    }
+
+    /// Counts the earlier occurrences of the current position since the last
+    /// capture or pawn move.
+    pub fn repetitions(&self) -> usize {
+        self.states
+            .iter()
+            .rev()
+            .take(self.half_move_clock)
+            .skip(1)
+            .step_by(2)
+            .filter(|state| state.hash == self.hash)
+            .count()
+    }
+
+    /// Returns true if neither side can possibly checkmate, which is the case
+    /// with bare kings and with a single minor piece against a bare king.
+    pub fn is_insufficient_material(&self) -> bool {
+        let heavy_pieces = [
+            Piece::WP,
+            Piece::WR,
+            Piece::WQ,
+            Piece::BP,
+            Piece::BR,
+            Piece::BQ,
+        ];
+        if heavy_pieces
+            .iter()
+            .any(|piece| self.pieces[piece.to_usize()].is_not_empty())
+        {
+            return false;
+        }
+
+        self.all_occupancy().pop_count() <= 3
+    }
+
     #[allow(dead_code)]
     pub fn all_occupancy(&self) -> BitBoard {
-        self.occupancy[Color::WHITE.to_usize()] | self.occupancy[Color::BLACK.to_usize()]
+        self.occupancy[Color::White.to_usize()] | self.occupancy[Color::Black.to_usize()]
     }
 
     #[allow(dead_code)]
@@ -354,13 +789,12 @@ impl Board {
     fn can_castle_short_for_color(&self, color: Color) -> bool {
         assert_eq!(color, self.color_to_move);
         match color {
-            Color::WHITE => self
+            Color::White => self
                 .castling_rights
                 .contains(CastlingRights::WHITE_SHORT_CASTLE),
-            Color::BLACK => self
+            Color::Black => self
                 .castling_rights
                 .contains(CastlingRights::BLACK_SHORT_CASTLE),
-            _ => panic!("Invalid color when checking can castle short"),
         }
     }
 
@@ -368,18 +802,23 @@ impl Board {
     fn can_castle_long_for_color(&self, color: Color) -> bool {
         assert_eq!(color, self.color_to_move);
         match color {
-            Color::WHITE => self
+            Color::White => self
                 .castling_rights
                 .contains(CastlingRights::WHITE_LONG_CASTLE),
-            Color::BLACK => self
+            Color::Black => self
                 .castling_rights
                 .contains(CastlingRights::BLACK_LONG_CASTLE),
-            _ => panic!("Invalid color when checking can castle long"),
         }
     }
 
+    /// Attaches `network` to the board and computes its accumulators, which
+    /// are then updated with every piece change. `None` detaches the network.
+    pub fn set_network(&mut self, network: Option<Arc<Network>>) {
+        self.nnue = network.map(|network| Accumulators::new(network, self));
+    }
+
     #[allow(dead_code)]
-    pub fn piece_at_square(&self, sq: Square) -> Piece {
+    pub fn piece_at_square(&self, sq: Square) -> Option<Piece> {
         self.state[sq.to_usize()]
     }
 
@@ -389,7 +828,15 @@ impl Board {
 
         self.pieces[piece.to_usize()] |= BitBoard::new(square);
         self.occupancy[color.to_usize()] |= BitBoard::new(square);
-        self.state[square.to_usize()] = piece;
+        self.state[square.to_usize()] = Some(piece);
+        self.hash ^= zobrist::piece_key(piece, square);
+        if piece == Piece::WP || piece == Piece::BP {
+            self.pawn_hash ^= zobrist::piece_key(piece, square);
+        }
+
+        if let Some(nnue) = &mut self.nnue {
+            nnue.add(piece, square);
+        }
     }
 
     #[allow(dead_code)]
@@ -398,7 +845,15 @@ impl Board {
 
         self.pieces[piece.to_usize()] &= !BitBoard::new(square);
         self.occupancy[color.to_usize()] &= !BitBoard::new(square);
-        self.state[square.to_usize()] = Piece::EMPTY;
+        self.state[square.to_usize()] = None;
+        self.hash ^= zobrist::piece_key(piece, square);
+        if piece == Piece::WP || piece == Piece::BP {
+            self.pawn_hash ^= zobrist::piece_key(piece, square);
+        }
+
+        if let Some(nnue) = &mut self.nnue {
+            nnue.remove(piece, square);
+        }
     }
 
     #[allow(dead_code)]
@@ -407,8 +862,17 @@ impl Board {
 
         self.pieces[piece.to_usize()] ^= BitBoard::new(from) | BitBoard::new(to);
         self.occupancy[color.to_usize()] ^= BitBoard::new(from) | BitBoard::new(to);
-        self.state[to.to_usize()] = piece;
-        self.state[from.to_usize()] = Piece::EMPTY;
+        self.state[to.to_usize()] = Some(piece);
+        self.state[from.to_usize()] = None;
+        self.hash ^= zobrist::piece_key(piece, from) ^ zobrist::piece_key(piece, to);
+        if piece == Piece::WP || piece == Piece::BP {
+            self.pawn_hash ^= zobrist::piece_key(piece, from) ^ zobrist::piece_key(piece, to);
+        }
+
+        if let Some(nnue) = &mut self.nnue {
+            nnue.remove(piece, from);
+            nnue.add(piece, to);
+        }
     }
 
     #[allow(dead_code)]
@@ -424,39 +888,39 @@ impl Board {
     #[allow(dead_code)]
     fn is_sqaure_attacked(&self, square: Square, color: Color) -> bool {
         let enemy = color.enemy();
-        let enemy_rook = if enemy == Color::WHITE {
+        let enemy_rook = if enemy == Color::White {
             Piece::WR.to_usize()
         } else {
             Piece::BR.to_usize()
         };
-        let enemy_bishop = if enemy == Color::WHITE {
+        let enemy_bishop = if enemy == Color::White {
             Piece::WB.to_usize()
         } else {
             Piece::BB.to_usize()
         };
-        let enemy_queen = if enemy == Color::WHITE {
+        let enemy_queen = if enemy == Color::White {
             Piece::WQ.to_usize()
         } else {
             Piece::BQ.to_usize()
         };
-        let enemy_knight = if enemy == Color::WHITE {
+        let enemy_knight = if enemy == Color::White {
             Piece::WN.to_usize()
         } else {
             Piece::BN.to_usize()
         };
-        let enemy_king = if enemy == Color::WHITE {
+        let enemy_king = if enemy == Color::White {
             Piece::WK.to_usize()
         } else {
             Piece::BK.to_usize()
         };
-        let enemy_pawn = if enemy == Color::WHITE {
+        let enemy_pawn = if enemy == Color::White {
             Piece::WP.to_usize()
         } else {
             Piece::BP.to_usize()
         };
 
         let all_occupancy =
-            self.occupancy[Color::WHITE.to_usize()] | self.occupancy[Color::BLACK.to_usize()];
+            self.occupancy[Color::White.to_usize()] | self.occupancy[Color::Black.to_usize()];
 
         if (movegen::get_rook_attacks(square, all_occupancy)
             & (self.pieces[enemy_rook] | self.pieces[enemy_queen]))
@@ -479,18 +943,14 @@ impl Board {
 
         let potential_attacking_pawns = movegen::get_king_attacks(square) & self.pieces[enemy_pawn];
         let attacking_pawns = match enemy {
-            Color::WHITE => {
+            Color::White => {
                 BitBoard::new(square)
                     & ((potential_attacking_pawns << 7u8) | (potential_attacking_pawns << 9u8))
             }
-            Color::BLACK => {
+            Color::Black => {
                 BitBoard::new(square)
                     & ((potential_attacking_pawns >> 7u8) | (potential_attacking_pawns >> 9u8))
             }
-            _ => panic!(
-                "Invalid color when checking attacked squares, color : {}",
-                color
-            ),
         };
 
         if attacking_pawns.is_not_empty() {
@@ -509,19 +969,10 @@ impl fmt::Display for Board {
             grid_to_string(
                 |square: Square| -> &str {
                     match self.piece_at_square(square) {
-                        Piece::WP => "♙ ",
-                        Piece::WN => "♘ ",
-                        Piece::WB => "♗ ",
-                        Piece::WR => "♖ ",
-                        Piece::WQ => "♕ ",
-                        Piece::WK => "♔ ",
-                        Piece::BP => "♟ ",
-                        Piece::BN => "♞ ",
-                        Piece::BB => "♝ ",
-                        Piece::BR => "♜ ",
-                        Piece::BQ => "♛ ",
-                        Piece::BK => "♚ ",
-                        _ => ". ",
+                        Some(piece) => [
+                            "♙ ", "♘ ", "♗ ", "♖ ", "♕ ", "♔ ", "♟ ", "♞ ", "♝ ", "♜ ", "♛ ", "♚ ",
+                        ][piece.to_usize()],
+                        None => ". ",
                     }
                 },
                 None
@@ -544,40 +995,38 @@ bitflags! {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Color(u8);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    White,
+    Black,
+}
 
 impl Color {
-    #[allow(dead_code)]
-    pub const WHITE: Color = Color(0);
-    #[allow(dead_code)]
-    pub const BLACK: Color = Color(1);
+    pub const ALL: [Color; 2] = [Color::White, Color::Black];
 
     #[allow(dead_code)]
     pub fn to_u8(self) -> u8 {
-        self.0
+        self as u8
     }
 
     #[allow(dead_code)]
     pub fn to_usize(self) -> usize {
-        self.0 as usize
+        self as usize
     }
 
     #[allow(dead_code)]
     pub fn enemy(self) -> Color {
-        if self.0 == 0 {
-            Color::BLACK
-        } else {
-            Color::WHITE
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
         }
     }
 
     #[allow(dead_code)]
     pub fn as_string(&self) -> &str {
-        match self.0 {
-            0 => "White",
-            1 => "Black",
-            _ => "Invalid Color",
+        match self {
+            Color::White => "White",
+            Color::Black => "Black",
         }
     }
 }
@@ -588,73 +1037,124 @@ impl fmt::Display for Color {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Piece(u8);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PieceType {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl PieceType {
+    pub const ALL: [PieceType; 6] = [
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+        PieceType::King,
+    ];
+
+    pub fn to_usize(self) -> usize {
+        self as usize
+    }
+
+    pub fn as_string(&self) -> &str {
+        match self {
+            PieceType::Pawn => "Pawn",
+            PieceType::Knight => "Knight",
+            PieceType::Bishop => "Bishop",
+            PieceType::Rook => "Rook",
+            PieceType::Queen => "Queen",
+            PieceType::King => "King",
+        }
+    }
+
+    /// Returns the letter of the kind in algebraic notation, `P` for pawns.
+    pub fn to_char(self) -> char {
+        ['P', 'N', 'B', 'R', 'Q', 'K'][self.to_usize()]
+    }
+}
+
+/// A piece of a colour. Its index, from 0 for the white pawn to 11 for the
+/// black king, is the one of its bitboard in `Board::pieces`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Piece {
+    color: Color,
+    kind: PieceType,
+}
 
 impl Piece {
-    #[allow(dead_code)]
-    pub const WP: Piece = Piece(0);
-    #[allow(dead_code)]
-    pub const WN: Piece = Piece(1);
-    #[allow(dead_code)]
-    pub const WB: Piece = Piece(2);
-    #[allow(dead_code)]
-    pub const WR: Piece = Piece(3);
-    #[allow(dead_code)]
-    pub const WQ: Piece = Piece(4);
-    #[allow(dead_code)]
-    pub const WK: Piece = Piece(5);
-    #[allow(dead_code)]
-    pub const BP: Piece = Piece(6);
-    #[allow(dead_code)]
-    pub const BN: Piece = Piece(7);
-    #[allow(dead_code)]
-    pub const BB: Piece = Piece(8);
-    #[allow(dead_code)]
-    pub const BR: Piece = Piece(9);
-    #[allow(dead_code)]
-    pub const BQ: Piece = Piece(10);
-    #[allow(dead_code)]
-    pub const BK: Piece = Piece(11);
-    #[allow(dead_code)]
-    pub const EMPTY: Piece = Piece(u8::MAX);
+    pub const WP: Piece = Piece::new(Color::White, PieceType::Pawn);
+    pub const WN: Piece = Piece::new(Color::White, PieceType::Knight);
+    pub const WB: Piece = Piece::new(Color::White, PieceType::Bishop);
+    pub const WR: Piece = Piece::new(Color::White, PieceType::Rook);
+    pub const WQ: Piece = Piece::new(Color::White, PieceType::Queen);
+    pub const WK: Piece = Piece::new(Color::White, PieceType::King);
+    pub const BP: Piece = Piece::new(Color::Black, PieceType::Pawn);
+    pub const BN: Piece = Piece::new(Color::Black, PieceType::Knight);
+    pub const BB: Piece = Piece::new(Color::Black, PieceType::Bishop);
+    pub const BR: Piece = Piece::new(Color::Black, PieceType::Rook);
+    pub const BQ: Piece = Piece::new(Color::Black, PieceType::Queen);
+    pub const BK: Piece = Piece::new(Color::Black, PieceType::King);
+
+    /// Every piece in the order of their indices.
+    pub const ALL: [Piece; 12] = [
+        Piece::WP,
+        Piece::WN,
+        Piece::WB,
+        Piece::WR,
+        Piece::WQ,
+        Piece::WK,
+        Piece::BP,
+        Piece::BN,
+        Piece::BB,
+        Piece::BR,
+        Piece::BQ,
+        Piece::BK,
+    ];
+
+    pub const fn new(color: Color, kind: PieceType) -> Self {
+        Piece { color, kind }
+    }
+
+    /// Returns the piece of index `index`, see `to_usize`.
+    pub fn from_index(index: usize) -> Option<Piece> {
+        Piece::ALL.get(index).copied()
+    }
 
     #[allow(dead_code)]
     pub fn to_u8(self) -> u8 {
-        self.0
+        self.to_usize() as u8
     }
 
     #[allow(dead_code)]
     pub fn to_usize(self) -> usize {
-        self.0 as usize
+        self.color.to_usize() * 6 + self.kind.to_usize()
     }
 
     #[allow(dead_code)]
     pub fn color(self) -> Color {
-        if (0..=5).contains(&self.0) {
-            Color::WHITE
-        } else {
-            Color::BLACK
+        self.color
+    }
+
+    pub fn kind(self) -> PieceType {
+        self.kind
+    }
+
+    /// Returns the letter of the piece in FEN, lowercase for black pieces.
+    pub fn to_char(self) -> char {
+        match self.color {
+            Color::White => self.kind.to_char(),
+            Color::Black => self.kind.to_char().to_ascii_lowercase(),
         }
     }
 
     #[allow(dead_code)]
-    pub fn as_string(&self) -> &str {
-        match self.0 {
-            0 => "White Pawn",
-            1 => "White Knight",
-            2 => "White Bishop",
-            3 => "White Rook",
-            4 => "White Queen",
-            5 => "White King",
-            6 => "Black Pawn",
-            7 => "Black Knight",
-            8 => "Black Bishop",
-            9 => "Black Rook",
-            10 => "Black Queen",
-            11 => "Black King",
-            _ => "Empty Sqaure",
-        }
+    pub fn as_string(&self) -> String {
+        format!("{} {}", self.color, self.kind.as_string())
     }
 }
 
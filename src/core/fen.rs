@@ -1,6 +1,5 @@
 use super::board::*;
-use super::square;
-use super::{BitBoard, Square};
+use super::{movegen, square, BitBoard, Square};
 
 #[allow(dead_code)]
 pub fn fen_to_board(fen: &str) -> Result<Board, &'static str> {
@@ -16,6 +15,18 @@ pub fn fen_to_board(fen: &str) -> Result<Board, &'static str> {
     add_en_passant_from_fen(&mut board, slices[3].trim())?;
     add_half_move_clock_from_fen(&mut board, slices[4].trim())?;
     add_full_move_number_from_fen(&mut board, slices[5].trim())?;
+    board.hash = board.compute_hash();
+    movegen::init();
+    board.update_check_info();
+
+    Ok(board)
+}
+
+/// Converts `fen` to a board like `fen_to_board`, and also rejects positions
+/// which cannot arise in a game, with every reason why.
+pub fn fen_to_validated_board(fen: &str) -> Result<Board, Vec<String>> {
+    let board = fen_to_board(fen).map_err(|e| vec![e.to_string()])?;
+    board.validate()?;
 
     Ok(board)
 }
@@ -57,9 +68,9 @@ fn add_pieces_from_fen(board: &mut Board, fen_str: &str) -> Result<(), &'static
 #[allow(dead_code)]
 fn add_color_to_move_from_fen(board: &mut Board, fen_str: &str) -> Result<(), &'static str> {
     board.color_to_move = if fen_str.starts_with('w') {
-        Color::WHITE
+        Color::White
     } else {
-        Color::BLACK
+        Color::Black
     };
     Ok(())
 }
@@ -146,34 +157,15 @@ fn pieces_to_fen(board: &Board) -> String {
     for rank in (0..8).rev() {
         for file in 0..8 {
             let square = Square::from(file, rank).unwrap();
-            let piece = board.piece_at_square(square);
-
-            if piece == Piece::EMPTY {
-                empty_squares += 1;
-            } else {
+            if let Some(piece) = board.piece_at_square(square) {
                 if empty_squares != 0 {
                     string.push(char::from_digit(empty_squares, 10).unwrap());
                     empty_squares = 0;
                 }
 
-                string.push(match piece {
-                    Piece::WP => 'P',
-                    Piece::WN => 'N',
-                    Piece::WB => 'B',
-                    Piece::WR => 'R',
-                    Piece::WQ => 'Q',
-                    Piece::WK => 'K',
-                    Piece::BP => 'p',
-                    Piece::BN => 'n',
-                    Piece::BB => 'b',
-                    Piece::BR => 'r',
-                    Piece::BQ => 'q',
-                    Piece::BK => 'k',
-                    _ => panic!(
-                        "Invalid piece when converting board to fen, piece : {}",
-                        piece
-                    ),
-                });
+                string.push(piece.to_char());
+            } else {
+                empty_squares += 1;
             }
 
             if square.to_u8() % 8 == 7 {
@@ -194,12 +186,8 @@ fn pieces_to_fen(board: &Board) -> String {
 #[allow(dead_code)]
 fn color_to_move_to_fen(board: &Board) -> String {
     match board.color_to_move {
-        Color::WHITE => "w".to_string(),
-        Color::BLACK => "b".to_string(),
-        _ => panic!(
-            "Invalid color to move when converting board to fen, color : {}",
-            board.color_to_move
-        ),
+        Color::White => "w".to_string(),
+        Color::Black => "b".to_string(),
     }
 }
 
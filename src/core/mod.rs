@@ -2,10 +2,15 @@ pub mod bitboard;
 pub mod board;
 pub mod fen;
 pub mod movegen;
+pub mod pgn;
+pub mod position;
 pub mod square;
 mod utils;
+pub mod validate;
+pub mod zobrist;
 
 pub use bitboard::BitBoard;
-pub use board::{Board, Color, Piece};
-pub use movegen::{Move, MoveFlags};
-pub use square::Square;
+pub use board::{Board, CheckInfo, Color, Piece, PieceType};
+pub use movegen::{Move, MoveFlags, Unmove};
+pub use position::Position;
+pub use square::{File, Rank, Square};
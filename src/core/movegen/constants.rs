@@ -156,6 +156,7 @@ pub const BISHOP_MAGIC_NUMBERS: [u64; 64] = [
     0x0010420214002208,
 ];
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     North,
     NorthEast,
@@ -166,3 +167,24 @@ pub enum Direction {
     West,
     NorthWest,
 }
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    pub fn to_usize(self) -> usize {
+        self as usize
+    }
+
+    pub fn opposite(self) -> Direction {
+        Direction::ALL[(self.to_usize() + 4) % 8]
+    }
+}
@@ -0,0 +1,55 @@
+use super::constants::Direction;
+use super::sliding::generate_slider_attacks_in_direction;
+use crate::core::{BitBoard, Square};
+
+static mut RAY_TABLE: [[BitBoard; 64]; 8] = [[BitBoard::EMPTY; 64]; 8];
+static mut BETWEEN_TABLE: [[BitBoard; 64]; 64] = [[BitBoard::EMPTY; 64]; 64];
+static mut LINE_TABLE: [[BitBoard; 64]; 64] = [[BitBoard::EMPTY; 64]; 64];
+
+pub fn init_lines() {
+    unsafe {
+        for index in 0..64 {
+            for direction in Direction::ALL {
+                RAY_TABLE[direction.to_usize()][index as usize] =
+                    generate_slider_attacks_in_direction(Square(index), BitBoard::EMPTY, direction);
+            }
+        }
+
+        for a in 0..64 {
+            for direction in Direction::ALL {
+                let ray = RAY_TABLE[direction.to_usize()][a];
+                let line = ray
+                    | RAY_TABLE[direction.opposite().to_usize()][a]
+                    | BitBoard::new(Square(a as u8));
+
+                let mut squares = ray;
+                while squares.is_not_empty() {
+                    let b = squares.bit_scan() as usize;
+                    squares = squares.pop_lsb();
+
+                    BETWEEN_TABLE[a][b] =
+                        ray & !RAY_TABLE[direction.to_usize()][b] & !BitBoard::new(Square(b as u8));
+                    LINE_TABLE[a][b] = line;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the squares from `sq` to the edge of the board in `direction`,
+/// without `sq`.
+pub fn ray(sq: Square, direction: Direction) -> BitBoard {
+    unsafe { RAY_TABLE[direction.to_usize()][sq.to_usize()] }
+}
+
+/// Returns the squares strictly between `a` and `b` if they are on the same
+/// rank, file or diagonal, and no squares otherwise.
+pub fn between(a: Square, b: Square) -> BitBoard {
+    unsafe { BETWEEN_TABLE[a.to_usize()][b.to_usize()] }
+}
+
+/// Returns the whole rank, file or diagonal through `a` and `b`, edge to
+/// edge, or no squares if they are not on one or are the same square.
+pub fn line(a: Square, b: Square) -> BitBoard {
+    unsafe { LINE_TABLE[a.to_usize()][b.to_usize()] }
+}
@@ -27,8 +27,8 @@ fn find_magic_for_bishop_at_sqaure(sq: Square) -> BitBoard {
     let count = (1 << shift) as usize;
     let mask = movegen::generate_relevant_occupancy_mask_at_square(sq, true);
 
-    let mut relevant_occupancies = Vec::with_capacity(count as usize);
-    let mut attacks = Vec::with_capacity(count as usize);
+    let mut relevant_occupancies = Vec::with_capacity(count);
+    let mut attacks = Vec::with_capacity(count);
 
     for index in 0..count {
         let occupancy = movegen::generate_occupancy(mask, index);
@@ -46,8 +46,8 @@ fn find_magic_for_rook_at_sqaure(sq: Square) -> BitBoard {
     let count = (1 << shift) as usize;
     let mask = movegen::generate_relevant_occupancy_mask_at_square(sq, false);
 
-    let mut relevant_occupancies = Vec::with_capacity(count as usize);
-    let mut attacks = Vec::with_capacity(count as usize);
+    let mut relevant_occupancies = Vec::with_capacity(count);
+    let mut attacks = Vec::with_capacity(count);
 
     for index in 0..count {
         let occupancy = movegen::generate_occupancy(mask, index);
@@ -1,15 +1,29 @@
 mod constants;
+pub mod lines;
 pub mod magic;
 pub mod r#move;
 pub mod movescan;
 pub mod non_sliding;
 pub mod sliding;
+pub mod unmove;
 
+pub use constants::Direction;
+pub use lines::*;
 pub use non_sliding::*;
 pub use r#move::{Move, MoveFlags};
 pub use sliding::*;
+pub use unmove::Unmove;
 
+use std::sync::Once;
+
+static INIT: Once = Once::new();
+
+/// Fills the attack tables. They are only written here, so once this returns
+/// they can be read from any number of search threads.
 pub fn init() {
-    init_magic();
-    init_non_sliding_attacks();
+    INIT.call_once(|| {
+        init_magic();
+        init_non_sliding_attacks();
+        init_lines();
+    });
 }
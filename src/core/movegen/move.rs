@@ -1,12 +1,9 @@
-#![allow(clippy::uninit_assumed_init)]
-
 use bitflags::bitflags;
 use std::fmt;
-use std::mem::MaybeUninit;
 
-use crate::core::{Board, Piece, Square};
+use crate::core::{Board, Color, Piece, PieceType, Square};
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Move(pub u16);
 
 impl Move {
@@ -21,14 +18,22 @@ impl Move {
             return Err("Invalid move: Move string is too short");
         }
 
-        let from = Square::from_string(&string[0..2]).unwrap();
-        let to = Square::from_string(&string[2..4]).unwrap();
+        let square = |range| {
+            string
+                .get(range)
+                .and_then(Square::from_string)
+                .ok_or("Invalid move: Bad square")
+        };
+        let from = square(0..2)?;
+        let to = square(2..4)?;
 
-        let mut moves: [Move; 218] = unsafe { MaybeUninit::uninit().assume_init() };
+        let mut moves = [Move(0); 218];
         let moves_count = board.get_moves(&mut moves);
 
+        let promotion = string[4..].trim().chars().next();
+
         for m in moves.iter().take(moves_count) {
-            if m.from() == from && m.to() == to {
+            if m.from() == from && m.to() == to && m.promotion_char() == promotion {
                 return Ok(*m);
             }
         }
@@ -36,6 +41,190 @@ impl Move {
         Err("Invalid move: Move not found in position")
     }
 
+    /// Parses a move in standard algebraic notation, such as `Nbd7`, `exd6`,
+    /// `e8=Q+` or `O-O`.
+    pub fn from_san(string: &str, board: &Board) -> Result<Move, &'static str> {
+        let san = string.trim_end_matches(['+', '#', '!', '?']);
+
+        let mut moves = [Move(0); 218];
+        let moves_count = board.get_moves(&mut moves);
+        let moves = &moves[..moves_count];
+
+        if san == "O-O" || san == "0-0" || san == "O-O-O" || san == "0-0-0" {
+            let flags = if san.len() == 3 {
+                MoveFlags::SHORT_CASTLE
+            } else {
+                MoveFlags::LONG_CASTLE
+            };
+
+            return moves
+                .iter()
+                .find(|m| m.flags() == flags)
+                .copied()
+                .ok_or("Invalid move: Castling is not possible");
+        }
+
+        let (san, promotion) = match san.find('=') {
+            Some(index) => (&san[..index], san[index + 1..].chars().next()),
+            None => match san.chars().last() {
+                Some(c @ ('N' | 'B' | 'R' | 'Q')) => (&san[..san.len() - 1], Some(c)),
+                _ => (san, None),
+            },
+        };
+        let promotion = promotion.map(|c| c.to_ascii_lowercase());
+
+        let (kind, san) = match san.chars().next() {
+            Some(c @ ('N' | 'B' | 'R' | 'Q' | 'K')) => (
+                *PieceType::ALL
+                    .iter()
+                    .find(|kind| kind.to_char() == c)
+                    .unwrap(),
+                &san[1..],
+            ),
+            _ => (PieceType::Pawn, san),
+        };
+
+        let san = san.replace('x', "");
+        if san.len() < 2 || !san.is_ascii() {
+            return Err("Invalid move: Move string is too short");
+        }
+        let to = Square::from_string(&san[san.len() - 2..]).ok_or("Invalid move: Bad square")?;
+        let disambiguation = &san[..san.len() - 2];
+
+        let mut candidates = moves.iter().filter(|m| {
+            m.to() == to
+                && board.piece_at_square(m.from()).map(Piece::kind) == Some(kind)
+                && m.promotion_char() == promotion
+                && disambiguation.chars().all(|c| match c {
+                    'a'..='h' => m.from().file() == c as u8 - b'a',
+                    '1'..='8' => m.from().rank() == c as u8 - b'1',
+                    _ => false,
+                })
+        });
+
+        // Only check legality when the notation leaves a choice, since it
+        // needs a copy of the board.
+        let first = *candidates
+            .next()
+            .ok_or("Invalid move: Move not found in position")?;
+        let rest: Vec<Move> = candidates.copied().collect();
+        if rest.is_empty() {
+            return Ok(first);
+        }
+
+        let mut board = board.clone();
+        let color = board.color_to_move;
+        let mut legal = std::iter::once(first).chain(rest).filter(|m| {
+            board.make_move(m);
+            let is_legal = !board.is_king_in_check(color);
+            board.undo_move(m);
+
+            is_legal
+        });
+
+        match (legal.next(), legal.next()) {
+            (Some(m), None) => Ok(m),
+            (Some(_), Some(_)) => Err("Invalid move: Move is ambiguous"),
+            _ => Err("Invalid move: Move not found in position"),
+        }
+    }
+
+    /// Returns the move in standard algebraic notation. The move must be legal
+    /// in `board`.
+    pub fn to_san(&self, board: &Board) -> String {
+        let mut board = board.clone();
+        let color = board.color_to_move;
+        let piece = board
+            .piece_at_square(self.from())
+            .expect("No piece to move");
+        let kind = piece.kind();
+
+        let mut san = match self.flags() {
+            MoveFlags::SHORT_CASTLE => "O-O".to_string(),
+            MoveFlags::LONG_CASTLE => "O-O-O".to_string(),
+            _ if kind == PieceType::Pawn => {
+                let mut san = String::new();
+                if self.is_capture() {
+                    san.push((b'a' + self.from().file()) as char);
+                    san.push('x');
+                }
+                san.push_str(self.to().as_string());
+                if let Some(promotion) = self.promotion_char() {
+                    san.push('=');
+                    san.push(promotion.to_ascii_uppercase());
+                }
+
+                san
+            }
+            _ => {
+                let mut san = kind.to_char().to_string();
+
+                // Other legal moves of the same kind of piece to the same
+                // square have to be told apart by file, rank or both.
+                let mut moves = [Move(0); 218];
+                let moves_count = board.get_moves(&mut moves);
+                let others: Vec<Move> = moves[..moves_count]
+                    .iter()
+                    .filter(|m| {
+                        m.to() == self.to()
+                            && m.from() != self.from()
+                            && board.piece_at_square(m.from()) == Some(piece)
+                    })
+                    .copied()
+                    .collect();
+                let others: Vec<Move> = others
+                    .into_iter()
+                    .filter(|m| {
+                        board.make_move(m);
+                        let is_legal = !board.is_king_in_check(color);
+                        board.undo_move(m);
+
+                        is_legal
+                    })
+                    .collect();
+
+                if !others.is_empty() {
+                    let file = (b'a' + self.from().file()) as char;
+                    let rank = (b'1' + self.from().rank()) as char;
+
+                    if others.iter().all(|m| m.from().file() != self.from().file()) {
+                        san.push(file);
+                    } else if others.iter().all(|m| m.from().rank() != self.from().rank()) {
+                        san.push(rank);
+                    } else {
+                        san.push(file);
+                        san.push(rank);
+                    }
+                }
+
+                if self.is_capture() {
+                    san.push('x');
+                }
+                san.push_str(self.to().as_string());
+
+                san
+            }
+        };
+
+        board.make_move(self);
+        if board.is_in_check() {
+            let mut moves = [Move(0); 218];
+            let moves_count = board.get_moves(&mut moves);
+            let enemy = board.color_to_move;
+            let has_legal_move = moves[..moves_count].iter().any(|m| {
+                board.make_move(m);
+                let is_legal = !board.is_king_in_check(enemy);
+                board.undo_move(m);
+
+                is_legal
+            });
+
+            san.push(if has_legal_move { '+' } else { '#' });
+        }
+
+        san
+    }
+
     #[allow(dead_code)]
     pub fn from(&self) -> Square {
         Square((self.0 & 63) as u8)
@@ -52,48 +241,58 @@ impl Move {
     }
 
     #[allow(dead_code)]
-    pub fn promotion(&self, is_white: bool) -> Piece {
+    pub fn is_capture(&self) -> bool {
+        self.flags().contains(MoveFlags::CAPTURE)
+    }
+
+    #[allow(dead_code)]
+    pub fn is_promotion(&self) -> bool {
+        self.flags().contains(MoveFlags::PROMOTE_TO_KNIGHT)
+    }
+
+    /// Returns the kind of piece the move promotes to, `None` if it is not
+    /// a promotion.
+    pub fn promotion_kind(&self) -> Option<PieceType> {
         match self.flags() {
             MoveFlags::PROMOTE_TO_KNIGHT | MoveFlags::PROMOTE_CAPTURE_TO_KNIGHT => {
-                if is_white {
-                    Piece::WN
-                } else {
-                    Piece::BN
-                }
+                Some(PieceType::Knight)
             }
             MoveFlags::PROMOTE_TO_BISHOP | MoveFlags::PROMOTE_CAPTURE_TO_BISHOP => {
-                if is_white {
-                    Piece::WB
-                } else {
-                    Piece::BB
-                }
+                Some(PieceType::Bishop)
             }
             MoveFlags::PROMOTE_TO_ROOK | MoveFlags::PROMOTE_CAPTURE_TO_ROOK => {
-                if is_white {
-                    Piece::WR
-                } else {
-                    Piece::BR
-                }
+                Some(PieceType::Rook)
             }
             MoveFlags::PROMOTE_TO_QUEEN | MoveFlags::PROMOTE_CAPTURE_TO_QUEEN => {
-                if is_white {
-                    Piece::WQ
-                } else {
-                    Piece::BQ
-                }
+                Some(PieceType::Queen)
             }
-            _ => panic!("Move is not promotion, flag : {:?}", self.flags()),
+            _ => None,
         }
     }
 
+    /// Returns the piece of `color` the move promotes to, `None` if it is not
+    /// a promotion.
+    pub fn promotion(&self, color: Color) -> Option<Piece> {
+        self.promotion_kind().map(|kind| Piece::new(color, kind))
+    }
+
+    #[allow(dead_code)]
+    pub fn promotion_char(&self) -> Option<char> {
+        self.promotion_kind()
+            .map(|kind| kind.to_char().to_ascii_lowercase())
+    }
+
     #[allow(dead_code)]
     pub fn as_string(&self) -> String {
         let from = self.from();
         let to = self.to();
 
-        let string = vec![from.as_string(), to.as_string()];
+        let mut string: String = vec![from.as_string(), to.as_string()].into_iter().collect();
+        if let Some(promotion) = self.promotion_char() {
+            string.push(promotion);
+        }
 
-        string.into_iter().collect()
+        string
     }
 }
 
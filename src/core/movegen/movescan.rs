@@ -1,6 +1,6 @@
 use super::*;
 use crate::core::board::*;
-use crate::core::{square, BitBoard, Square};
+use crate::core::{square, BitBoard};
 
 pub fn scan_piece_moves(
     board: &Board,
@@ -8,30 +8,22 @@ pub fn scan_piece_moves(
     moves: &mut [Move],
     mut index: usize,
 ) -> usize {
-    let mut piece_bb = board.pieces[piece.to_usize()];
     let enemy = piece.color().enemy();
 
-    while piece_bb.is_not_empty() {
-        let from_bb = piece_bb.lsb();
-        let from_square = Square(from_bb.bit_scan());
-        piece_bb = piece_bb.pop_lsb();
-
+    for from_square in board.pieces[piece.to_usize()] {
         let all_occupancy = board.all_occupancy();
-        let mut piece_moves = match piece {
-            Piece::WN | Piece::BN => get_knight_attacks(from_square),
-            Piece::WB | Piece::BB => get_bishop_attacks(from_square, all_occupancy),
-            Piece::WR | Piece::BR => get_rook_attacks(from_square, all_occupancy),
-            Piece::WQ | Piece::BQ => get_queen_attacks(from_square, all_occupancy),
-            Piece::WK | Piece::BK => get_king_attacks(from_square),
-            _ => panic!("Invalid piece when getting moves, piece : {}", piece),
+        let piece_moves = match piece.kind() {
+            // Pawn moves are scanned separately.
+            PieceType::Pawn => BitBoard::EMPTY,
+            PieceType::Knight => get_knight_attacks(from_square),
+            PieceType::Bishop => get_bishop_attacks(from_square, all_occupancy),
+            PieceType::Rook => get_rook_attacks(from_square, all_occupancy),
+            PieceType::Queen => get_queen_attacks(from_square, all_occupancy),
+            PieceType::King => get_king_attacks(from_square),
         } & !board.occupancy[piece.color().to_usize()];
 
-        while piece_moves.is_not_empty() {
-            let to_bb = piece_moves.lsb();
-            let to_square = Square(to_bb.bit_scan());
-            piece_moves = piece_moves.pop_lsb();
-
-            let is_capture = (to_bb & board.occupancy[enemy.to_usize()]).is_not_empty();
+        for to_square in piece_moves {
+            let is_capture = board.occupancy[enemy.to_usize()].is_set(to_square);
             let flags = if is_capture {
                 MoveFlags::CAPTURE
             } else {
@@ -44,23 +36,23 @@ pub fn scan_piece_moves(
 
         match piece {
             Piece::WK => {
-                if is_king_side_castle_possible(board, Color::WHITE) {
-                    moves[index] = Move::new(square::E1, square::F1, MoveFlags::SHORT_CASTLE);
+                if is_king_side_castle_possible(board, Color::White) {
+                    moves[index] = Move::new(square::E1, square::G1, MoveFlags::SHORT_CASTLE);
                     index += 1;
                 }
 
-                if is_queen_side_castle_possible(board, Color::WHITE) {
+                if is_queen_side_castle_possible(board, Color::White) {
                     moves[index] = Move::new(square::E1, square::C1, MoveFlags::LONG_CASTLE);
                     index += 1;
                 }
             }
             Piece::BK => {
-                if is_king_side_castle_possible(board, Color::BLACK) {
-                    moves[index] = Move::new(square::E8, square::F8, MoveFlags::SHORT_CASTLE);
+                if is_king_side_castle_possible(board, Color::Black) {
+                    moves[index] = Move::new(square::E8, square::G8, MoveFlags::SHORT_CASTLE);
                     index += 1;
                 }
 
-                if is_queen_side_castle_possible(board, Color::BLACK) {
+                if is_queen_side_castle_possible(board, Color::Black) {
                     moves[index] = Move::new(square::E8, square::C8, MoveFlags::LONG_CASTLE);
                     index += 1;
                 }
@@ -87,7 +79,7 @@ pub fn scan_pawn_single_push(
     moves: &mut [Move],
     mut index: usize,
 ) -> usize {
-    let is_white = color == Color::WHITE;
+    let is_white = color == Color::White;
     let all_occupancy = board.all_occupancy();
 
     let piece_bb = if is_white {
@@ -102,26 +94,21 @@ pub fn scan_pawn_single_push(
         BitBoard::RANK_1
     };
 
-    let mut pawn_moves = match color {
-        Color::WHITE => piece_bb << 8u8,
-        Color::BLACK => piece_bb >> 8u8,
-        _ => panic!(
-            "Invalid color when getting pawn single push moves, color : {}",
-            color
-        ),
-    } & !all_occupancy;
-
-    while pawn_moves.is_not_empty() {
-        let to_bb = pawn_moves.lsb();
-        let to_square = Square(to_bb.bit_scan());
+    let forward = if is_white {
+        Direction::North
+    } else {
+        Direction::South
+    };
+    let pawn_moves = piece_bb.shift(forward) & !all_occupancy;
+
+    for to_square in pawn_moves {
         let from_square = if is_white {
             to_square.south().unwrap()
         } else {
             to_square.north().unwrap()
         };
-        pawn_moves = pawn_moves.pop_lsb();
 
-        if (to_bb & enemy_backrank).is_not_empty() {
+        if enemy_backrank.is_set(to_square) {
             moves[index] = Move::new(from_square, to_square, MoveFlags::PROMOTE_TO_KNIGHT);
             index += 1;
             moves[index] = Move::new(from_square, to_square, MoveFlags::PROMOTE_TO_BISHOP);
@@ -145,33 +132,29 @@ pub fn scan_pawn_double_push(
     moves: &mut [Move],
     mut index: usize,
 ) -> usize {
-    let is_white = color == Color::WHITE;
+    let is_white = color == Color::White;
     let all_occupancy = board.all_occupancy();
-    
+
     let piece_bb = if is_white {
         board.pieces[Piece::WP.to_usize()]
     } else {
         board.pieces[Piece::BP.to_usize()]
     };
 
-    let mut pawn_moves = match color {
-        Color::WHITE => (((piece_bb & BitBoard::RANK_2) << 8u8) & !all_occupancy) << 8u8,
-        Color::BLACK => (((piece_bb & BitBoard::RANK_7) >> 8u8) & !all_occupancy) >> 8u8,
-        _ => panic!(
-            "Invalid color when getting pawn double push moves, color : {}",
-            color
-        ),
-    } & !all_occupancy;
-
-    while pawn_moves.is_not_empty() {
-        let to_bb = pawn_moves.lsb();
-        let to_square = Square(to_bb.bit_scan());
+    let (forward, start_rank) = if is_white {
+        (Direction::North, BitBoard::RANK_2)
+    } else {
+        (Direction::South, BitBoard::RANK_7)
+    };
+    let pawn_moves =
+        ((piece_bb & start_rank).shift(forward) & !all_occupancy).shift(forward) & !all_occupancy;
+
+    for to_square in pawn_moves {
         let from_square = if is_white {
             to_square.south().unwrap().south().unwrap()
         } else {
             to_square.north().unwrap().north().unwrap()
         };
-        pawn_moves = pawn_moves.pop_lsb();
 
         moves[index] = Move::new(from_square, to_square, MoveFlags::DOUBLE_PUSH);
         index += 1;
@@ -187,18 +170,18 @@ pub fn scan_pawn_diagonal_attacks(
     moves: &mut [Move],
     mut index: usize,
 ) -> usize {
-    let is_white = color == Color::WHITE;
+    let is_white = color == Color::White;
     let piece_bb = if is_white {
         board.pieces[Piece::WP.to_usize()]
     } else {
         board.pieces[Piece::BP.to_usize()]
     };
 
-    let shift = if is_left ^ is_white { 9 } else { 7 };
-    let not_on_file = if is_left {
-        !BitBoard::FILE_A
-    } else {
-        !BitBoard::FILE_H
+    let direction = match (is_white, is_left) {
+        (true, true) => Direction::NorthWest,
+        (true, false) => Direction::NorthEast,
+        (false, true) => Direction::SouthWest,
+        (false, false) => Direction::SouthEast,
     };
 
     let enemy_backrank = if is_white {
@@ -207,18 +190,10 @@ pub fn scan_pawn_diagonal_attacks(
         BitBoard::RANK_1
     };
 
-    let mut pawn_moves = match color {
-        Color::WHITE => (piece_bb & not_on_file) << (shift as u8),
-        Color::BLACK => (piece_bb & not_on_file) >> (shift as u8),
-        _ => panic!(
-            "Invalid color when getting pawn diagonal attacks, color : {}",
-            color
-        ),
-    } & (board.occupancy[color.enemy().to_usize()] | board.en_passant);
-
-    while pawn_moves.is_not_empty() {
-        let to_bb = pawn_moves.lsb();
-        let to_square = Square(to_bb.bit_scan());
+    let pawn_moves =
+        piece_bb.shift(direction) & (board.occupancy[color.enemy().to_usize()] | board.en_passant);
+
+    for to_square in pawn_moves {
         let from_square = if is_white && is_left {
             to_square.south_east().unwrap()
         } else if is_white && !is_left {
@@ -228,9 +203,8 @@ pub fn scan_pawn_diagonal_attacks(
         } else {
             to_square.north_west().unwrap()
         };
-        pawn_moves = pawn_moves.pop_lsb();
 
-        if (to_bb & enemy_backrank).is_not_empty() {
+        if enemy_backrank.is_set(to_square) {
             moves[index] = Move::new(from_square, to_square, MoveFlags::PROMOTE_CAPTURE_TO_KNIGHT);
             index += 1;
             moves[index] = Move::new(from_square, to_square, MoveFlags::PROMOTE_CAPTURE_TO_BISHOP);
@@ -240,7 +214,7 @@ pub fn scan_pawn_diagonal_attacks(
             moves[index] = Move::new(from_square, to_square, MoveFlags::PROMOTE_CAPTURE_TO_QUEEN);
             index += 1;
         } else {
-            let is_en_passant = (to_bb & board.en_passant).is_not_empty();
+            let is_en_passant = board.en_passant.is_set(to_square);
 
             moves[index] = Move::new(
                 from_square,
@@ -259,7 +233,7 @@ pub fn scan_pawn_diagonal_attacks(
 }
 
 fn is_king_side_castle_possible(board: &Board, color: Color) -> bool {
-    let is_white = color == Color::WHITE;
+    let is_white = color == Color::White;
     let can_short_castle = board.can_castle_short();
     let is_king_side_clear = board.are_squares_empty(if is_white {
         &[square::F1, square::G1]
@@ -267,21 +241,21 @@ fn is_king_side_castle_possible(board: &Board, color: Color) -> bool {
         &[square::F8, square::G8]
     });
     let is_king_side_in_attack = if is_white {
-        board.are_squares_attacked(&[square::E1, square::F1, square::G1], Color::WHITE)
+        board.are_squares_attacked(&[square::E1, square::F1, square::G1], Color::White)
     } else {
-        board.are_squares_attacked(&[square::E8, square::F8, square::G8], Color::BLACK)
+        board.are_squares_attacked(&[square::E8, square::F8, square::G8], Color::Black)
     };
     let is_king_side_rook_exist = if is_white {
-        board.piece_at_square(square::H1) == Piece::WR
+        board.piece_at_square(square::H1) == Some(Piece::WR)
     } else {
-        board.piece_at_square(square::H8) == Piece::BR
+        board.piece_at_square(square::H8) == Some(Piece::BR)
     };
 
     can_short_castle && is_king_side_clear && !is_king_side_in_attack && is_king_side_rook_exist
 }
 
 fn is_queen_side_castle_possible(board: &Board, color: Color) -> bool {
-    let is_white = color == Color::WHITE;
+    let is_white = color == Color::White;
     let can_long_castle = board.can_castle_long();
     let is_queen_side_clear = board.are_squares_empty(if is_white {
         &[square::D1, square::C1, square::B1]
@@ -289,14 +263,14 @@ fn is_queen_side_castle_possible(board: &Board, color: Color) -> bool {
         &[square::D8, square::C8, square::B8]
     });
     let is_queen_side_in_attack = if is_white {
-        board.are_squares_attacked(&[square::E1, square::D1, square::C1], Color::WHITE)
+        board.are_squares_attacked(&[square::E1, square::D1, square::C1], Color::White)
     } else {
-        board.are_squares_attacked(&[square::E8, square::D8, square::C8], Color::BLACK)
+        board.are_squares_attacked(&[square::E8, square::D8, square::C8], Color::Black)
     };
     let is_queen_side_rook_exist = if is_white {
-        board.piece_at_square(square::A1) == Piece::WR
+        board.piece_at_square(square::A1) == Some(Piece::WR)
     } else {
-        board.piece_at_square(square::A8) == Piece::BR
+        board.piece_at_square(square::A8) == Some(Piece::BR)
     };
 
     can_long_castle && is_queen_side_clear && !is_queen_side_in_attack && is_queen_side_rook_exist
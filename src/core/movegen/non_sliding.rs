@@ -1,12 +1,15 @@
 use super::super::BitBoard;
+use super::super::Color;
 use super::super::Square;
 
 static mut KING_ATTACKS_TABLE: [BitBoard; 64] = [BitBoard::EMPTY; 64];
 static mut KNIGHT_ATTACKS_TABLE: [BitBoard; 64] = [BitBoard::EMPTY; 64];
+static mut PAWN_ATTACKS_TABLE: [[BitBoard; 64]; 2] = [[BitBoard::EMPTY; 64]; 2];
 
 pub fn init_non_sliding_attacks() {
     generate_king_attacks();
     generate_knight_attacks();
+    generate_pawn_attacks();
 }
 
 pub fn get_king_attacks(sq: Square) -> BitBoard {
@@ -17,6 +20,12 @@ pub fn get_knight_attacks(sq: Square) -> BitBoard {
     unsafe { KNIGHT_ATTACKS_TABLE[sq.to_usize()] }
 }
 
+/// Returns the squares a pawn of `color` on `sq` attacks. Seen from the other
+/// colour, these are the squares of the pawns of `color` which attack `sq`.
+pub fn get_pawn_attacks(sq: Square, color: Color) -> BitBoard {
+    unsafe { PAWN_ATTACKS_TABLE[color.to_usize()][sq.to_usize()] }
+}
+
 fn generate_king_attacks() {
     unsafe {
         for index in 0..64 {
@@ -52,3 +61,16 @@ fn generate_knight_attacks() {
         }
     }
 }
+
+fn generate_pawn_attacks() {
+    unsafe {
+        for index in 0..64 {
+            let bb = BitBoard::new(Square(index));
+
+            PAWN_ATTACKS_TABLE[Color::White.to_usize()][index as usize] =
+                ((bb & !BitBoard::FILE_A) << 7u8) | ((bb & !BitBoard::FILE_H) << 9u8);
+            PAWN_ATTACKS_TABLE[Color::Black.to_usize()][index as usize] =
+                ((bb & !BitBoard::FILE_A) >> 9u8) | ((bb & !BitBoard::FILE_H) >> 7u8);
+        }
+    }
+}
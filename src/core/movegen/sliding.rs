@@ -1,6 +1,7 @@
 use super::constants::{self, Direction};
 use crate::core::{BitBoard, Square};
 use arr_macro::arr;
+use std::ptr::{addr_of, addr_of_mut};
 
 pub struct MagicAttackTable {
     pub relevant_occupancy_mask: BitBoard,
@@ -9,6 +10,12 @@ pub struct MagicAttackTable {
     pub attacks: Vec<BitBoard>,
 }
 
+impl Default for MagicAttackTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MagicAttackTable {
     pub const fn new() -> MagicAttackTable {
         MagicAttackTable {
@@ -26,7 +33,7 @@ impl MagicAttackTable {
 
 pub fn get_rook_attacks(sq: Square, occ: BitBoard) -> BitBoard {
     unsafe {
-        let table = &ROOK_ATTACKS_TABLE[sq.to_usize()];
+        let table = &(*addr_of!(ROOK_ATTACKS_TABLE))[sq.to_usize()];
         let hash = table.generate_hash(occ);
         table.attacks[hash]
     }
@@ -34,7 +41,7 @@ pub fn get_rook_attacks(sq: Square, occ: BitBoard) -> BitBoard {
 
 pub fn get_bishop_attacks(sq: Square, occ: BitBoard) -> BitBoard {
     unsafe {
-        let table = &BISHOP_ATTACKS_TABLE[sq.to_usize()];
+        let table = &(*addr_of!(BISHOP_ATTACKS_TABLE))[sq.to_usize()];
         let hash = table.generate_hash(occ);
         table.attacks[hash]
     }
@@ -44,6 +51,21 @@ pub fn get_queen_attacks(sq: Square, occ: BitBoard) -> BitBoard {
     get_rook_attacks(sq, occ) | get_bishop_attacks(sq, occ)
 }
 
+/// Returns the squares a rook on `sq` attacks through the first of `blockers`
+/// in each direction, up to the next occupied square, and not those it
+/// attacks directly.
+pub fn get_rook_xray_attacks(sq: Square, occ: BitBoard, blockers: BitBoard) -> BitBoard {
+    let attacks = get_rook_attacks(sq, occ);
+    attacks ^ get_rook_attacks(sq, occ ^ (attacks & blockers))
+}
+
+/// Returns the squares a bishop on `sq` attacks through the first of
+/// `blockers` on each diagonal, like `get_rook_xray_attacks`.
+pub fn get_bishop_xray_attacks(sq: Square, occ: BitBoard, blockers: BitBoard) -> BitBoard {
+    let attacks = get_bishop_attacks(sq, occ);
+    attacks ^ get_bishop_attacks(sq, occ ^ (attacks & blockers))
+}
+
 pub fn init_magic() {
     for index in 0..64 {
         init_attacks_table_for_square(Square(index), false);
@@ -69,8 +91,8 @@ fn init_attacks_table_for_square(sq: Square, is_bishop: bool) {
             BitBoard(constants::ROOK_MAGICS_NUMBERS[sq.to_usize()])
         };
 
-        let mut relevant_occupancies = Vec::with_capacity(count as usize);
-        let mut attacks = Vec::with_capacity(count as usize);
+        let mut relevant_occupancies = Vec::with_capacity(count);
+        let mut attacks = Vec::with_capacity(count);
 
         for index in 0..count {
             let occupancy = generate_occupancy(mask, index);
@@ -83,10 +105,10 @@ fn init_attacks_table_for_square(sq: Square, is_bishop: bool) {
             attacks.push(attack);
         }
 
-        let mut attacks_table = if is_bishop {
-            &mut BISHOP_ATTACKS_TABLE
+        let attacks_table = if is_bishop {
+            &mut *addr_of_mut!(BISHOP_ATTACKS_TABLE)
         } else {
-            &mut ROOK_ATTACKS_TABLE
+            &mut *addr_of_mut!(ROOK_ATTACKS_TABLE)
         };
 
         attacks_table[sq.to_usize()].shift = shift;
@@ -159,7 +181,7 @@ pub fn generate_rook_attacks(sq: Square, occ: BitBoard) -> BitBoard {
         | generate_slider_attacks_in_direction(sq, occ, Direction::West)
 }
 
-fn generate_slider_attacks_in_direction(
+pub(super) fn generate_slider_attacks_in_direction(
     sq: Square,
     occ: BitBoard,
     direction: Direction,
@@ -0,0 +1,399 @@
+use super::*;
+use crate::core::board::*;
+use crate::core::{square, BitBoard, Square};
+
+/// A move taken back: the move which led to the position, the piece it
+/// captured, and the castling rights and en passant square before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unmove {
+    pub m: Move,
+    /// The piece put back on the board, if the move took one.
+    pub captured: Option<Piece>,
+    pub castling_rights: CastlingRights,
+    pub en_passant: BitBoard,
+}
+
+// Pieces which can be put back where they were taken, pawns only off the
+// first and last ranks.
+const WHITE_CAPTURES: [Piece; 5] = [Piece::WP, Piece::WN, Piece::WB, Piece::WR, Piece::WQ];
+const BLACK_CAPTURES: [Piece; 5] = [Piece::BP, Piece::BN, Piece::BB, Piece::BR, Piece::BQ];
+
+/// Finds every move which can have led to the position, with each piece it
+/// can have taken. The position before it keeps the castling rights of the
+/// position, and gets those of `castling_rights` which the move can have
+/// lost, so un-castling needs the right of that castling. It has no en
+/// passant square, but before a capture en passant.
+pub fn scan_unmoves(board: &Board, castling_rights: CastlingRights, unmoves: &mut Vec<Unmove>) {
+    let color = board.color_to_move.enemy();
+    let is_white = color == Color::White;
+    let empty = !board.all_occupancy();
+
+    // Only a double push leaves an en passant square.
+    if board.en_passant.is_not_empty() {
+        let passed = board.en_passant.bit_scan() as i8;
+        let (from, to) = if is_white {
+            (passed - 8, passed + 8)
+        } else {
+            (passed + 8, passed - 8)
+        };
+        let (from, to) = (Square(from as u8), Square(to as u8));
+        let pawn = if is_white { Piece::WP } else { Piece::BP };
+
+        if board.piece_at_square(to) == Some(pawn)
+            && empty.is_set(from)
+            && empty.is_set(Square(passed as u8))
+        {
+            let m = Move::new(from, to, MoveFlags::DOUBLE_PUSH);
+            add_unmove(board, unmoves, m, None, castling_rights, BitBoard::EMPTY);
+        }
+
+        return;
+    }
+
+    scan_pawn_unmoves(board, castling_rights, unmoves);
+
+    let pieces = if is_white {
+        [Piece::WN, Piece::WB, Piece::WR, Piece::WQ, Piece::WK]
+    } else {
+        [Piece::BN, Piece::BB, Piece::BR, Piece::BQ, Piece::BK]
+    };
+    for piece in pieces {
+        scan_piece_unmoves(board, piece, castling_rights, unmoves);
+    }
+}
+
+fn scan_pawn_unmoves(board: &Board, castling_rights: CastlingRights, unmoves: &mut Vec<Unmove>) {
+    let color = board.color_to_move.enemy();
+    let is_white = color == Color::White;
+    let empty = !board.all_occupancy();
+    let (pawn, behind) = if is_white {
+        (Piece::WP, -8)
+    } else {
+        (Piece::BP, 8)
+    };
+
+    let mut pawns = board.pieces[pawn.to_usize()];
+    while pawns.is_not_empty() {
+        let to = Square(pawns.bit_scan());
+        pawns = pawns.pop_lsb();
+
+        // Ranks counted from the side of the pawn, which has not moved yet on
+        // the second one.
+        let rank = if is_white { to.rank() } else { 7 - to.rank() };
+        if rank <= 1 {
+            continue;
+        }
+
+        let back = Square((to.to_i8() + behind) as u8);
+        if empty.is_set(back) {
+            let m = Move::new(back, to, MoveFlags::QUIET);
+            add_unmove(board, unmoves, m, None, castling_rights, BitBoard::EMPTY);
+        }
+
+        for from in diagonals_behind(to, behind) {
+            if !empty.is_set(from) {
+                continue;
+            }
+
+            for &captured in captured_pieces(board.color_to_move, to) {
+                let m = Move::new(from, to, MoveFlags::CAPTURE);
+                add_unmove(
+                    board,
+                    unmoves,
+                    m,
+                    Some(captured),
+                    castling_rights,
+                    BitBoard::EMPTY,
+                );
+            }
+
+            // The pawn taken en passant had passed `to` from the square in
+            // front of it.
+            let front = Square((to.to_i8() - behind) as u8);
+            if rank == 5 && empty.is_set(back) && empty.is_set(front) {
+                let captured = if is_white { Piece::BP } else { Piece::WP };
+                let m = Move::new(from, to, MoveFlags::EN_PASSANT);
+                add_unmove(
+                    board,
+                    unmoves,
+                    m,
+                    Some(captured),
+                    castling_rights,
+                    BitBoard::new(to),
+                );
+            }
+        }
+    }
+}
+
+fn scan_piece_unmoves(
+    board: &Board,
+    piece: Piece,
+    castling_rights: CastlingRights,
+    unmoves: &mut Vec<Unmove>,
+) {
+    let color = piece.color();
+    let enemy = color.enemy();
+    let all_occupancy = board.all_occupancy();
+    let empty = !all_occupancy;
+
+    let mut piece_bb = board.pieces[piece.to_usize()];
+    while piece_bb.is_not_empty() {
+        let to = Square(piece_bb.bit_scan());
+        piece_bb = piece_bb.pop_lsb();
+
+        let mut origins = match piece.kind() {
+            // Pawns are taken back by `scan_pawn_unmoves`.
+            PieceType::Pawn => BitBoard::EMPTY,
+            PieceType::Knight => get_knight_attacks(to),
+            PieceType::Bishop => get_bishop_attacks(to, all_occupancy),
+            PieceType::Rook => get_rook_attacks(to, all_occupancy),
+            PieceType::Queen => get_queen_attacks(to, all_occupancy),
+            PieceType::King => get_king_attacks(to),
+        } & empty;
+
+        while origins.is_not_empty() {
+            let from = Square(origins.bit_scan());
+            origins = origins.pop_lsb();
+
+            let m = Move::new(from, to, MoveFlags::QUIET);
+            add_unmove(board, unmoves, m, None, castling_rights, BitBoard::EMPTY);
+
+            for &captured in captured_pieces(enemy, to) {
+                let m = Move::new(from, to, MoveFlags::CAPTURE);
+                add_unmove(
+                    board,
+                    unmoves,
+                    m,
+                    Some(captured),
+                    castling_rights,
+                    BitBoard::EMPTY,
+                );
+            }
+        }
+
+        let (last_rank, behind) = if color == Color::White {
+            (7, -8)
+        } else {
+            (0, 8)
+        };
+        let promotion = match piece.kind() {
+            PieceType::Knight => Some(MoveFlags::PROMOTE_TO_KNIGHT),
+            PieceType::Bishop => Some(MoveFlags::PROMOTE_TO_BISHOP),
+            PieceType::Rook => Some(MoveFlags::PROMOTE_TO_ROOK),
+            PieceType::Queen => Some(MoveFlags::PROMOTE_TO_QUEEN),
+            PieceType::Pawn | PieceType::King => None,
+        };
+        if let Some(flags) = promotion.filter(|_| to.rank() == last_rank) {
+            let back = Square((to.to_i8() + behind) as u8);
+            if empty.is_set(back) {
+                let m = Move::new(back, to, flags);
+                add_unmove(board, unmoves, m, None, castling_rights, BitBoard::EMPTY);
+            }
+
+            for from in diagonals_behind(to, behind) {
+                if !empty.is_set(from) {
+                    continue;
+                }
+
+                for &captured in captured_pieces(enemy, to) {
+                    let m = Move::new(from, to, flags | MoveFlags::CAPTURE);
+                    add_unmove(
+                        board,
+                        unmoves,
+                        m,
+                        Some(captured),
+                        castling_rights,
+                        BitBoard::EMPTY,
+                    );
+                }
+            }
+        }
+
+        if piece.kind() == PieceType::King {
+            scan_castling_unmoves(board, piece, castling_rights, unmoves);
+        }
+    }
+}
+
+fn scan_castling_unmoves(
+    board: &Board,
+    king: Piece,
+    castling_rights: CastlingRights,
+    unmoves: &mut Vec<Unmove>,
+) {
+    let (rook, squares) = if king == Piece::WK {
+        (
+            Piece::WR,
+            [
+                (square::G1, square::F1, square::H1, square::H1),
+                (square::C1, square::D1, square::A1, square::B1),
+            ],
+        )
+    } else {
+        (
+            Piece::BR,
+            [
+                (square::G8, square::F8, square::H8, square::H8),
+                (square::C8, square::D8, square::A8, square::B8),
+            ],
+        )
+    };
+    let from = if king == Piece::WK {
+        square::E1
+    } else {
+        square::E8
+    };
+
+    for (flags, (king_to, rook_to, rook_from, passed)) in [
+        (MoveFlags::SHORT_CASTLE, squares[0]),
+        (MoveFlags::LONG_CASTLE, squares[1]),
+    ] {
+        if board.piece_at_square(king_to) == Some(king)
+            && board.piece_at_square(rook_to) == Some(rook)
+            && board.are_squares_empty(&[from, rook_from, passed])
+        {
+            let m = Move::new(from, king_to, flags);
+            add_unmove(board, unmoves, m, None, castling_rights, BitBoard::EMPTY);
+        }
+    }
+}
+
+// Squares a pawn which captured on `to` came from.
+fn diagonals_behind(to: Square, behind: i8) -> impl Iterator<Item = Square> {
+    let back = to.to_i8() + behind;
+    [(back - 1, to.file() > 0), (back + 1, to.file() < 7)]
+        .into_iter()
+        .filter(|&(_, on_board)| on_board)
+        .map(|(square, _)| Square(square as u8))
+}
+
+fn captured_pieces(color: Color, square: Square) -> &'static [Piece] {
+    let pieces = if color == Color::White {
+        &WHITE_CAPTURES
+    } else {
+        &BLACK_CAPTURES
+    };
+
+    if square.rank() == 0 || square.rank() == 7 {
+        &pieces[1..]
+    } else {
+        pieces
+    }
+}
+
+// Takes the move back on a copy of the board to find the castling rights
+// before it, and keeps it if that position is legal.
+fn add_unmove(
+    board: &Board,
+    unmoves: &mut Vec<Unmove>,
+    m: Move,
+    captured: Option<Piece>,
+    castling_rights: CastlingRights,
+    en_passant: BitBoard,
+) {
+    let mut unmove = Unmove {
+        m,
+        captured,
+        castling_rights: board.castling_rights,
+        en_passant,
+    };
+    let mut previous = board.clone();
+    previous.unmake(&unmove);
+
+    // Rights are only lost, so those left must have been there before, and
+    // the move keeps any other.
+    let lost = lost_castling_rights(&previous, &m);
+    let at_home = castling_rights_at_home(&previous);
+    if board.castling_rights.intersects(lost) || !at_home.contains(board.castling_rights) {
+        return;
+    }
+    unmove.castling_rights |= castling_rights & lost & at_home;
+
+    let color = previous.color_to_move;
+    let castled = match (m.flags(), color == Color::White) {
+        (MoveFlags::SHORT_CASTLE, true) => Some((CastlingRights::WHITE_SHORT_CASTLE, square::F1)),
+        (MoveFlags::LONG_CASTLE, true) => Some((CastlingRights::WHITE_LONG_CASTLE, square::D1)),
+        (MoveFlags::SHORT_CASTLE, false) => Some((CastlingRights::BLACK_SHORT_CASTLE, square::F8)),
+        (MoveFlags::LONG_CASTLE, false) => Some((CastlingRights::BLACK_LONG_CASTLE, square::D8)),
+        _ => None,
+    };
+    if let Some((right, passed)) = castled {
+        if !unmove.castling_rights.contains(right)
+            || previous.are_squares_attacked(&[m.from(), passed], color)
+        {
+            return;
+        }
+    }
+
+    if !previous.is_king_in_check(color.enemy()) {
+        unmoves.push(unmove);
+    }
+}
+
+// Rights which `m` takes away in `board`, the position before it.
+fn lost_castling_rights(board: &Board, m: &Move) -> CastlingRights {
+    let mut lost = CastlingRights::NONE;
+
+    for square in [m.from(), m.to()] {
+        lost |= match square {
+            square::E1 if board.piece_at_square(square) == Some(Piece::WK) => {
+                CastlingRights::WHITE_CASTLE
+            }
+            square::E8 if board.piece_at_square(square) == Some(Piece::BK) => {
+                CastlingRights::BLACK_CASTLE
+            }
+            square::A1 => CastlingRights::WHITE_LONG_CASTLE,
+            square::H1 => CastlingRights::WHITE_SHORT_CASTLE,
+            square::A8 => CastlingRights::BLACK_LONG_CASTLE,
+            square::H8 => CastlingRights::BLACK_SHORT_CASTLE,
+            _ => CastlingRights::NONE,
+        };
+    }
+
+    lost
+}
+
+// Rights whose king and rook are on their starting squares.
+pub(crate) fn castling_rights_at_home(board: &Board) -> CastlingRights {
+    let mut rights = CastlingRights::NONE;
+
+    for (right, king, king_square, rook, rook_square) in [
+        (
+            CastlingRights::WHITE_SHORT_CASTLE,
+            Piece::WK,
+            square::E1,
+            Piece::WR,
+            square::H1,
+        ),
+        (
+            CastlingRights::WHITE_LONG_CASTLE,
+            Piece::WK,
+            square::E1,
+            Piece::WR,
+            square::A1,
+        ),
+        (
+            CastlingRights::BLACK_SHORT_CASTLE,
+            Piece::BK,
+            square::E8,
+            Piece::BR,
+            square::H8,
+        ),
+        (
+            CastlingRights::BLACK_LONG_CASTLE,
+            Piece::BK,
+            square::E8,
+            Piece::BR,
+            square::A8,
+        ),
+    ] {
+        if board.piece_at_square(king_square) == Some(king)
+            && board.piece_at_square(rook_square) == Some(rook)
+        {
+            rights |= right;
+        }
+    }
+
+    rights
+}
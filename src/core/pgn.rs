@@ -0,0 +1,188 @@
+use std::fmt::Write;
+
+use super::{Board, Color, Move};
+
+const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const LINE_WIDTH: usize = 80;
+
+/// A game in Portable Game Notation: its tag pairs, the position it started
+/// from and the moves played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub start: Board,
+    pub moves: Vec<Move>,
+}
+
+impl PgnGame {
+    pub fn new(start: Board) -> Self {
+        PgnGame {
+            tags: Vec::new(),
+            start,
+            moves: Vec::new(),
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of a tag, adding it after the existing tags if needed.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Returns the result tag, `*` if the game has none.
+    pub fn result(&self) -> &str {
+        self.tag("Result").unwrap_or("*")
+    }
+
+    /// Returns the position after the moves of the game.
+    pub fn end(&self) -> Board {
+        let mut board = self.start.clone();
+        for m in &self.moves {
+            board.make_move(m);
+        }
+
+        board
+    }
+
+    /// Writes the game as PGN, with the movetext wrapped to 80 columns. A
+    /// `FEN` tag is added for games which do not start from the initial
+    /// position.
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        let fen = self.start.to_fen();
+
+        for (name, value) in &self.tags {
+            let _ = writeln!(pgn, "[{} \"{}\"]", name, value.replace('"', "'"));
+        }
+        if fen != STARTING_FEN && self.tag("FEN").is_none() {
+            let _ = writeln!(pgn, "[SetUp \"1\"]\n[FEN \"{}\"]", fen);
+        }
+        pgn.push('\n');
+
+        let mut board = self.start.clone();
+        let mut tokens = Vec::with_capacity(self.moves.len() * 3 / 2 + 1);
+        for (index, m) in self.moves.iter().enumerate() {
+            if board.color_to_move == Color::White {
+                tokens.push(format!("{}.", board.full_move_number));
+            } else if index == 0 {
+                tokens.push(format!("{}...", board.full_move_number));
+            }
+
+            tokens.push(m.to_san(&board));
+            board.make_move(m);
+        }
+        tokens.push(self.result().to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + token.len() + 1 > LINE_WIDTH {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+
+            pgn.push_str(&token);
+            line_length += token.len();
+        }
+        pgn.push_str("\n\n");
+
+        pgn
+    }
+}
+
+/// Reads every game of a PGN file. Comments, variations and numeric
+/// annotation glyphs are skipped.
+pub fn parse(content: &str) -> Result<Vec<PgnGame>, String> {
+    let mut games = Vec::new();
+    let mut tags: Vec<(String, String)> = Vec::new();
+    let mut movetext = String::new();
+
+    // The sentinel tag ends the last game.
+    for line in content.lines().chain(std::iter::once("[End \"\"]")) {
+        let line = line.trim();
+
+        if line.starts_with('[') && line.ends_with(']') {
+            if !movetext.trim().is_empty() {
+                games.push(parse_game(std::mem::take(&mut tags), &movetext)?);
+                movetext.clear();
+            }
+
+            if let Some((name, value)) = line[1..line.len() - 1].split_once(' ') {
+                tags.push((name.to_string(), value.trim().trim_matches('"').to_string()));
+            }
+        } else if !line.starts_with('%') {
+            movetext.push_str(line);
+            movetext.push(' ');
+        }
+    }
+
+    Ok(games)
+}
+
+fn parse_game(tags: Vec<(String, String)>, movetext: &str) -> Result<PgnGame, String> {
+    let start = match tags.iter().find(|(name, _)| name == "FEN") {
+        Some((_, fen)) => Board::from(fen).map_err(|e| e.to_string())?,
+        None => Board::new(),
+    };
+
+    let mut game = PgnGame {
+        tags,
+        start,
+        moves: Vec::new(),
+    };
+    let mut board = game.start.clone();
+
+    for token in strip_annotations(movetext).split_whitespace() {
+        if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token) {
+            continue;
+        }
+
+        // Move numbers such as `12.` or `12...` may be attached to the move.
+        let san = token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+        if san.is_empty() || san.starts_with('$') {
+            continue;
+        }
+
+        let m = Move::from_san(san, &board).map_err(|e| format!("{} ({})", e, san))?;
+        board.make_move(&m);
+        game.moves.push(m);
+    }
+
+    Ok(game)
+}
+
+// Removes comments and variations from the movetext.
+fn strip_annotations(movetext: &str) -> String {
+    let mut stripped = String::with_capacity(movetext.len());
+    let mut in_comment = false;
+    let mut depth = 0;
+
+    for c in movetext.chars() {
+        match c {
+            '{' if !in_comment => in_comment = true,
+            '}' if in_comment => in_comment = false,
+            '(' if !in_comment => depth += 1,
+            ')' if !in_comment && depth > 0 => depth -= 1,
+            _ if !in_comment && depth == 0 => {
+                stripped.push(c);
+                continue;
+            }
+            _ => continue,
+        }
+
+        stripped.push(' ');
+    }
+
+    stripped
+}
@@ -0,0 +1,251 @@
+use super::board::*;
+use super::movegen::{Move, MoveFlags};
+use super::{square, zobrist, BitBoard, Square};
+
+/// A position without the undo records and network accumulators of `Board`,
+/// so that it is `Copy` and can be handed to threads or copied at every ply
+/// instead of taking moves back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub state: [Option<Piece>; 64],
+    pub pieces: [BitBoard; 12],
+    pub occupancy: [BitBoard; 2],
+    pub color_to_move: Color,
+    pub en_passant: BitBoard,
+    pub castling_rights: CastlingRights,
+    pub half_move_clock: usize,
+    pub full_move_number: usize,
+    pub hash: u64,
+    pub pawn_hash: u64,
+}
+
+/// What `Position::make` overwrites, for `Position::unmake` to restore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UndoInfo {
+    pub captured: Option<Piece>,
+    pub castling_rights: CastlingRights,
+    pub en_passant: BitBoard,
+    pub half_move_clock: usize,
+    pub hash: u64,
+    pub pawn_hash: u64,
+}
+
+impl Position {
+    pub fn from_board(board: &Board) -> Self {
+        Position {
+            state: board.state,
+            pieces: board.pieces,
+            occupancy: board.occupancy,
+            color_to_move: board.color_to_move,
+            en_passant: board.en_passant,
+            castling_rights: board.castling_rights,
+            half_move_clock: board.half_move_clock,
+            full_move_number: board.full_move_number,
+            hash: board.hash,
+            pawn_hash: board.pawn_hash,
+        }
+    }
+
+    /// Returns a board of the position, with no moves to undo.
+    pub fn to_board(&self) -> Board {
+        let mut board = Board::new_empty();
+        board.state = self.state;
+        board.pieces = self.pieces;
+        board.occupancy = self.occupancy;
+        board.color_to_move = self.color_to_move;
+        board.en_passant = self.en_passant;
+        board.castling_rights = self.castling_rights;
+        board.half_move_clock = self.half_move_clock;
+        board.full_move_number = self.full_move_number;
+        board.hash = self.hash;
+        board.pawn_hash = self.pawn_hash;
+        board.update_check_info();
+
+        board
+    }
+
+    pub fn piece_at_square(&self, square: Square) -> Option<Piece> {
+        self.state[square.to_usize()]
+    }
+
+    /// Returns the position after `m`, leaving this one as it is.
+    pub fn make_move(&self, m: &Move) -> Position {
+        let mut position = *self;
+        position.make(m);
+
+        position
+    }
+
+    /// Plays `m` in place, returning what `unmake` needs to take it back.
+    pub fn make(&mut self, m: &Move) -> UndoInfo {
+        let from = m.from();
+        let to = m.to();
+        let flags = m.flags();
+        let piece = self.piece_at_square(from).expect("No piece to move");
+        let is_white = piece.color() == Color::White;
+
+        let mut undo = UndoInfo {
+            captured: None,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            half_move_clock: self.half_move_clock,
+            hash: self.hash,
+            pawn_hash: self.pawn_hash,
+        };
+        self.hash ^= zobrist::castling_key(self.castling_rights);
+        self.hash ^= zobrist::en_passant_key(self.en_passant);
+        self.en_passant = BitBoard::EMPTY;
+
+        if m.is_capture() && flags != MoveFlags::EN_PASSANT {
+            let captured = self.piece_at_square(to).expect("No piece to capture");
+            self.remove_piece(captured, to);
+            undo.captured = Some(captured);
+        }
+
+        match flags {
+            MoveFlags::QUIET | MoveFlags::CAPTURE => self.move_piece(from, to, piece),
+            MoveFlags::DOUBLE_PUSH => {
+                self.move_piece(from, to, piece);
+                self.en_passant =
+                    BitBoard::new(Square((to.to_i8() + if is_white { -8 } else { 8 }) as u8));
+            }
+            MoveFlags::EN_PASSANT => {
+                self.move_piece(from, to, piece);
+                self.remove_piece(
+                    if is_white { Piece::BP } else { Piece::WP },
+                    Square((to.to_i8() + if is_white { -8 } else { 8 }) as u8),
+                );
+            }
+            MoveFlags::SHORT_CASTLE | MoveFlags::LONG_CASTLE => {
+                let (rook, rook_from, rook_to) = castling_rook(flags, is_white);
+                self.move_piece(from, to, piece);
+                self.move_piece(rook_from, rook_to, rook);
+            }
+            _ => {
+                let promotion = m.promotion(piece.color()).expect("Invalid flag");
+                self.remove_piece(piece, from);
+                self.add_piece(promotion, to);
+            }
+        }
+
+        self.castling_rights &= !lost_castling_rights(piece, from, to);
+        self.hash ^= zobrist::castling_key(self.castling_rights);
+        self.hash ^= zobrist::en_passant_key(self.en_passant);
+        self.hash ^= zobrist::SIDE_KEY;
+
+        if piece == Piece::WP || piece == Piece::BP || m.is_capture() {
+            self.half_move_clock = 0;
+        } else {
+            self.half_move_clock += 1;
+        }
+        if !is_white {
+            self.full_move_number += 1;
+        }
+        self.color_to_move = self.color_to_move.enemy();
+
+        undo
+    }
+
+    /// Takes back `m`, which `make` played and returned `undo` for.
+    pub fn unmake(&mut self, m: &Move, undo: &UndoInfo) {
+        let from = m.from();
+        let to = m.to();
+        let flags = m.flags();
+        let piece = self.piece_at_square(to).expect("No piece to unmake");
+        let is_white = piece.color() == Color::White;
+
+        match flags {
+            MoveFlags::QUIET | MoveFlags::CAPTURE | MoveFlags::DOUBLE_PUSH => {
+                self.move_piece(to, from, piece)
+            }
+            MoveFlags::EN_PASSANT => {
+                self.move_piece(to, from, piece);
+                self.add_piece(
+                    if is_white { Piece::BP } else { Piece::WP },
+                    Square((to.to_i8() + if is_white { -8 } else { 8 }) as u8),
+                );
+            }
+            MoveFlags::SHORT_CASTLE | MoveFlags::LONG_CASTLE => {
+                let (rook, rook_from, rook_to) = castling_rook(flags, is_white);
+                self.move_piece(to, from, piece);
+                self.move_piece(rook_to, rook_from, rook);
+            }
+            _ => {
+                self.remove_piece(piece, to);
+                self.add_piece(if is_white { Piece::WP } else { Piece::BP }, from);
+            }
+        }
+
+        if let Some(captured) = undo.captured {
+            self.add_piece(captured, to);
+        }
+
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.half_move_clock = undo.half_move_clock;
+        self.hash = undo.hash;
+        self.pawn_hash = undo.pawn_hash;
+        if !is_white {
+            self.full_move_number -= 1;
+        }
+        self.color_to_move = self.color_to_move.enemy();
+    }
+
+    fn add_piece(&mut self, piece: Piece, square: Square) {
+        self.pieces[piece.to_usize()] |= BitBoard::new(square);
+        self.occupancy[piece.color().to_usize()] |= BitBoard::new(square);
+        self.state[square.to_usize()] = Some(piece);
+        self.update_hashes(piece, square);
+    }
+
+    fn remove_piece(&mut self, piece: Piece, square: Square) {
+        self.pieces[piece.to_usize()] &= !BitBoard::new(square);
+        self.occupancy[piece.color().to_usize()] &= !BitBoard::new(square);
+        self.state[square.to_usize()] = None;
+        self.update_hashes(piece, square);
+    }
+
+    fn move_piece(&mut self, from: Square, to: Square, piece: Piece) {
+        self.remove_piece(piece, from);
+        self.add_piece(piece, to);
+    }
+
+    fn update_hashes(&mut self, piece: Piece, square: Square) {
+        self.hash ^= zobrist::piece_key(piece, square);
+        if piece == Piece::WP || piece == Piece::BP {
+            self.pawn_hash ^= zobrist::piece_key(piece, square);
+        }
+    }
+}
+
+// The rook and its squares for a castling move.
+fn castling_rook(flags: MoveFlags, is_white: bool) -> (Piece, Square, Square) {
+    match (flags, is_white) {
+        (MoveFlags::SHORT_CASTLE, true) => (Piece::WR, square::H1, square::F1),
+        (MoveFlags::SHORT_CASTLE, false) => (Piece::BR, square::H8, square::F8),
+        (_, true) => (Piece::WR, square::A1, square::D1),
+        (_, false) => (Piece::BR, square::A8, square::D8),
+    }
+}
+
+// Rights taken away by moving `piece` from `from` to `to`: those of a king
+// which moves, and of a rook which moves or is taken.
+fn lost_castling_rights(piece: Piece, from: Square, to: Square) -> CastlingRights {
+    let mut lost = match piece {
+        Piece::WK => CastlingRights::WHITE_CASTLE,
+        Piece::BK => CastlingRights::BLACK_CASTLE,
+        _ => CastlingRights::NONE,
+    };
+
+    for square in [from, to] {
+        lost |= match square {
+            square::A1 => CastlingRights::WHITE_LONG_CASTLE,
+            square::H1 => CastlingRights::WHITE_SHORT_CASTLE,
+            square::A8 => CastlingRights::BLACK_LONG_CASTLE,
+            square::H8 => CastlingRights::BLACK_SHORT_CASTLE,
+            _ => CastlingRights::NONE,
+        };
+    }
+
+    lost
+}
@@ -1,5 +1,117 @@
+use super::Color;
+use std::fmt;
+use std::str::FromStr;
+
+/// A square of the board, from a1 (0) to h8 (63). Outside the crate squares
+/// are made from a file and a rank, or parsed, so they are always valid.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub struct Square(pub u8);
+pub struct Square(pub(crate) u8);
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum File {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Rank {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Eighth,
+}
+
+impl File {
+    pub const ALL: [File; 8] = [
+        File::A,
+        File::B,
+        File::C,
+        File::D,
+        File::E,
+        File::F,
+        File::G,
+        File::H,
+    ];
+
+    pub fn of(square: Square) -> File {
+        File::ALL[square.file() as usize]
+    }
+
+    pub fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    pub fn to_char(self) -> char {
+        (b'a' + self.to_u8()) as char
+    }
+}
+
+impl Rank {
+    pub const ALL: [Rank; 8] = [
+        Rank::First,
+        Rank::Second,
+        Rank::Third,
+        Rank::Fourth,
+        Rank::Fifth,
+        Rank::Sixth,
+        Rank::Seventh,
+        Rank::Eighth,
+    ];
+
+    pub fn of(square: Square) -> Rank {
+        Rank::ALL[square.rank() as usize]
+    }
+
+    pub fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    pub fn to_char(self) -> char {
+        (b'1' + self.to_u8()) as char
+    }
+
+    /// Returns the rank as seen from the side of `color`, so that the eighth
+    /// rank is the first for black.
+    pub fn relative_to(self, color: Color) -> Rank {
+        if color == Color::White {
+            self
+        } else {
+            Rank::ALL[7 - self as usize]
+        }
+    }
+}
+
+impl TryFrom<u8> for File {
+    type Error = String;
+
+    fn try_from(file: u8) -> Result<Self, Self::Error> {
+        File::ALL
+            .get(file as usize)
+            .copied()
+            .ok_or(format!("Invalid file {}", file))
+    }
+}
+
+impl TryFrom<u8> for Rank {
+    type Error = String;
+
+    fn try_from(rank: u8) -> Result<Self, Self::Error> {
+        Rank::ALL
+            .get(rank as usize)
+            .copied()
+            .ok_or(format!("Invalid rank {}", rank))
+    }
+}
 
 impl Square {
     const NORTH: i8 = 8;
@@ -11,6 +123,10 @@ impl Square {
     const WEST: i8 = -1;
     const NORTH_WEST: i8 = 7;
 
+    pub const fn new(file: File, rank: Rank) -> Self {
+        Square(rank as u8 * 8 + file as u8)
+    }
+
     pub fn from(file: i8, rank: i8) -> Option<Self> {
         if (0..=7).contains(&rank) && (0..=7).contains(&file) {
             Some(Square((rank * 8 + file) as u8))
@@ -20,19 +136,30 @@ impl Square {
     }
 
     pub fn from_string(string: &str) -> Option<Self> {
-        let chars: Vec<char> = string.chars().collect();
-        let file = chars[0];
-        let rank = chars[1];
-
-        if ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'].contains(&file)
-            && ['1', '2', '3', '4', '5', '6', '7', '8'].contains(&rank)
-        {
-            Some(Square(file as u8 - b'a' + 8 * (rank as u8 - b'1')))
+        string.parse().ok()
+    }
+
+    /// Returns the square mirrored across the middle of the board, so that
+    /// a1 becomes a8.
+    pub fn flip(self) -> Square {
+        Square(self.0 ^ 56)
+    }
+
+    /// Returns the square as seen from the side of `color`, flipped for
+    /// black.
+    pub fn relative_to(self, color: Color) -> Square {
+        if color == Color::White {
+            self
         } else {
-            None
+            self.flip()
         }
     }
 
+    /// Returns the rank of the square as seen from the side of `color`.
+    pub fn relative_rank(self, color: Color) -> Rank {
+        Rank::of(self).relative_to(color)
+    }
+
     pub fn north(&self) -> Option<Square> {
         if self.rank() < 7 {
             return self.get_neighbor(Self::NORTH);
@@ -106,6 +233,34 @@ impl Square {
         self.0 / 8
     }
 
+    /// Returns the number of king moves from this square to `other`.
+    pub fn distance(&self, other: Square) -> u8 {
+        self.file_distance(other).max(self.rank_distance(other))
+    }
+
+    pub fn file_distance(&self, other: Square) -> u8 {
+        self.file().abs_diff(other.file())
+    }
+
+    pub fn rank_distance(&self, other: Square) -> u8 {
+        self.rank().abs_diff(other.rank())
+    }
+
+    /// Returns true if `a`, `b` and `c` are on one rank, file or diagonal.
+    pub fn is_aligned(a: Square, b: Square, c: Square) -> bool {
+        let is_line = |x: Square, y: Square| {
+            x != y
+                && (x.file() == y.file()
+                    || x.rank() == y.rank()
+                    || x.file_distance(y) == x.rank_distance(y))
+        };
+        let (ax, ay) = (a.file() as i32, a.rank() as i32);
+        let (bx, by) = (b.file() as i32, b.rank() as i32);
+        let (cx, cy) = (c.file() as i32, c.rank() as i32);
+
+        is_line(a, b) && (bx - ax) * (cy - ay) == (cx - ax) * (by - ay)
+    }
+
     pub fn to_u8(self) -> u8 {
         self.0
     }
@@ -193,6 +348,43 @@ impl Square {
     }
 }
 
+impl TryFrom<u8> for Square {
+    type Error = String;
+
+    fn try_from(index: u8) -> Result<Self, Self::Error> {
+        if index < 64 {
+            Ok(Square(index))
+        } else {
+            Err(format!("Invalid square {}", index))
+        }
+    }
+}
+
+impl FromStr for Square {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let mut chars = string.chars();
+        let (Some(file), Some(rank), None) = (chars.next(), chars.next(), chars.next()) else {
+            return Err(format!("Invalid square {}", string));
+        };
+
+        match (file, rank) {
+            ('a'..='h', '1'..='8') => Ok(Square::new(
+                File::ALL[file as usize - 'a' as usize],
+                Rank::ALL[rank as usize - '1' as usize],
+            )),
+            _ => Err(format!("Invalid square {}", string)),
+        }
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_string())
+    }
+}
+
 #[allow(dead_code)]
 pub const A1: Square = Square(0);
 #[allow(dead_code)]
@@ -0,0 +1,168 @@
+use super::board::*;
+use super::movegen::get_king_attacks;
+use super::movegen::unmove::castling_rights_at_home;
+use super::{BitBoard, Square};
+
+/// Checks that `board` can arise in a game, and returns every reason found
+/// why it cannot.
+///
+/// Beyond the placement of the pieces, the castling rights and the en passant
+/// square, a position must have a legal move which leads to it, which rules
+/// out checks no move can give, such as a double check with no discovered
+/// check.
+pub fn validate(board: &Board) -> Result<(), Vec<String>> {
+    let mut violations = Vec::new();
+
+    for color in [Color::White, Color::Black] {
+        validate_material(board, color, &mut violations);
+    }
+
+    let pawns = board.pieces[Piece::WP.to_usize()] | board.pieces[Piece::BP.to_usize()];
+    if (pawns & (BitBoard::RANK_1 | BitBoard::RANK_8)).is_not_empty() {
+        violations.push("Pawns are on the first or last rank".to_string());
+    }
+
+    // The other checks need a king of each side.
+    if board.pieces[Piece::WK.to_usize()].pop_count() != 1
+        || board.pieces[Piece::BK.to_usize()].pop_count() != 1
+    {
+        return Err(violations);
+    }
+
+    let white_king = Square(board.pieces[Piece::WK.to_usize()].bit_scan());
+    if get_king_attacks(white_king).is_set(Square(board.pieces[Piece::BK.to_usize()].bit_scan())) {
+        violations.push("The kings are next to each other".to_string());
+    }
+
+    let color = board.color_to_move;
+    if checkers(board, color.enemy()).is_not_empty() {
+        violations.push(format!("{} is in check but not to move", color.enemy()));
+    }
+
+    let checkers = checkers(board, color);
+    let sliders = [
+        Piece::WB,
+        Piece::WR,
+        Piece::WQ,
+        Piece::BB,
+        Piece::BR,
+        Piece::BQ,
+    ]
+    .iter()
+    .fold(BitBoard::EMPTY, |bb, piece| {
+        bb | board.pieces[piece.to_usize()]
+    });
+    if checkers.pop_count() > 2 {
+        violations.push(format!("{} is checked by more than two pieces", color));
+    } else if checkers.pop_count() == 2 && (checkers & sliders).is_empty() {
+        // One of the checks of a double check is discovered, by a slider.
+        violations.push(format!(
+            "{} is checked by two pieces which do not slide",
+            color
+        ));
+    }
+
+    validate_castling_rights(board, &mut violations);
+    validate_en_passant(board, &mut violations);
+
+    // A position which passes every other check can still not be reached if
+    // no legal move leads to it.
+    if violations.is_empty() && board.get_unmoves(CastlingRights::ALL).is_empty() {
+        violations.push("No legal move leads to the position".to_string());
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+// Every piece beyond those of the starting position is a promoted pawn, so
+// pawns and promoted pieces are at most eight.
+fn validate_material(board: &Board, color: Color, violations: &mut Vec<String>) {
+    let count = |kind: usize| board.pieces[color.to_usize() * 6 + kind].pop_count() as usize;
+    let (pawns, knights, rooks, queens, kings) = (count(0), count(1), count(3), count(4), count(5));
+
+    if kings != 1 {
+        violations.push(format!("{} has {} kings", color, kings));
+    }
+    if pawns > 8 {
+        violations.push(format!("{} has more than eight pawns", color));
+    }
+
+    // Bishops start on a square of either colour.
+    let bishops = board.pieces[color.to_usize() * 6 + 2];
+    let light_squares = BitBoard(0x55AA_55AA_55AA_55AA);
+    let light_bishops = (bishops & light_squares).pop_count() as usize;
+    let dark_bishops = (bishops & !light_squares).pop_count() as usize;
+
+    let promoted = knights.saturating_sub(2)
+        + light_bishops.saturating_sub(1)
+        + dark_bishops.saturating_sub(1)
+        + rooks.saturating_sub(2)
+        + queens.saturating_sub(1);
+    if pawns <= 8 && pawns + promoted > 8 {
+        violations.push(format!(
+            "{} has {} promoted pieces but only {} missing pawns",
+            color,
+            promoted,
+            8 - pawns
+        ));
+    }
+}
+
+fn validate_castling_rights(board: &Board, violations: &mut Vec<String>) {
+    let at_home = castling_rights_at_home(board);
+
+    for (right, name) in [
+        (CastlingRights::WHITE_SHORT_CASTLE, 'K'),
+        (CastlingRights::WHITE_LONG_CASTLE, 'Q'),
+        (CastlingRights::BLACK_SHORT_CASTLE, 'k'),
+        (CastlingRights::BLACK_LONG_CASTLE, 'q'),
+    ] {
+        if board.castling_rights.contains(right) && !at_home.contains(right) {
+            violations.push(format!(
+                "Castling right {} without the king and rook on their squares",
+                name
+            ));
+        }
+    }
+}
+
+// The en passant square is the one a pawn of the side which is not to move
+// has just passed, so it and the square the pawn came from are empty.
+fn validate_en_passant(board: &Board, violations: &mut Vec<String>) {
+    if board.en_passant.is_empty() {
+        return;
+    }
+
+    let passed = Square(board.en_passant.bit_scan());
+    let (rank, pawn, forward) = if board.color_to_move == Color::White {
+        (5, Piece::BP, -8)
+    } else {
+        (2, Piece::WP, 8)
+    };
+
+    let is_consistent = passed.rank() == rank
+        && board.piece_at_square(Square((passed.to_i8() + forward) as u8)) == Some(pawn)
+        && board.piece_at_square(passed).is_none()
+        && board
+            .piece_at_square(Square((passed.to_i8() - forward) as u8))
+            .is_none();
+    if !is_consistent {
+        violations.push(format!(
+            "En passant square {} without a pawn which has just passed it",
+            passed.as_string()
+        ));
+    }
+}
+
+// Pieces which attack the king of `color`, apart from the enemy king.
+fn checkers(board: &Board, color: Color) -> BitBoard {
+    let king = board.pieces[color.to_usize() * 6 + 5];
+    let enemy = color.enemy().to_usize();
+    board.attackers_to(Square(king.bit_scan()), board.all_occupancy())
+        & board.occupancy[enemy]
+        & !board.pieces[enemy * 6 + 5]
+}
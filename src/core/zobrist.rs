@@ -0,0 +1,69 @@
+use super::board::{CastlingRights, Color, Piece};
+use super::{BitBoard, Square};
+
+const SEED: u64 = 0x5eed_c0de_1234_abcd;
+
+pub const PIECE_KEYS: [[u64; 64]; 12] = generate_piece_keys();
+pub const CASTLING_KEYS: [u64; 16] = generate_keys::<16>(SEED ^ 0xc457);
+pub const EN_PASSANT_KEYS: [u64; 8] = generate_keys::<8>(SEED ^ 0xe9a5);
+pub const SIDE_KEY: u64 = splitmix64(SEED ^ 0x51de).1;
+
+pub fn piece_key(piece: Piece, square: Square) -> u64 {
+    PIECE_KEYS[piece.to_usize()][square.to_usize()]
+}
+
+pub fn castling_key(castling_rights: CastlingRights) -> u64 {
+    CASTLING_KEYS[castling_rights.bits() as usize]
+}
+
+pub fn en_passant_key(en_passant: BitBoard) -> u64 {
+    if en_passant.is_empty() {
+        0
+    } else {
+        EN_PASSANT_KEYS[Square(en_passant.bit_scan()).file() as usize]
+    }
+}
+
+pub fn side_key(color: Color) -> u64 {
+    if color == Color::White {
+        0
+    } else {
+        SIDE_KEY
+    }
+}
+
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    (state, z ^ (z >> 31))
+}
+
+const fn generate_keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut state = seed;
+    let mut index = 0;
+
+    while index < N {
+        let (next_state, key) = splitmix64(state);
+        keys[index] = key;
+        state = next_state;
+        index += 1;
+    }
+
+    keys
+}
+
+const fn generate_piece_keys() -> [[u64; 64]; 12] {
+    let mut keys = [[0; 64]; 12];
+    let mut piece = 0;
+
+    while piece < 12 {
+        keys[piece] = generate_keys::<64>(SEED ^ ((piece as u64 + 1) << 32));
+        piece += 1;
+    }
+
+    keys
+}
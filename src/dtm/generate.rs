@@ -0,0 +1,282 @@
+use super::{Dtm, DtmTable, DtmTables, Material};
+use crate::core::board::CastlingRights;
+use crate::core::{BitBoard, Board, Color, Move, Piece, Square};
+
+// Longest distance to mate which fits in a byte of the table.
+const MAX_PLIES: usize = 254;
+
+// Counter of a position which does not lose, as one of its moves draws or
+// wins into another table.
+const CANNOT_LOSE: u8 = u8::MAX;
+
+/// Generates the table of `material` by retrograde analysis, with the tables
+/// of every material it can be converted to by captures and promotions.
+///
+/// Positions after a double pawn push are stored without their en passant
+/// square, so a capture en passant is never taken into account.
+pub fn generate(material: &Material) -> Result<DtmTables, String> {
+    let mut tables = DtmTables::default();
+
+    for material in sub_materials(material) {
+        let table = generate_table(&material, &tables)?;
+        tables.insert(table);
+    }
+
+    Ok(tables)
+}
+
+// Every material which `material` can turn into, itself included, ordered
+// so that each one comes after those it can turn into.
+fn sub_materials(material: &Material) -> Vec<Material> {
+    let mut materials = vec![material.clone()];
+    let mut next = 0;
+
+    while next < materials.len() {
+        let pieces = materials[next].pieces.clone();
+        next += 1;
+
+        for (i, &piece) in pieces.iter().enumerate().skip(2) {
+            let mut captured = pieces.clone();
+            captured.remove(i);
+            let mut converted = vec![captured];
+
+            if piece == Piece::WP || piece == Piece::BP {
+                let promotions = if piece == Piece::WP {
+                    [Piece::WQ, Piece::WR, Piece::WB, Piece::WN]
+                } else {
+                    [Piece::BQ, Piece::BR, Piece::BB, Piece::BN]
+                };
+                for promotion in promotions {
+                    let mut promoted = pieces.clone();
+                    promoted[i] = promotion;
+                    converted.push(promoted);
+                }
+            }
+
+            for pieces in converted {
+                let material = Material::from_pieces(pieces);
+                if !materials.contains(&material) {
+                    materials.push(material);
+                }
+            }
+        }
+    }
+
+    let pawns = |material: &Material| {
+        material
+            .pieces
+            .iter()
+            .filter(|&&piece| piece == Piece::WP || piece == Piece::BP)
+            .count()
+    };
+    materials.sort_by_key(|material| (material.pieces.len(), pawns(material)));
+
+    materials
+}
+
+// Positions are resolved in order of their distance to mate, from the mates.
+// A position is won in n plies if one of its moves leads to a position lost
+// in n - 1, which is found by unmaking moves from the lost positions. It is
+// lost in n plies once all of its moves lead to won positions, the last of
+// them won in n - 1, so every position counts its moves left. Captures and
+// promotions lead to the tables of other materials, which are looked up at
+// once. Positions left when no more can be resolved are draws.
+fn generate_table(material: &Material, tables: &DtmTables) -> Result<DtmTable, String> {
+    let size = material.size();
+    let mut values = vec![0u8; size];
+    let mut resolved = vec![false; size];
+    let mut counters = vec![0u8; size];
+    let mut external_losses = vec![0u8; size];
+    // Positions to resolve at each distance to mate. Losses have an even and
+    // wins an odd distance.
+    let mut levels = vec![Vec::new(); MAX_PLIES + 2];
+
+    let mut successors = Vec::new();
+    for index in 0..size {
+        let Some(mut board) = material.position(index) else {
+            resolved[index] = true;
+            continue;
+        };
+
+        successors.clear();
+        let Some(outcome) = classify(&mut board, material, tables, &mut successors) else {
+            resolved[index] = true;
+            continue;
+        };
+
+        successors.sort_unstable();
+        successors.dedup();
+        // A move which draws or wins into another table keeps the position
+        // from being lost, whatever its moves in this table lead to.
+        let cannot_lose = outcome.can_draw || outcome.win.is_some();
+        counters[index] = if cannot_lose {
+            CANNOT_LOSE
+        } else {
+            successors.len() as u8
+        };
+
+        if let Some(plies) = outcome.win {
+            levels[plies].push(index as u32);
+        }
+        if !cannot_lose {
+            external_losses[index] = outcome.loss as u8;
+            if successors.is_empty() {
+                levels[outcome.loss].push(index as u32);
+            }
+        }
+    }
+
+    let mut predecessors = Vec::new();
+    for plies in 0..=MAX_PLIES {
+        let positions = std::mem::take(&mut levels[plies]);
+
+        for index in positions {
+            let index = index as usize;
+            if resolved[index] {
+                continue;
+            }
+            resolved[index] = true;
+
+            let is_loss = plies % 2 == 0;
+            values[index] = if is_loss {
+                Dtm::Loss(plies as u8).to_byte()
+            } else {
+                Dtm::Win(plies as u8).to_byte()
+            };
+
+            let board = material.position(index).unwrap();
+            predecessors.clear();
+            unmoves(&board, material, &mut predecessors);
+            predecessors.sort_unstable();
+            predecessors.dedup();
+
+            for &predecessor in &predecessors {
+                if resolved[predecessor] {
+                    continue;
+                }
+
+                if is_loss {
+                    levels[plies + 1].push(predecessor as u32);
+                } else if counters[predecessor] != CANNOT_LOSE {
+                    counters[predecessor] -= 1;
+                    if counters[predecessor] == 0 {
+                        let loss = (plies + 1).max(external_losses[predecessor] as usize);
+                        levels[loss].push(predecessor as u32);
+                    }
+                }
+            }
+        }
+    }
+
+    if levels[MAX_PLIES + 1]
+        .iter()
+        .any(|&index| !resolved[index as usize])
+    {
+        return Err(format!(
+            "{}: a mate is longer than {} plies",
+            material.name(),
+            MAX_PLIES
+        ));
+    }
+
+    Ok(DtmTable {
+        material: material.clone(),
+        values,
+    })
+}
+
+// What the moves to other tables of a position lead to: the shortest win,
+// whether one draws, and the longest loss.
+struct Outcome {
+    win: Option<usize>,
+    can_draw: bool,
+    loss: usize,
+}
+
+// Plays the moves of a position, recording the positions of this table they
+// lead to. Returns `None` for a position resolved by its moves alone, that is
+// a stalemate, and records a mate as a loss in 0 plies.
+fn classify(
+    board: &mut Board,
+    material: &Material,
+    tables: &DtmTables,
+    successors: &mut Vec<usize>,
+) -> Option<Outcome> {
+    let color = board.color_to_move;
+    let mut moves = [Move(0); 218];
+    let moves_count = board.get_moves(&mut moves);
+    let mut legal_moves = 0;
+    let mut outcome = Outcome {
+        win: None,
+        can_draw: false,
+        loss: 0,
+    };
+
+    for m in &moves[..moves_count] {
+        board.make_move(m);
+        if board.is_king_in_check(color) {
+            board.undo_move(m);
+            continue;
+        }
+        legal_moves += 1;
+
+        if m.is_capture() || m.is_promotion() {
+            let table = Material::of(board).and_then(|material| tables.get(&material));
+            let dtm = table.map_or(Dtm::Draw, |table| table.get(table.material.index(board)));
+
+            match dtm.before_move() {
+                Dtm::Win(plies) => {
+                    let plies = plies as usize;
+                    outcome.win = Some(outcome.win.map_or(plies, |win| win.min(plies)));
+                }
+                Dtm::Loss(plies) => outcome.loss = outcome.loss.max(plies as usize),
+                Dtm::Draw => outcome.can_draw = true,
+            }
+        } else {
+            successors.push(material.index(board));
+        }
+        board.undo_move(m);
+    }
+
+    match legal_moves {
+        0 if board.is_in_check() => Some(Outcome {
+            win: None,
+            can_draw: false,
+            loss: 0,
+        }),
+        0 => None,
+        _ => Some(outcome),
+    }
+}
+
+// Finds the positions of this table which lead to `board` by a move which is
+// neither a capture nor a promotion. Positions are stored without their en
+// passant square, so a pawn which can have just been pushed two squares is
+// also taken back as if the square was set.
+fn unmoves(board: &Board, material: &Material, predecessors: &mut Vec<usize>) {
+    let color = board.color_to_move.enemy();
+    let mut unmoves = board.get_unmoves(CastlingRights::NONE);
+
+    let (pawn, pushed_rank, behind) = if color == Color::White {
+        (Piece::WP, 3, -8)
+    } else {
+        (Piece::BP, 4, 8)
+    };
+    for to in board.pieces[pawn.to_usize()] {
+        if to.rank() == pushed_rank {
+            let mut pushed = board.clone();
+            pushed.en_passant = BitBoard::new(Square((to.to_i8() + behind) as u8));
+            unmoves.extend(pushed.get_unmoves(CastlingRights::NONE));
+        }
+    }
+
+    for unmove in unmoves {
+        if unmove.captured.is_some() || unmove.m.is_promotion() {
+            continue;
+        }
+
+        let mut predecessor = board.clone();
+        predecessor.unmake(&unmove);
+        predecessors.push(material.index(&predecessor));
+    }
+}
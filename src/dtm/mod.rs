@@ -0,0 +1,476 @@
+mod generate;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::core::board::CastlingRights;
+use crate::core::{BitBoard, Board, Color, Piece, Square};
+
+pub use generate::generate;
+
+/// Largest number of pieces, kings included, of a table. A five piece table
+/// without pawns has about 335 million entries.
+pub const MAX_PIECES: usize = 5;
+
+/// Extension of table files, which are named after their material.
+pub const EXTENSION: &str = "dtm";
+
+const MAGIC: &[u8; 4] = b"DTM1";
+
+// Pieces of either side in the order of table names.
+const WHITE_PIECES: [(char, Piece); 5] = [
+    ('Q', Piece::WQ),
+    ('R', Piece::WR),
+    ('B', Piece::WB),
+    ('N', Piece::WN),
+    ('P', Piece::WP),
+];
+const BLACK_PIECES: [(char, Piece); 5] = [
+    ('Q', Piece::BQ),
+    ('R', Piece::BR),
+    ('B', Piece::BB),
+    ('N', Piece::BN),
+    ('P', Piece::BP),
+];
+
+// The white king is moved by symmetry into the a1-d1-d4 triangle without
+// pawns, and to the files a to d with pawns.
+const TRIANGLE: [u8; 10] = [0, 1, 2, 3, 9, 10, 11, 18, 19, 27];
+
+/// Distance to mate of a position for the side to move, in plies. A side
+/// which is mated has lost in zero plies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dtm {
+    Win(u8),
+    Loss(u8),
+    Draw,
+}
+
+impl Dtm {
+    // Draws are stored as 0, losses with odd and wins with even bytes.
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            0 => Dtm::Draw,
+            byte if byte % 2 == 1 => Dtm::Loss(byte - 1),
+            byte => Dtm::Win(byte - 1),
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Dtm::Draw => 0,
+            Dtm::Win(plies) | Dtm::Loss(plies) => plies + 1,
+        }
+    }
+
+    /// Returns the result of the position before the move which led to a
+    /// position with this result, for the side which played it.
+    pub fn before_move(self) -> Self {
+        match self {
+            Dtm::Win(plies) => Dtm::Loss(plies.saturating_add(1)),
+            Dtm::Loss(plies) => Dtm::Win(plies.saturating_add(1)),
+            Dtm::Draw => Dtm::Draw,
+        }
+    }
+}
+
+/// Material of a table, such as `KQvKR` with the white pieces first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Material {
+    /// Every piece, in the order of their squares in the index: the kings,
+    /// then the other white pieces and the other black pieces as named.
+    pieces: Vec<Piece>,
+}
+
+impl Material {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid material: {}", name);
+        let (white, black) = name.split_once('v').ok_or_else(invalid)?;
+
+        let mut pieces = vec![Piece::WK, Piece::BK];
+        for (side, names) in [(white, WHITE_PIECES), (black, BLACK_PIECES)] {
+            let rest = side.strip_prefix('K').ok_or_else(invalid)?;
+            let mut side_pieces = Vec::new();
+
+            for c in rest.chars() {
+                let position = names
+                    .iter()
+                    .position(|&(n, _)| n == c)
+                    .ok_or_else(invalid)?;
+                side_pieces.push(position);
+            }
+            side_pieces.sort_unstable();
+            pieces.extend(side_pieces.iter().map(|&position| names[position].1));
+        }
+
+        if pieces.len() > MAX_PIECES {
+            return Err(format!(
+                "{}: at most {} pieces are supported",
+                name, MAX_PIECES
+            ));
+        }
+
+        Ok(Material { pieces })
+    }
+
+    // Sorts `pieces`, the kings first, into the order of the table names.
+    fn from_pieces(mut pieces: Vec<Piece>) -> Self {
+        let order = |piece: &Piece| {
+            [Piece::WK, Piece::BK]
+                .iter()
+                .chain(
+                    WHITE_PIECES
+                        .iter()
+                        .chain(BLACK_PIECES.iter())
+                        .map(|(_, p)| p),
+                )
+                .position(|p| p == piece)
+        };
+        pieces.sort_by_key(order);
+
+        Material { pieces }
+    }
+
+    /// Returns the material of `board`, if it has few enough pieces.
+    pub fn of(board: &Board) -> Option<Self> {
+        if board.all_occupancy().pop_count() as usize > MAX_PIECES {
+            return None;
+        }
+
+        let mut pieces = vec![Piece::WK, Piece::BK];
+        for (_, piece) in WHITE_PIECES.iter().chain(BLACK_PIECES.iter()) {
+            let count = board.pieces[piece.to_usize()].pop_count() as usize;
+            pieces.extend(std::iter::repeat_n(*piece, count));
+        }
+
+        Some(Material { pieces })
+    }
+
+    pub fn name(&self) -> String {
+        let side = |color: Color, names: &[(char, Piece); 5]| -> String {
+            let pieces = self.pieces[2..]
+                .iter()
+                .filter(|piece| piece.color() == color);
+            std::iter::once('K')
+                .chain(pieces.map(|piece| names.iter().find(|(_, p)| p == piece).unwrap().0))
+                .collect()
+        };
+
+        format!(
+            "{}v{}",
+            side(Color::White, &WHITE_PIECES),
+            side(Color::Black, &BLACK_PIECES)
+        )
+    }
+
+    pub fn piece_count(&self) -> usize {
+        self.pieces.len()
+    }
+
+    fn has_pawns(&self) -> bool {
+        self.pieces.contains(&Piece::WP) || self.pieces.contains(&Piece::BP)
+    }
+
+    fn king_squares(&self) -> usize {
+        if self.has_pawns() {
+            32
+        } else {
+            TRIANGLE.len()
+        }
+    }
+
+    /// Number of entries of the table, both sides to move.
+    pub fn size(&self) -> usize {
+        2 * self.king_squares() * 64usize.pow(self.pieces.len() as u32 - 1)
+    }
+
+    // Squares of the pieces of `board`, in the order of `pieces`.
+    fn squares(&self, board: &Board) -> Vec<u8> {
+        let mut squares = Vec::with_capacity(self.pieces.len());
+        let mut previous = None;
+
+        for &piece in &self.pieces {
+            if Some(piece) != previous {
+                let mut bitboard = board.pieces[piece.to_usize()];
+                while bitboard.is_not_empty() {
+                    squares.push(bitboard.bit_scan());
+                    bitboard = bitboard.pop_lsb();
+                }
+            }
+            previous = Some(piece);
+        }
+
+        squares
+    }
+
+    /// Index of the position of `board`, which must have this material. The
+    /// position is mirrored, and rotated without pawns, to the smallest index
+    /// of its symmetric positions, so that they share one entry.
+    pub fn index(&self, board: &Board) -> usize {
+        let squares = self.squares(board);
+        self.index_of_squares(&squares, board.color_to_move == Color::White)
+            .expect("Every position has a symmetric one in the table")
+    }
+
+    fn index_of_squares(&self, squares: &[u8], white_to_move: bool) -> Option<usize> {
+        let transforms = if self.has_pawns() { 2 } else { 8 };
+        let mut best = None;
+        let mut transformed = squares.to_vec();
+
+        for transform in 0..transforms {
+            for (target, &square) in transformed.iter_mut().zip(squares) {
+                *target = transform_square(square, transform);
+            }
+
+            let king = transformed[0];
+            let king_index = if self.has_pawns() {
+                (king % 8 < 4).then_some((king / 8 * 4 + king % 8) as usize)
+            } else {
+                TRIANGLE.iter().position(|&square| square == king)
+            };
+            let Some(king_index) = king_index else {
+                continue;
+            };
+
+            // Identical pieces are ordered by square, so that swapping them
+            // gives the same index.
+            let mut start = 1;
+            while start < transformed.len() {
+                let end = (start..self.pieces.len())
+                    .find(|&end| self.pieces[end] != self.pieces[start])
+                    .unwrap_or(self.pieces.len());
+                transformed[start..end].sort_unstable();
+                start = end;
+            }
+
+            let mut index = (!white_to_move as usize) * self.king_squares() + king_index;
+            for &square in &transformed[1..] {
+                index = index * 64 + square as usize;
+            }
+
+            best = Some(best.map_or(index, |best: usize| best.min(index)));
+        }
+
+        best
+    }
+
+    /// Returns the position of `index` if it is the representative of its
+    /// symmetric positions and a legal placement of the pieces, with the side
+    /// which is not to move out of check.
+    pub fn position(&self, index: usize) -> Option<Board> {
+        let mut squares = vec![0; self.pieces.len()];
+        let mut rest = index;
+        for square in squares[1..].iter_mut().rev() {
+            *square = (rest % 64) as u8;
+            rest /= 64;
+        }
+
+        let king_index = rest % self.king_squares();
+        let white_to_move = rest / self.king_squares() == 0;
+        squares[0] = if self.has_pawns() {
+            (king_index / 4 * 8 + king_index % 4) as u8
+        } else {
+            TRIANGLE[king_index]
+        };
+
+        let mut occupancy = BitBoard::EMPTY;
+        for (&square, &piece) in squares.iter().zip(&self.pieces) {
+            let is_pawn = piece == Piece::WP || piece == Piece::BP;
+            if occupancy.is_set(Square(square)) || (is_pawn && !(8..56).contains(&square)) {
+                return None;
+            }
+            occupancy |= BitBoard::new(Square(square));
+        }
+
+        if self.index_of_squares(&squares, white_to_move) != Some(index) {
+            return None;
+        }
+
+        let mut board = Board::new_empty();
+        for (&square, &piece) in squares.iter().zip(&self.pieces) {
+            board.add_piece_to_square(piece, Square(square));
+        }
+        board.color_to_move = if white_to_move {
+            Color::White
+        } else {
+            Color::Black
+        };
+        board.update_check_info();
+
+        let color = board.color_to_move;
+        (!board.is_king_in_check(color.enemy())).then_some(board)
+    }
+}
+
+// Applies one of the eight symmetries of the board: a transposition along the
+// a1-h8 diagonal, then mirrors of the files and of the ranks.
+fn transform_square(square: u8, transform: usize) -> u8 {
+    let (mut file, mut rank) = (square % 8, square / 8);
+    if transform & 4 != 0 {
+        (file, rank) = (rank, file);
+    }
+    if transform & 1 != 0 {
+        file = 7 - file;
+    }
+    if transform & 2 != 0 {
+        rank = 7 - rank;
+    }
+
+    8 * rank + file
+}
+
+/// The distance to mate of every position of a material, one byte each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DtmTable {
+    material: Material,
+    values: Vec<u8>,
+}
+
+impl DtmTable {
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+
+        DtmTable::from_bytes(&bytes).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_bytes()).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Reads a table: the magic bytes, the length of the material name and
+    /// the name, then the value of every index.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 5 || &bytes[..4] != MAGIC {
+            return Err("Not a DTM table".to_string());
+        }
+
+        let name_end = 5 + bytes[4] as usize;
+        let name = bytes
+            .get(5..name_end)
+            .and_then(|name| std::str::from_utf8(name).ok())
+            .ok_or("Invalid material name")?;
+        let material = Material::parse(name)?;
+
+        let values = bytes[name_end..].to_vec();
+        if values.len() != material.size() {
+            return Err("Invalid table size".to_string());
+        }
+
+        Ok(DtmTable { material, values })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let name = self.material.name();
+        let mut bytes = Vec::with_capacity(5 + name.len() + self.values.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(name.len() as u8);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&self.values);
+
+        bytes
+    }
+
+    /// Probes the table for `board`. Returns `None` if the material differs,
+    /// the position has castling rights or an en passant capture, which the
+    /// table does not store, or the side which is not to move is in check.
+    pub fn probe(&self, board: &Board) -> Option<Dtm> {
+        if Material::of(board)? != self.material
+            || board.castling_rights != CastlingRights::NONE
+            || can_capture_en_passant(board)
+            || board.is_king_in_check(board.color_to_move.enemy())
+        {
+            return None;
+        }
+
+        Some(self.get(self.material.index(board)))
+    }
+
+    fn get(&self, index: usize) -> Dtm {
+        Dtm::from_byte(self.values[index])
+    }
+
+    /// Longest win of the table, in plies.
+    pub fn longest_win(&self) -> u8 {
+        self.values
+            .iter()
+            .filter(|&&byte| byte > 0 && byte % 2 == 0)
+            .max()
+            .map_or(0, |byte| byte - 1)
+    }
+}
+
+/// Tables of several materials, such as those in a directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DtmTables {
+    tables: HashMap<String, DtmTable>,
+}
+
+impl DtmTables {
+    /// Loads every `.dtm` file of `directory`.
+    pub fn load(directory: &str) -> Result<Self, String> {
+        let mut tables = DtmTables::default();
+        let entries = fs::read_dir(directory).map_err(|e| format!("{}: {}", directory, e))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some(EXTENSION) {
+                tables.insert(DtmTable::load(&path.to_string_lossy())?);
+            }
+        }
+
+        Ok(tables)
+    }
+
+    pub fn insert(&mut self, table: DtmTable) {
+        self.tables.insert(table.material.name(), table);
+    }
+
+    pub fn get(&self, material: &Material) -> Option<&DtmTable> {
+        self.tables.get(&material.name())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Saves every table to `directory`, in a file named after its material.
+    pub fn save(&self, directory: &str) -> Result<(), String> {
+        for (name, table) in &self.tables {
+            let path = Path::new(directory).join(format!("{}.{}", name, EXTENSION));
+            table.save(&path.to_string_lossy())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn probe(&self, board: &Board) -> Option<Dtm> {
+        self.get(&Material::of(board)?)?.probe(board)
+    }
+}
+
+fn can_capture_en_passant(board: &Board) -> bool {
+    if board.en_passant.is_empty() {
+        return false;
+    }
+
+    let target = Square(board.en_passant.bit_scan());
+    let (pawn, rank) = if board.color_to_move == Color::White {
+        (Piece::WP, 4)
+    } else {
+        (Piece::BP, 3)
+    };
+
+    [target.file() as i8 - 1, target.file() as i8 + 1]
+        .iter()
+        .filter(|file| (0..8).contains(*file))
+        .any(|&file| board.pieces[pawn.to_usize()].is_set(Square(rank * 8 + file as u8)))
+}
@@ -0,0 +1,193 @@
+use super::{kpk, PAWN_VALUE, PIECE_VALUES};
+use crate::core::{BitBoard, Board, Color, Move, Piece, Square};
+
+/// Base score of endgames which are won, above any material balance so that
+/// the search converts into them.
+pub const KNOWN_WIN: i32 = 10000;
+
+/// Evaluates the endgames whose result is known from their material alone:
+/// a bare king against a king and pawn, against the mating material, and the
+/// drawn endings with two knights, bishops all on squares of one colour, or a
+/// bishop of the wrong colour for its rook pawns. Returns the score from
+/// white's point of view, or `None` for other positions.
+pub fn evaluate(board: &Board) -> Option<i32> {
+    for strong in [Color::White, Color::Black] {
+        if board.occupancy[strong.enemy().to_usize()].pop_count() != 1 {
+            continue;
+        }
+
+        let score = evaluate_against_bare_king(board, strong)?;
+        return Some(if strong == Color::White {
+            score
+        } else {
+            -score
+        });
+    }
+
+    None
+}
+
+fn evaluate_against_bare_king(board: &Board, strong: Color) -> Option<i32> {
+    let count = |kind: usize| board.pieces[strong.to_usize() * 6 + kind].pop_count();
+    let (pawns, knights, bishops, rooks, queens) =
+        (count(0), count(1), count(2), count(3), count(4));
+    let minors = knights + bishops;
+
+    if pawns == 1 && minors + rooks + queens == 0 {
+        Some(kpk(board, strong))
+    } else if knights == 2 && pawns + bishops + rooks + queens == 0 {
+        Some(0)
+    } else if bishops == 1 && pawns > 0 && knights + rooks + queens == 0 {
+        wrong_bishop(board, strong).then_some(0)
+    } else if knights == 1 && bishops == 1 && pawns + rooks + queens == 0 {
+        Some(kbnk(board, strong))
+    } else if bishops >= 2
+        && pawns + knights + rooks + queens == 0
+        && !has_both_bishops(board, strong)
+    {
+        Some(0)
+    } else if rooks + queens > 0 || bishops >= 2 {
+        Some(kxk(board, strong))
+    } else {
+        None
+    }
+}
+
+// King and pawn against king, looked up in the bitbase.
+fn kpk(board: &Board, strong: Color) -> i32 {
+    // The bitbase has the pawn on the white side, so the board is flipped
+    // when black has it.
+    let flip = if strong == Color::White { 0 } else { 56 };
+    let pawn = Square(board.pieces[strong.to_usize() * 6].bit_scan() ^ flip);
+    let strong_king = Square(king_square(board, strong).to_u8() ^ flip);
+    let weak_king = Square(king_square(board, strong.enemy()).to_u8() ^ flip);
+
+    if kpk::probe(strong_king, pawn, weak_king, board.color_to_move == strong) {
+        KNOWN_WIN + PAWN_VALUE + 10 * pawn.rank() as i32
+    } else {
+        0
+    }
+}
+
+// Rook pawns only queen on a square of their bishop's colour, otherwise the
+// king in the corner holds the draw.
+fn wrong_bishop(board: &Board, strong: Color) -> bool {
+    let pawns = board.pieces[strong.to_usize() * 6];
+    let file = if (pawns & !BitBoard::FILE_A).is_empty() {
+        0
+    } else if (pawns & !BitBoard::FILE_H).is_empty() {
+        7
+    } else {
+        return false;
+    };
+
+    let rank = if strong == Color::White { 7 } else { 0 };
+    let queening_square = Square(8 * rank + file);
+    let bishop = Square(board.pieces[strong.to_usize() * 6 + 2].bit_scan());
+
+    is_light(bishop) != is_light(queening_square)
+        && king_square(board, strong.enemy()).distance(queening_square) <= 1
+}
+
+// King and bishop and knight against king: the king is driven to a corner of
+// the bishop's colour.
+fn kbnk(board: &Board, strong: Color) -> i32 {
+    if is_stalemate(board, strong.enemy()) {
+        return 0;
+    }
+
+    let strong_king = king_square(board, strong);
+    let mut weak_king = king_square(board, strong.enemy());
+    let bishop = Square(board.pieces[strong.to_usize() * 6 + 2].bit_scan());
+
+    // `push_to_corner` drives to a1 and h8, mirror for a light squared bishop.
+    if is_light(bishop) {
+        weak_king = Square(weak_king.to_u8() ^ 7);
+    }
+
+    KNOWN_WIN
+        + KNIGHT_AND_BISHOP
+        + 3 * push_close(strong_king, weak_king)
+        + 420 * push_to_corner(weak_king)
+}
+
+const KNIGHT_AND_BISHOP: i32 = PIECE_VALUES[1] + PIECE_VALUES[2];
+
+// Mating material against a bare king: the king is driven to the edge, with
+// the strong king close to it.
+fn kxk(board: &Board, strong: Color) -> i32 {
+    if is_stalemate(board, strong.enemy()) {
+        return 0;
+    }
+
+    let strong_king = king_square(board, strong);
+    let weak_king = king_square(board, strong.enemy());
+    let material: i32 = (0..5)
+        .map(|kind| {
+            board.pieces[strong.to_usize() * 6 + kind].pop_count() as i32 * PIECE_VALUES[kind]
+        })
+        .sum();
+
+    KNOWN_WIN + material + push_to_edge(weak_king) + push_close(strong_king, weak_king)
+}
+
+// The evaluation of a bare king is only called at the leaves, where a
+// stalemate would otherwise be scored as a win.
+fn is_stalemate(board: &Board, weak: Color) -> bool {
+    if board.color_to_move != weak || board.is_in_check() {
+        return false;
+    }
+
+    let mut board = board.clone();
+    let mut moves = [Move(0); 218];
+    let moves_count = board.get_moves(&mut moves);
+
+    !moves[..moves_count].iter().any(|m| {
+        board.make_move(m);
+        let is_legal = !board.is_king_in_check(weak);
+        board.undo_move(m);
+
+        is_legal
+    })
+}
+
+fn king_square(board: &Board, color: Color) -> Square {
+    let king = if color == Color::White {
+        Piece::WK
+    } else {
+        Piece::BK
+    };
+
+    Square(board.pieces[king.to_usize()].bit_scan())
+}
+
+// Bishops only mate together when they stand on squares of both colours.
+fn has_both_bishops(board: &Board, strong: Color) -> bool {
+    let bishops = board.pieces[strong.to_usize() * 6 + 2];
+    let light = bishops
+        .into_iter()
+        .filter(|&bishop| is_light(bishop))
+        .count();
+
+    light > 0 && light < bishops.pop_count() as usize
+}
+
+fn is_light(square: Square) -> bool {
+    (square.file() + square.rank()) % 2 == 1
+}
+
+fn push_to_edge(square: Square) -> i32 {
+    let file = square.file().min(7 - square.file()) as i32;
+    let rank = square.rank().min(7 - square.rank()) as i32;
+
+    90 - (7 * file * file / 2 + 7 * rank * rank / 2)
+}
+
+// Largest in the a1 and h8 corners.
+fn push_to_corner(square: Square) -> i32 {
+    (7 - square.rank() as i32 - square.file() as i32).abs()
+}
+
+fn push_close(a: Square, b: Square) -> i32 {
+    140 - 20 * a.distance(b) as i32
+}
@@ -0,0 +1,142 @@
+use super::params::index;
+use super::pawns::{adjacent_files, pawns, FILES};
+use super::pieces::piece_attacks;
+use super::{EvalParams, Trace};
+use crate::core::movegen;
+use crate::core::{BitBoard, Board, Color, Piece, Rank, Square};
+
+/// Scores the safety of `color`'s king: the pawn shield in front of it, open
+/// files next to it and the enemy pieces attacking the squares around it.
+pub fn king_safety(
+    board: &Board,
+    color: Color,
+    params: &EvalParams,
+    trace: &mut impl Trace,
+) -> i32 {
+    let king_square = king_square(board, color);
+
+    pawn_shield(board, color, king_square, params, trace)
+        - open_files(board, color, king_square, params, trace)
+        - king_attacks(board, color, king_square, params, trace)
+}
+
+fn king_square(board: &Board, color: Color) -> Square {
+    let king = if color == Color::White {
+        Piece::WK
+    } else {
+        Piece::BK
+    };
+
+    Square(board.pieces[king.to_usize()].bit_scan())
+}
+
+// Only a king on its first two ranks is sheltered by the pawns in front of it.
+fn pawn_shield(
+    board: &Board,
+    color: Color,
+    king_square: Square,
+    params: &EvalParams,
+    trace: &mut impl Trace,
+) -> i32 {
+    if king_square.relative_rank(color) > Rank::Second {
+        return 0;
+    }
+
+    let rank = king_square.rank() as usize;
+    let file = king_square.file() as usize;
+    let shield_files = FILES[file] | adjacent_files(file);
+    let own_pawns = pawns(board, color);
+    let mut score = 0;
+
+    for (distance, bonus) in params.pawn_shield.iter().enumerate() {
+        let shield_rank = if color == Color::White {
+            rank + distance + 1
+        } else {
+            rank - distance - 1
+        };
+        let shield = shield_files & (BitBoard::RANK_1 << (8 * shield_rank));
+
+        let count = (own_pawns & shield).pop_count() as i32;
+        score += count * bonus;
+        trace.add(color, index::PAWN_SHIELD + distance, count as f64);
+    }
+
+    score
+}
+
+fn open_files(
+    board: &Board,
+    color: Color,
+    king_square: Square,
+    params: &EvalParams,
+    trace: &mut impl Trace,
+) -> i32 {
+    let own_pawns = pawns(board, color);
+    let enemy_pawns = pawns(board, color.enemy());
+    let file = king_square.file() as usize;
+    let mut penalty = 0;
+
+    for file_bb in &FILES[file.saturating_sub(1)..=(file + 1).min(7)] {
+        if (*file_bb & own_pawns).is_empty() {
+            penalty += params.king_semi_open_file;
+            trace.add(color, index::KING_SEMI_OPEN_FILE, -1.0);
+            if (*file_bb & enemy_pawns).is_empty() {
+                penalty += params.king_open_file;
+                trace.add(color, index::KING_OPEN_FILE, -1.0);
+            }
+        }
+    }
+
+    penalty
+}
+
+// Every king zone square a piece attacks adds the attack units of its kind.
+// Attacks only count once at least two enemy pieces take part, and the
+// penalty grows with the square of the attack units.
+fn king_attacks(
+    board: &Board,
+    color: Color,
+    king_square: Square,
+    params: &EvalParams,
+    trace: &mut impl Trace,
+) -> i32 {
+    let zone = movegen::get_king_attacks(king_square) | BitBoard::new(king_square);
+    let occupancy = board.all_occupancy();
+    let enemy = color.enemy();
+    let mut attackers = 0;
+    let mut units = 0;
+    let mut attacked_by_kind = [0; 6];
+
+    for (kind, attack_units) in params.king_attack_units.iter().enumerate() {
+        let mut piece_bb = board.pieces[enemy.to_usize() * 6 + kind];
+        while piece_bb.is_not_empty() && *attack_units > 0 {
+            let square = Square(piece_bb.bit_scan());
+            piece_bb = piece_bb.pop_lsb();
+
+            let attacked = (piece_attacks(kind, square, occupancy) & zone).pop_count() as i32;
+            if attacked > 0 {
+                attackers += 1;
+                units += attacked * attack_units;
+                attacked_by_kind[kind] += attacked;
+            }
+        }
+    }
+
+    if attackers < 2 {
+        return 0;
+    }
+
+    // The penalty is subtracted, and the derivative of units^2 / 4 by the
+    // attack units of a kind is units / 2 per square its pieces attack.
+    let penalty = units * units / 4;
+    if penalty >= params.max_king_attack {
+        trace.add(color, index::MAX_KING_ATTACK, -1.0);
+    } else {
+        for (kind, attacked) in attacked_by_kind.iter().enumerate() {
+            let coefficient = -(units * attacked) as f64 / 2.0;
+            trace.add(color, index::KING_ATTACK_UNITS + kind, coefficient);
+        }
+    }
+
+    penalty.min(params.max_king_attack)
+}
@@ -0,0 +1,230 @@
+use std::sync::OnceLock;
+
+use crate::core::{Board, Color, Move, Piece, Square};
+
+// Positions are indexed by the side to move, the pawn on files a to d and
+// ranks 2 to 7, and the two kings. The pawn is always white.
+const PAWN_SQUARES: usize = 24;
+const POSITIONS: usize = 2 * PAWN_SQUARES * 64 * 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Invalid,
+    Unknown,
+    Draw,
+    Win,
+}
+
+/// Returns true if white wins the position with a white king, a white pawn
+/// and a black king. Positions where the side which is not to move is in
+/// check are never won.
+///
+/// The bitbase is generated by retrograde analysis on the first probe, which
+/// takes a moment, call `init` to do it beforehand.
+pub fn probe(white_king: Square, pawn: Square, black_king: Square, white_to_move: bool) -> bool {
+    // The bitbase only has the pawn on files a to d, the others are mirrored.
+    let mirror = if pawn.file() > 3 { 7 } else { 0 };
+    let index = index(
+        white_to_move,
+        white_king.to_usize() ^ mirror,
+        pawn.to_usize() ^ mirror,
+        black_king.to_usize() ^ mirror,
+    );
+
+    match index {
+        Some(index) => bitbase()[index / 64] & (1 << (index % 64)) != 0,
+        None => false,
+    }
+}
+
+/// Generates the bitbase, if it has not been generated yet.
+pub fn init() {
+    bitbase();
+}
+
+fn bitbase() -> &'static [u64] {
+    static BITBASE: OnceLock<Vec<u64>> = OnceLock::new();
+    BITBASE.get_or_init(generate)
+}
+
+fn index(white_to_move: bool, white_king: usize, pawn: usize, black_king: usize) -> Option<usize> {
+    let (file, rank) = (pawn % 8, pawn / 8);
+    if file > 3 || !(1..7).contains(&rank) {
+        return None;
+    }
+
+    let side = if white_to_move { 0 } else { 1 };
+    let pawn_index = (rank - 1) * 4 + file;
+
+    Some(((side * PAWN_SQUARES + pawn_index) * 64 + white_king) * 64 + black_king)
+}
+
+fn position(index: usize) -> (bool, usize, usize, usize) {
+    let black_king = index % 64;
+    let white_king = index / 64 % 64;
+    let pawn_index = index / 64 / 64 % PAWN_SQUARES;
+    let white_to_move = index / 64 / 64 / PAWN_SQUARES == 0;
+
+    (
+        white_to_move,
+        white_king,
+        8 * (pawn_index / 4 + 1) + pawn_index % 4,
+        black_king,
+    )
+}
+
+// Classifies every position by its moves, played on a `Board`, then marks
+// positions as won until nothing changes: white wins if one of its moves
+// wins, black loses if all of its moves lose. Positions left are draws.
+fn generate() -> Vec<u64> {
+    let mut outcomes = vec![Outcome::Unknown; POSITIONS];
+    let mut successors = Vec::new();
+    let mut first_successor = vec![0; POSITIONS + 1];
+
+    let empty_board = Board::new_empty();
+
+    for (index, outcome) in outcomes.iter_mut().enumerate() {
+        first_successor[index] = successors.len();
+
+        let (white_to_move, white_king, pawn, black_king) = position(index);
+        if white_king == black_king || white_king == pawn || black_king == pawn {
+            *outcome = Outcome::Invalid;
+            continue;
+        }
+
+        let mut board = empty_board.clone();
+        board.add_piece_to_square(Piece::WK, Square(white_king as u8));
+        board.add_piece_to_square(Piece::WP, Square(pawn as u8));
+        board.add_piece_to_square(Piece::BK, Square(black_king as u8));
+        board.color_to_move = if white_to_move {
+            Color::White
+        } else {
+            Color::Black
+        };
+        board.update_check_info();
+
+        let color = board.color_to_move;
+        if board.is_king_in_check(color.enemy()) {
+            *outcome = Outcome::Invalid;
+            continue;
+        }
+
+        *outcome = classify(&mut board, &mut successors);
+    }
+    first_successor[POSITIONS] = successors.len();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for index in 0..POSITIONS {
+            if outcomes[index] != Outcome::Unknown {
+                continue;
+            }
+
+            let mut next = successors[first_successor[index]..first_successor[index + 1]]
+                .iter()
+                .map(|&successor| outcomes[successor]);
+            let white_to_move = index < POSITIONS / 2;
+            let wins = if white_to_move {
+                next.any(|outcome| outcome == Outcome::Win)
+            } else {
+                next.all(|outcome| outcome == Outcome::Win)
+            };
+
+            if wins {
+                outcomes[index] = Outcome::Win;
+                changed = true;
+            }
+        }
+    }
+
+    let mut bitbase = vec![0; POSITIONS / 64];
+    for (index, outcome) in outcomes.iter().enumerate() {
+        if *outcome == Outcome::Win {
+            bitbase[index / 64] |= 1 << (index % 64);
+        }
+    }
+
+    bitbase
+}
+
+// Finds the outcome of a position which follows from its moves alone, or
+// records the positions its moves lead to and returns `Unknown`.
+fn classify(board: &mut Board, successors: &mut Vec<usize>) -> Outcome {
+    let color = board.color_to_move;
+    let mut moves = [Move(0); 218];
+    let moves_count = board.get_moves(&mut moves);
+    let mut legal_moves = 0;
+
+    for m in &moves[..moves_count] {
+        board.make_move(m);
+        if board.is_king_in_check(color) {
+            board.undo_move(m);
+            continue;
+        }
+        legal_moves += 1;
+
+        let outcome = if m.is_promotion() {
+            promotion_outcome(board)
+        } else if m.is_capture() {
+            // Only the pawn can be taken, which leaves a bare king each.
+            Outcome::Draw
+        } else {
+            let white_king = board.pieces[Piece::WK.to_usize()].bit_scan() as usize;
+            let pawn = board.pieces[Piece::WP.to_usize()].bit_scan() as usize;
+            let black_king = board.pieces[Piece::BK.to_usize()].bit_scan() as usize;
+            let white_to_move = board.color_to_move == Color::White;
+
+            // Pushes keep the pawn on its file, so the index is always there.
+            successors.push(index(white_to_move, white_king, pawn, black_king).unwrap());
+            Outcome::Unknown
+        };
+        board.undo_move(m);
+
+        if color == Color::White && outcome == Outcome::Win {
+            return Outcome::Win;
+        }
+        if color == Color::Black && outcome == Outcome::Draw {
+            return Outcome::Draw;
+        }
+    }
+
+    match legal_moves {
+        0 if board.is_in_check() => Outcome::Win,
+        0 => Outcome::Draw,
+        _ => Outcome::Unknown,
+    }
+}
+
+// A queen or a rook wins against a bare king, unless it is taken at once or
+// the king is stalemated.
+fn promotion_outcome(board: &mut Board) -> Outcome {
+    let promoted = board.pieces[Piece::WQ.to_usize()] | board.pieces[Piece::WR.to_usize()];
+    if promoted.is_empty() {
+        return Outcome::Draw;
+    }
+
+    let mut moves = [Move(0); 218];
+    let moves_count = board.get_moves(&mut moves);
+    let mut legal_moves = 0;
+
+    for m in &moves[..moves_count] {
+        board.make_move(m);
+        let is_legal = !board.is_king_in_check(Color::Black);
+        board.undo_move(m);
+
+        if is_legal {
+            if m.is_capture() {
+                return Outcome::Draw;
+            }
+            legal_moves += 1;
+        }
+    }
+
+    if legal_moves == 0 && !board.is_in_check() {
+        Outcome::Draw
+    } else {
+        Outcome::Win
+    }
+}
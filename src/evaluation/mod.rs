@@ -0,0 +1,189 @@
+use crate::core::{BitBoard, Board, Color, Piece, Square};
+use params::index;
+
+pub mod endgame;
+mod king;
+pub mod kpk;
+pub mod nnue;
+pub mod params;
+pub mod pawns;
+mod pieces;
+pub mod trace;
+mod weights;
+
+pub use params::EvalParams;
+pub use pawns::PawnTable;
+pub use trace::Trace;
+pub use weights::DEFAULT_PARAMS;
+
+// Nominal piece values for move ordering, the evaluation uses the tuned
+// values in `EvalParams`.
+pub const PAWN_VALUE: i32 = 100;
+pub const KNIGHT_VALUE: i32 = 320;
+pub const BISHOP_VALUE: i32 = 330;
+pub const ROOK_VALUE: i32 = 500;
+pub const QUEEN_VALUE: i32 = 900;
+pub const KING_VALUE: i32 = 0;
+
+pub const PIECE_VALUES: [i32; 6] = [
+    PAWN_VALUE,
+    KNIGHT_VALUE,
+    BISHOP_VALUE,
+    ROOK_VALUE,
+    QUEEN_VALUE,
+    KING_VALUE,
+];
+
+/// Terms of the handcrafted evaluation, as listed by `Breakdown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Term {
+    Material,
+    PieceSquares,
+    Pawns,
+    Mobility,
+    KingSafety,
+    BishopPair,
+    Rooks,
+    Outposts,
+}
+
+impl Term {
+    pub const ALL: [Term; 8] = [
+        Term::Material,
+        Term::PieceSquares,
+        Term::Pawns,
+        Term::Mobility,
+        Term::KingSafety,
+        Term::BishopPair,
+        Term::Rooks,
+        Term::Outposts,
+    ];
+
+    pub fn as_string(&self) -> &str {
+        match self {
+            Term::Material => "Material",
+            Term::PieceSquares => "Piece squares",
+            Term::Pawns => "Pawns",
+            Term::Mobility => "Mobility",
+            Term::KingSafety => "King safety",
+            Term::BishopPair => "Bishop pair",
+            Term::Rooks => "Rooks",
+            Term::Outposts => "Outposts",
+        }
+    }
+}
+
+/// Score of every evaluation term for each side, in centipawns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Breakdown {
+    scores: [[i32; 2]; Term::ALL.len()],
+}
+
+impl Breakdown {
+    pub fn get(&self, term: Term, color: Color) -> i32 {
+        self.scores[term as usize][color.to_usize()]
+    }
+
+    /// Returns the difference of the sides' totals from white's point of view.
+    pub fn total(&self) -> i32 {
+        self.scores.iter().map(|[white, black]| white - black).sum()
+    }
+}
+
+/// Returns the static evaluation of the position in centipawns, relative to
+/// the side to move. Endgames with a known result are scored by
+/// `endgame::evaluate`, otherwise the network attached to the board is used
+/// if there is one.
+pub fn evaluate(board: &Board, pawn_table: &mut PawnTable) -> i32 {
+    let score = match endgame::evaluate(board) {
+        Some(score) => score,
+        None => {
+            if let Some(nnue) = &board.nnue {
+                return nnue.evaluate(board.color_to_move);
+            }
+
+            breakdown(board, pawn_table).total()
+        }
+    };
+
+    if board.color_to_move == Color::White {
+        score
+    } else {
+        -score
+    }
+}
+
+/// Evaluates every term of the handcrafted evaluation for both sides.
+pub fn breakdown(board: &Board, pawn_table: &mut PawnTable) -> Breakdown {
+    breakdown_with(board, pawn_table, &DEFAULT_PARAMS)
+}
+
+/// Like `breakdown`, with the weights taken from `params`. The pawn table
+/// must not hold entries evaluated with other parameters.
+pub fn breakdown_with(board: &Board, pawn_table: &mut PawnTable, params: &EvalParams) -> Breakdown {
+    breakdown_traced(board, pawn_table, params, &mut ())
+}
+
+/// Like `breakdown_with`, reporting the derivatives of the scores by the
+/// parameters to `trace`.
+pub fn breakdown_traced<T: Trace>(
+    board: &Board,
+    pawn_table: &mut PawnTable,
+    params: &EvalParams,
+    trace: &mut T,
+) -> Breakdown {
+    let mut scores = [[0; 2]; Term::ALL.len()];
+
+    scores[Term::Pawns as usize] = pawns::evaluate_sides(board, pawn_table, params, trace);
+    for color in [Color::White, Color::Black] {
+        let (material, piece_squares) = evaluate_pieces(board, color, params, trace);
+        let side = color.to_usize();
+
+        scores[Term::Material as usize][side] = material;
+        scores[Term::PieceSquares as usize][side] = piece_squares;
+        scores[Term::Mobility as usize][side] = pieces::mobility(board, color, params, trace);
+        scores[Term::KingSafety as usize][side] = king::king_safety(board, color, params, trace);
+        scores[Term::BishopPair as usize][side] = pieces::bishop_pair(board, color, params, trace);
+        scores[Term::Rooks as usize][side] = pieces::rooks(board, color, params, trace);
+        scores[Term::Outposts as usize][side] = pieces::outposts(board, color, params, trace);
+    }
+
+    Breakdown { scores }
+}
+
+pub fn piece_value(piece: Piece) -> i32 {
+    PIECE_VALUES[piece.kind().to_usize()]
+}
+
+// Returns the material and piece square table scores of `color`.
+fn evaluate_pieces(
+    board: &Board,
+    color: Color,
+    params: &EvalParams,
+    trace: &mut impl Trace,
+) -> (i32, i32) {
+    let mut material = 0;
+    let mut piece_squares = 0;
+
+    for kind in 0..6 {
+        let mut piece_bb: BitBoard = board.pieces[color.to_usize() * 6 + kind];
+
+        while piece_bb.is_not_empty() {
+            let square = Square(piece_bb.bit_scan());
+            piece_bb = piece_bb.pop_lsb();
+
+            let table_index = if color == Color::White {
+                square.to_usize() ^ 56
+            } else {
+                square.to_usize()
+            };
+
+            material += params.piece_values[kind];
+            piece_squares += params.piece_squares[kind][table_index];
+            trace.add(color, index::PIECE_VALUES + kind, 1.0);
+            trace.add(color, index::PIECE_SQUARES + kind * 64 + table_index, 1.0);
+        }
+    }
+
+    (material, piece_squares)
+}
@@ -0,0 +1,265 @@
+use std::fmt;
+use std::fs;
+use std::sync::Arc;
+
+use crate::core::{Board, Color, Piece, Square};
+
+const INPUTS: usize = 768;
+const QA: i32 = 255;
+const QB: i32 = 64;
+const SCALE: i64 = 400;
+
+/// A 768→N→1 network with a clipped ReLU hidden layer, evaluated from both
+/// sides' perspectives. Network files hold little-endian `i16` values in this
+/// order: feature weights `[768][N]`, feature biases `[N]`, output weights
+/// `[2N]` (side to move first) and the output bias. The hidden size is
+/// inferred from the file length.
+pub struct Network {
+    hidden: usize,
+    feature_weights: Vec<i16>,
+    feature_biases: Vec<i16>,
+    output_weights: Vec<i16>,
+    output_bias: i16,
+}
+
+impl Network {
+    pub fn load(path: &str) -> Result<Self, &'static str> {
+        match fs::read(path) {
+            Ok(bytes) => Self::from_bytes(&bytes),
+            Err(_) => Err("Could not read network file"),
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let values: Vec<i16> = bytes
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+
+        // A network with N hidden neurons holds 768N + N + 2N + 1 values.
+        let hidden = values.len().saturating_sub(1) / (INPUTS + 3);
+        if !bytes.len().is_multiple_of(2)
+            || hidden == 0
+            || values.len() != hidden * (INPUTS + 3) + 1
+        {
+            return Err("Invalid network file size");
+        }
+
+        let (feature_weights, rest) = values.split_at(INPUTS * hidden);
+        let (feature_biases, rest) = rest.split_at(hidden);
+        let (output_weights, rest) = rest.split_at(2 * hidden);
+
+        Ok(Network {
+            hidden,
+            feature_weights: feature_weights.to_vec(),
+            feature_biases: feature_biases.to_vec(),
+            output_weights: output_weights.to_vec(),
+            output_bias: rest[0],
+        })
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.hidden
+    }
+
+    fn feature_weights(&self, index: usize) -> &[i16] {
+        &self.feature_weights[index * self.hidden..(index + 1) * self.hidden]
+    }
+}
+
+/// Hidden layer values for both perspectives, kept up to date by `Board` as
+/// pieces are added, removed and moved so that an evaluation only has to run
+/// the output layer.
+#[derive(Clone)]
+pub struct Accumulators {
+    network: Arc<Network>,
+    values: [Vec<i16>; 2],
+}
+
+impl Accumulators {
+    pub fn new(network: Arc<Network>, board: &Board) -> Self {
+        let mut accumulators = Accumulators {
+            values: [
+                network.feature_biases.clone(),
+                network.feature_biases.clone(),
+            ],
+            network,
+        };
+
+        for (index, piece) in board.state.iter().enumerate() {
+            if let Some(piece) = piece {
+                accumulators.add(*piece, Square(index as u8));
+            }
+        }
+
+        accumulators
+    }
+
+    pub fn add(&mut self, piece: Piece, square: Square) {
+        for perspective in [Color::White, Color::Black] {
+            let weights = self
+                .network
+                .feature_weights(feature_index(perspective, piece, square));
+            add_weights(&mut self.values[perspective.to_usize()], weights);
+        }
+    }
+
+    pub fn remove(&mut self, piece: Piece, square: Square) {
+        for perspective in [Color::White, Color::Black] {
+            let weights = self
+                .network
+                .feature_weights(feature_index(perspective, piece, square));
+            sub_weights(&mut self.values[perspective.to_usize()], weights);
+        }
+    }
+
+    /// Returns the network's evaluation in centipawns, relative to `color`.
+    pub fn evaluate(&self, color: Color) -> i32 {
+        let hidden = self.network.hidden;
+        let weights = &self.network.output_weights;
+
+        let sum = forward(&self.values[color.to_usize()], &weights[..hidden])
+            + forward(&self.values[color.enemy().to_usize()], &weights[hidden..]);
+        let output = sum + self.network.output_bias as i64;
+
+        (output * SCALE / (QA * QB) as i64) as i32
+    }
+}
+
+impl PartialEq for Accumulators {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.network, &other.network) && self.values == other.values
+    }
+}
+
+impl Eq for Accumulators {}
+
+impl fmt::Debug for Accumulators {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Accumulators")
+            .field("hidden", &self.network.hidden)
+            .finish()
+    }
+}
+
+// Features are relative to the perspective: the perspective's own pieces come
+// first and black sees the board flipped vertically.
+fn feature_index(perspective: Color, piece: Piece, square: Square) -> usize {
+    let kind = piece.kind().to_usize();
+
+    if perspective == Color::White {
+        piece.color().to_usize() * 384 + kind * 64 + square.to_usize()
+    } else {
+        piece.color().enemy().to_usize() * 384 + kind * 64 + (square.to_usize() ^ 56)
+    }
+}
+
+// The hidden layer updates and the output layer use AVX2 where the CPU has
+// it, and give the same results as the scalar code on any CPU.
+fn add_weights(values: &mut [i16], weights: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // Safety: the CPU supports AVX2, checked just above.
+        return unsafe { update_avx2::<true>(values, weights) };
+    }
+
+    for (value, weight) in values.iter_mut().zip(weights) {
+        *value = value.wrapping_add(*weight);
+    }
+}
+
+fn sub_weights(values: &mut [i16], weights: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // Safety: the CPU supports AVX2, checked just above.
+        return unsafe { update_avx2::<false>(values, weights) };
+    }
+
+    for (value, weight) in values.iter_mut().zip(weights) {
+        *value = value.wrapping_sub(*weight);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn update_avx2<const ADD: bool>(values: &mut [i16], weights: &[i16]) {
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 16;
+
+    let chunks = values.len() / LANES;
+    for chunk in 0..chunks {
+        let value_ptr = values.as_mut_ptr().add(chunk * LANES) as *mut __m256i;
+        let value = _mm256_loadu_si256(value_ptr);
+        let weight = _mm256_loadu_si256(weights.as_ptr().add(chunk * LANES) as *const __m256i);
+        let updated = if ADD {
+            _mm256_add_epi16(value, weight)
+        } else {
+            _mm256_sub_epi16(value, weight)
+        };
+        _mm256_storeu_si256(value_ptr, updated);
+    }
+
+    for index in chunks * LANES..values.len() {
+        values[index] = if ADD {
+            values[index].wrapping_add(weights[index])
+        } else {
+            values[index].wrapping_sub(weights[index])
+        };
+    }
+}
+
+fn forward(values: &[i16], weights: &[i16]) -> i64 {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // Safety: the CPU supports AVX2, checked just above.
+        return unsafe { forward_avx2(values, weights) };
+    }
+
+    forward_scalar(values, weights)
+}
+
+fn forward_scalar(values: &[i16], weights: &[i16]) -> i64 {
+    values
+        .iter()
+        .zip(weights)
+        .map(|(value, weight)| (*value as i32).clamp(0, QA) as i64 * *weight as i64)
+        .sum()
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn forward_avx2(values: &[i16], weights: &[i16]) -> i64 {
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 16;
+    // A product pair is at most 2 * 255 * 32768 in magnitude, so an i32 lane
+    // holds the sums of 64 chunks before it is widened to i64.
+    const WIDEN_INTERVAL: usize = 64;
+
+    let chunks = values.len() / LANES;
+    let zero = _mm256_setzero_si256();
+    let max = _mm256_set1_epi16(QA as i16);
+    let mut wide_sum = _mm256_setzero_si256();
+    let mut sum = _mm256_setzero_si256();
+
+    for chunk in 0..chunks {
+        let value = _mm256_loadu_si256(values.as_ptr().add(chunk * LANES) as *const __m256i);
+        let weight = _mm256_loadu_si256(weights.as_ptr().add(chunk * LANES) as *const __m256i);
+        let clipped = _mm256_min_epi16(_mm256_max_epi16(value, zero), max);
+        sum = _mm256_add_epi32(sum, _mm256_madd_epi16(clipped, weight));
+
+        if (chunk + 1) % WIDEN_INTERVAL == 0 || chunk + 1 == chunks {
+            let low = _mm256_cvtepi32_epi64(_mm256_castsi256_si128(sum));
+            let high = _mm256_cvtepi32_epi64(_mm256_extracti128_si256::<1>(sum));
+            wide_sum = _mm256_add_epi64(wide_sum, _mm256_add_epi64(low, high));
+            sum = _mm256_setzero_si256();
+        }
+    }
+
+    let mut lanes = [0i64; 4];
+    _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, wide_sum);
+
+    let tail = chunks * LANES;
+    lanes.iter().sum::<i64>() + forward_scalar(&values[tail..], &weights[tail..])
+}
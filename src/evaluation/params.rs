@@ -0,0 +1,185 @@
+use std::fmt::Write;
+
+/// Shape of a parameter field, used to lay out the generated source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Scalar,
+    Array,
+    /// Rows of 64 values, one per square.
+    SquareTables,
+}
+
+/// Index of the first weight of each field of `EvalParams` in the vector made
+/// by `to_vector`.
+pub mod index {
+    pub const PIECE_VALUES: usize = 0;
+    pub const PIECE_SQUARES: usize = PIECE_VALUES + 6;
+    pub const DOUBLED_PAWN: usize = PIECE_SQUARES + 6 * 64;
+    pub const ISOLATED_PAWN: usize = DOUBLED_PAWN + 1;
+    pub const BACKWARD_PAWN: usize = ISOLATED_PAWN + 1;
+    pub const CONNECTED_PAWN: usize = BACKWARD_PAWN + 1;
+    pub const PASSED_PAWN: usize = CONNECTED_PAWN + 8;
+    pub const FREE_PASSER: usize = PASSED_PAWN + 8;
+    pub const MOBILITY_WEIGHTS: usize = FREE_PASSER + 8;
+    pub const MOBILITY_BASELINES: usize = MOBILITY_WEIGHTS + 4;
+    pub const BISHOP_PAIR: usize = MOBILITY_BASELINES + 4;
+    pub const ROOK_OPEN_FILE: usize = BISHOP_PAIR + 1;
+    pub const ROOK_SEMI_OPEN_FILE: usize = ROOK_OPEN_FILE + 1;
+    pub const ROOK_SEVENTH_RANK: usize = ROOK_SEMI_OPEN_FILE + 1;
+    pub const KNIGHT_OUTPOST: usize = ROOK_SEVENTH_RANK + 1;
+    pub const BISHOP_OUTPOST: usize = KNIGHT_OUTPOST + 1;
+    pub const KING_ATTACK_UNITS: usize = BISHOP_OUTPOST + 1;
+    pub const MAX_KING_ATTACK: usize = KING_ATTACK_UNITS + 6;
+    pub const PAWN_SHIELD: usize = MAX_KING_ATTACK + 1;
+    pub const KING_SEMI_OPEN_FILE: usize = PAWN_SHIELD + 2;
+    pub const KING_OPEN_FILE: usize = KING_SEMI_OPEN_FILE + 1;
+    /// Number of weights.
+    pub const COUNT: usize = KING_OPEN_FILE + 1;
+}
+
+/// Every weight of the handcrafted evaluation. Penalties are stored as
+/// positive values and subtracted where they apply. Piece square tables are
+/// laid out from white's point of view with the 8th rank first, in the
+/// order pawn, knight, bishop, rook, queen, king.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    pub piece_values: [i32; 6],
+    pub piece_squares: [[i32; 64]; 6],
+    pub doubled_pawn: i32,
+    pub isolated_pawn: i32,
+    pub backward_pawn: i32,
+    pub connected_pawn: [i32; 8],
+    pub passed_pawn: [i32; 8],
+    pub free_passer: [i32; 8],
+    pub mobility_weights: [i32; 4],
+    pub mobility_baselines: [i32; 4],
+    pub bishop_pair: i32,
+    pub rook_open_file: i32,
+    pub rook_semi_open_file: i32,
+    pub rook_seventh_rank: i32,
+    pub knight_outpost: i32,
+    pub bishop_outpost: i32,
+    pub king_attack_units: [i32; 6],
+    pub max_king_attack: i32,
+    pub pawn_shield: [i32; 2],
+    pub king_semi_open_file: i32,
+    pub king_open_file: i32,
+}
+
+impl EvalParams {
+    /// Calls `visit` with every field in declaration order.
+    pub fn visit(&self, mut visit: impl FnMut(&'static str, Shape, &[i32])) {
+        let mut copy = self.clone();
+        copy.visit_mut(|name, shape, values| visit(name, shape, values));
+    }
+
+    /// Calls `visit` with every field in declaration order, allowing the
+    /// values to be changed.
+    pub fn visit_mut(&mut self, mut visit: impl FnMut(&'static str, Shape, &mut [i32])) {
+        use std::slice::from_mut;
+        use Shape::*;
+
+        visit("piece_values", Array, &mut self.piece_values);
+        visit(
+            "piece_squares",
+            SquareTables,
+            self.piece_squares.as_flattened_mut(),
+        );
+        visit("doubled_pawn", Scalar, from_mut(&mut self.doubled_pawn));
+        visit("isolated_pawn", Scalar, from_mut(&mut self.isolated_pawn));
+        visit("backward_pawn", Scalar, from_mut(&mut self.backward_pawn));
+        visit("connected_pawn", Array, &mut self.connected_pawn);
+        visit("passed_pawn", Array, &mut self.passed_pawn);
+        visit("free_passer", Array, &mut self.free_passer);
+        visit("mobility_weights", Array, &mut self.mobility_weights);
+        visit("mobility_baselines", Array, &mut self.mobility_baselines);
+        visit("bishop_pair", Scalar, from_mut(&mut self.bishop_pair));
+        visit("rook_open_file", Scalar, from_mut(&mut self.rook_open_file));
+        visit(
+            "rook_semi_open_file",
+            Scalar,
+            from_mut(&mut self.rook_semi_open_file),
+        );
+        visit(
+            "rook_seventh_rank",
+            Scalar,
+            from_mut(&mut self.rook_seventh_rank),
+        );
+        visit("knight_outpost", Scalar, from_mut(&mut self.knight_outpost));
+        visit("bishop_outpost", Scalar, from_mut(&mut self.bishop_outpost));
+        visit("king_attack_units", Array, &mut self.king_attack_units);
+        visit(
+            "max_king_attack",
+            Scalar,
+            from_mut(&mut self.max_king_attack),
+        );
+        visit("pawn_shield", Array, &mut self.pawn_shield);
+        visit(
+            "king_semi_open_file",
+            Scalar,
+            from_mut(&mut self.king_semi_open_file),
+        );
+        visit("king_open_file", Scalar, from_mut(&mut self.king_open_file));
+    }
+
+    /// Returns all weights as one vector, in the order of `visit`.
+    pub fn to_vector(&self) -> Vec<i32> {
+        let mut vector = Vec::new();
+        self.visit(|_, _, values| vector.extend_from_slice(values));
+
+        vector
+    }
+
+    /// Sets all weights from a vector made by `to_vector`.
+    pub fn set_vector(&mut self, vector: &[i32]) {
+        let mut index = 0;
+        self.visit_mut(|_, _, values| {
+            values.copy_from_slice(&vector[index..index + values.len()]);
+            index += values.len();
+        });
+
+        assert_eq!(vector.len(), index, "Parameter vector has the wrong length");
+    }
+
+    /// Returns the source of `weights.rs`, which defines these parameters as
+    /// the engine's defaults.
+    pub fn to_rust_source(&self) -> String {
+        let mut source = String::new();
+
+        source.push_str(
+            "// Evaluation weights, in the format written by the tuner\n// (`cargo run --release --bin tune`).\n\n",
+        );
+        source.push_str("use super::params::EvalParams;\n\n");
+        source.push_str("#[rustfmt::skip]\n");
+        source.push_str("pub const DEFAULT_PARAMS: EvalParams = EvalParams {\n");
+
+        self.visit(|name, shape, values| {
+            let _ = match shape {
+                Shape::Scalar => writeln!(source, "    {}: {},", name, values[0]),
+                Shape::Array => writeln!(source, "    {}: [{}],", name, join(values)),
+                Shape::SquareTables => {
+                    let _ = writeln!(source, "    {}: [", name);
+                    for table in values.chunks(64) {
+                        source.push_str("        [\n");
+                        for rank in table.chunks(8) {
+                            let row: Vec<String> =
+                                rank.iter().map(|value| format!("{:4}", value)).collect();
+                            let _ = writeln!(source, "           {},", row.join(","));
+                        }
+                        source.push_str("        ],\n");
+                    }
+                    writeln!(source, "    ],")
+                }
+            };
+        });
+
+        source.push_str("};\n");
+        source
+    }
+}
+
+fn join(values: &[i32]) -> String {
+    let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+
+    values.join(", ")
+}
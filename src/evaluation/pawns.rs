@@ -0,0 +1,302 @@
+use super::params::index;
+use super::{EvalParams, Trace};
+use crate::core::{BitBoard, Board, Color, Piece, Square};
+
+pub const DEFAULT_PAWN_TABLE_SIZE: usize = 1 << 14;
+
+pub(super) const FILES: [BitBoard; 8] = [
+    BitBoard::FILE_A,
+    BitBoard::FILE_B,
+    BitBoard::FILE_C,
+    BitBoard::FILE_D,
+    BitBoard::FILE_E,
+    BitBoard::FILE_F,
+    BitBoard::FILE_G,
+    BitBoard::FILE_H,
+];
+
+const RANKS: [BitBoard; 8] = [
+    BitBoard::RANK_1,
+    BitBoard::RANK_2,
+    BitBoard::RANK_3,
+    BitBoard::RANK_4,
+    BitBoard::RANK_5,
+    BitBoard::RANK_6,
+    BitBoard::RANK_7,
+    BitBoard::RANK_8,
+];
+
+/// Pawn structure of a position, which only depends on where the pawns are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PawnEntry {
+    pub key: u64,
+    pub scores: [i32; 2],
+    pub passed: [BitBoard; 2],
+}
+
+impl PawnEntry {
+    const EMPTY: PawnEntry = PawnEntry {
+        key: 0,
+        scores: [0; 2],
+        passed: [BitBoard::EMPTY; 2],
+    };
+}
+
+/// Cache of pawn structure evaluations indexed by the board's pawn hash. Each
+/// search thread owns one. Entries do not record the parameters they were
+/// evaluated with, so the table has to be cleared when those change.
+pub struct PawnTable {
+    entries: Vec<PawnEntry>,
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAWN_TABLE_SIZE)
+    }
+}
+
+impl PawnTable {
+    pub fn new(size: usize) -> Self {
+        PawnTable {
+            entries: vec![PawnEntry::EMPTY; size.max(1).next_power_of_two()],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(PawnEntry::EMPTY);
+    }
+
+    pub fn probe(&mut self, board: &Board, params: &EvalParams) -> PawnEntry {
+        let index = (board.pawn_hash as usize) & (self.entries.len() - 1);
+        let entry = self.entries[index];
+
+        if entry.key == board.pawn_hash {
+            return entry;
+        }
+
+        let entry = PawnEntry {
+            key: board.pawn_hash,
+            scores: [
+                evaluate_structure(board, Color::White, params, &mut ()),
+                evaluate_structure(board, Color::Black, params, &mut ()),
+            ],
+            passed: [
+                passed_pawns(board, Color::White),
+                passed_pawns(board, Color::Black),
+            ],
+        };
+        self.entries[index] = entry;
+
+        entry
+    }
+}
+
+/// Returns the pawn structure score from white's point of view.
+pub fn evaluate(board: &Board, pawn_table: &mut PawnTable, params: &EvalParams) -> i32 {
+    let [white, black] = evaluate_sides(board, pawn_table, params, &mut ());
+
+    white - black
+}
+
+/// Returns the pawn structure score of each side. Passed pawns with nothing in
+/// front of them are scored here rather than in the cached entry, since their
+/// path also depends on the other pieces. The pawn table is bypassed while
+/// `trace` is enabled.
+pub fn evaluate_sides<T: Trace>(
+    board: &Board,
+    pawn_table: &mut PawnTable,
+    params: &EvalParams,
+    trace: &mut T,
+) -> [i32; 2] {
+    let entry = if T::ENABLED {
+        PawnEntry {
+            key: board.pawn_hash,
+            scores: [Color::White, Color::Black]
+                .map(|color| evaluate_structure(board, color, params, trace)),
+            passed: [Color::White, Color::Black].map(|color| passed_pawns(board, color)),
+        }
+    } else {
+        pawn_table.probe(board, params)
+    };
+
+    [Color::White, Color::Black].map(|color| {
+        entry.scores[color.to_usize()] + free_passers(board, &entry, color, params, trace)
+    })
+}
+
+fn evaluate_structure(
+    board: &Board,
+    color: Color,
+    params: &EvalParams,
+    trace: &mut impl Trace,
+) -> i32 {
+    let own_pawns = pawns(board, color);
+    let enemy_pawns = pawns(board, color.enemy());
+    let mut score = 0;
+
+    for file in FILES {
+        let count = (own_pawns & file).pop_count() as i32;
+        if count > 1 {
+            score -= params.doubled_pawn * (count - 1);
+            trace.add(color, index::DOUBLED_PAWN, -(count - 1) as f64);
+        }
+    }
+
+    let mut pawns_bb = own_pawns;
+    while pawns_bb.is_not_empty() {
+        let square = Square(pawns_bb.bit_scan());
+        pawns_bb = pawns_bb.pop_lsb();
+
+        let file = square.file() as usize;
+        let rank = square.rank() as usize;
+        let relative_rank = square.relative_rank(color) as usize;
+        let neighbours = own_pawns & adjacent_files(file);
+
+        if neighbours.is_empty() {
+            score -= params.isolated_pawn;
+            trace.add(color, index::ISOLATED_PAWN, -1.0);
+        } else if is_backward(color, file, rank, neighbours, enemy_pawns) {
+            score -= params.backward_pawn;
+            trace.add(color, index::BACKWARD_PAWN, -1.0);
+        }
+
+        let phalanx = neighbours & RANKS[rank];
+        let supported = neighbours & RANKS[behind(color, rank)];
+        if phalanx.is_not_empty() || supported.is_not_empty() {
+            score += params.connected_pawn[relative_rank];
+            trace.add(color, index::CONNECTED_PAWN + relative_rank, 1.0);
+        }
+
+        if is_passed(color, file, rank, enemy_pawns) {
+            score += params.passed_pawn[relative_rank];
+            trace.add(color, index::PASSED_PAWN + relative_rank, 1.0);
+        }
+    }
+
+    score
+}
+
+fn passed_pawns(board: &Board, color: Color) -> BitBoard {
+    let enemy_pawns = pawns(board, color.enemy());
+    let mut passed = BitBoard::EMPTY;
+
+    let mut pawns_bb = pawns(board, color);
+    while pawns_bb.is_not_empty() {
+        let square = Square(pawns_bb.bit_scan());
+        pawns_bb = pawns_bb.pop_lsb();
+
+        if is_passed(
+            color,
+            square.file() as usize,
+            square.rank() as usize,
+            enemy_pawns,
+        ) {
+            passed |= BitBoard::new(square);
+        }
+    }
+
+    passed
+}
+
+fn free_passers(
+    board: &Board,
+    entry: &PawnEntry,
+    color: Color,
+    params: &EvalParams,
+    trace: &mut impl Trace,
+) -> i32 {
+    let occupancy = board.all_occupancy();
+    let mut score = 0;
+
+    let mut passed_bb = entry.passed[color.to_usize()];
+    while passed_bb.is_not_empty() {
+        let square = Square(passed_bb.bit_scan());
+        passed_bb = passed_bb.pop_lsb();
+
+        let rank = square.rank() as usize;
+        let path = FILES[square.file() as usize] & ranks_ahead(color, rank);
+        if (path & occupancy).is_empty() {
+            let relative_rank = square.relative_rank(color) as usize;
+            score += params.free_passer[relative_rank];
+            trace.add(color, index::FREE_PASSER + relative_rank, 1.0);
+        }
+    }
+
+    score
+}
+
+fn is_passed(color: Color, file: usize, rank: usize, enemy_pawns: BitBoard) -> bool {
+    let front_span = (FILES[file] | adjacent_files(file)) & ranks_ahead(color, rank);
+
+    (enemy_pawns & front_span).is_empty()
+}
+
+// A pawn is backward when all its neighbours have advanced past it and an
+// enemy pawn controls the square in front of it, so it cannot safely advance.
+fn is_backward(
+    color: Color,
+    file: usize,
+    rank: usize,
+    neighbours: BitBoard,
+    enemy_pawns: BitBoard,
+) -> bool {
+    if (neighbours & !ranks_ahead(color, rank)).is_not_empty() {
+        return false;
+    }
+
+    let stop_rank = ahead(color, rank);
+    if stop_rank == rank {
+        return false;
+    }
+    let guard_rank = ahead(color, stop_rank);
+
+    guard_rank != stop_rank
+        && (enemy_pawns & adjacent_files(file) & RANKS[guard_rank]).is_not_empty()
+}
+
+pub(super) fn pawns(board: &Board, color: Color) -> BitBoard {
+    if color == Color::White {
+        board.pieces[Piece::WP.to_usize()]
+    } else {
+        board.pieces[Piece::BP.to_usize()]
+    }
+}
+
+pub(super) fn adjacent_files(file: usize) -> BitBoard {
+    let west = if file > 0 {
+        FILES[file - 1]
+    } else {
+        BitBoard::EMPTY
+    };
+    let east = if file < 7 {
+        FILES[file + 1]
+    } else {
+        BitBoard::EMPTY
+    };
+
+    west | east
+}
+
+pub(super) fn ranks_ahead(color: Color, rank: usize) -> BitBoard {
+    let mut ranks = BitBoard::EMPTY;
+    for (index, rank_bb) in RANKS.iter().enumerate() {
+        if (color == Color::White && index > rank) || (color == Color::Black && index < rank) {
+            ranks |= *rank_bb;
+        }
+    }
+
+    ranks
+}
+
+// The next rank towards promotion, or the same rank at the last one.
+fn ahead(color: Color, rank: usize) -> usize {
+    if color == Color::White {
+        (rank + 1).min(7)
+    } else {
+        rank.saturating_sub(1)
+    }
+}
+
+fn behind(color: Color, rank: usize) -> usize {
+    ahead(color.enemy(), rank)
+}
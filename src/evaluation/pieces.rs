@@ -0,0 +1,125 @@
+use super::params::index;
+use super::pawns::{adjacent_files, pawns, ranks_ahead, FILES};
+use super::{EvalParams, Trace};
+use crate::core::movegen;
+use crate::core::{BitBoard, Board, Color, Rank, Square};
+
+const KNIGHT: usize = 1;
+const BISHOP: usize = 2;
+const ROOK: usize = 3;
+const QUEEN: usize = 4;
+
+/// Returns the squares attacked by a knight, bishop, rook or queen of kind
+/// `kind` on `square`.
+pub fn piece_attacks(kind: usize, square: Square, occupancy: BitBoard) -> BitBoard {
+    match kind {
+        KNIGHT => movegen::get_knight_attacks(square),
+        BISHOP => movegen::get_bishop_attacks(square, occupancy),
+        ROOK => movegen::get_rook_attacks(square, occupancy),
+        QUEEN => movegen::get_queen_attacks(square, occupancy),
+        _ => BitBoard::EMPTY,
+    }
+}
+
+/// Scores the safe squares each knight, bishop, rook and queen can move to,
+/// as (moves - baseline) * weight for its kind. Squares held by friendly
+/// pieces or attacked by enemy pawns are not safe.
+pub fn mobility(board: &Board, color: Color, params: &EvalParams, trace: &mut impl Trace) -> i32 {
+    let occupancy = board.all_occupancy();
+    let available = !board.occupancy[color.to_usize()] & !board.pawn_attacks(color.enemy());
+    let mut score = 0;
+
+    for kind in KNIGHT..=QUEEN {
+        let mut piece_bb = board.pieces[color.to_usize() * 6 + kind];
+        while piece_bb.is_not_empty() {
+            let square = Square(piece_bb.bit_scan());
+            piece_bb = piece_bb.pop_lsb();
+
+            let moves = (piece_attacks(kind, square, occupancy) & available).pop_count() as i32;
+            let baseline = params.mobility_baselines[kind - 1];
+            let weight = params.mobility_weights[kind - 1];
+            score += (moves - baseline) * weight;
+            trace.add(
+                color,
+                index::MOBILITY_WEIGHTS + kind - 1,
+                (moves - baseline) as f64,
+            );
+            trace.add(color, index::MOBILITY_BASELINES + kind - 1, -weight as f64);
+        }
+    }
+
+    score
+}
+
+pub fn bishop_pair(
+    board: &Board,
+    color: Color,
+    params: &EvalParams,
+    trace: &mut impl Trace,
+) -> i32 {
+    if board.pieces[color.to_usize() * 6 + BISHOP].pop_count() >= 2 {
+        trace.add(color, index::BISHOP_PAIR, 1.0);
+        params.bishop_pair
+    } else {
+        0
+    }
+}
+
+/// Scores rooks on open and semi-open files and on the seventh rank.
+pub fn rooks(board: &Board, color: Color, params: &EvalParams, trace: &mut impl Trace) -> i32 {
+    let own_pawns = pawns(board, color);
+    let all_pawns = own_pawns | pawns(board, color.enemy());
+    let mut score = 0;
+
+    let mut rooks_bb = board.pieces[color.to_usize() * 6 + ROOK];
+    while rooks_bb.is_not_empty() {
+        let square = Square(rooks_bb.bit_scan());
+        rooks_bb = rooks_bb.pop_lsb();
+
+        let file = FILES[square.file() as usize];
+        if (file & all_pawns).is_empty() {
+            score += params.rook_open_file;
+            trace.add(color, index::ROOK_OPEN_FILE, 1.0);
+        } else if (file & own_pawns).is_empty() {
+            score += params.rook_semi_open_file;
+            trace.add(color, index::ROOK_SEMI_OPEN_FILE, 1.0);
+        }
+
+        if square.relative_rank(color) == Rank::Seventh {
+            score += params.rook_seventh_rank;
+            trace.add(color, index::ROOK_SEVENTH_RANK, 1.0);
+        }
+    }
+
+    score
+}
+
+/// Scores knights and bishops on the fourth to sixth rank that are defended
+/// by a pawn and can never be driven away by an enemy pawn.
+pub fn outposts(board: &Board, color: Color, params: &EvalParams, trace: &mut impl Trace) -> i32 {
+    let defended = board.pawn_attacks(color);
+    let enemy_pawns = pawns(board, color.enemy());
+    let mut score = 0;
+
+    for (kind, bonus, bonus_index) in [
+        (KNIGHT, params.knight_outpost, index::KNIGHT_OUTPOST),
+        (BISHOP, params.bishop_outpost, index::BISHOP_OUTPOST),
+    ] {
+        let mut piece_bb = board.pieces[color.to_usize() * 6 + kind] & defended;
+        while piece_bb.is_not_empty() {
+            let square = Square(piece_bb.bit_scan());
+            piece_bb = piece_bb.pop_lsb();
+
+            let rank = square.rank() as usize;
+            let attack_span = adjacent_files(square.file() as usize) & ranks_ahead(color, rank);
+            if (Rank::Fourth..=Rank::Sixth).contains(&square.relative_rank(color))
+                && (attack_span & enemy_pawns).is_empty()
+            {
+                score += bonus;
+                trace.add(color, bonus_index, 1.0);
+            }
+        }
+    }
+
+    score
+}
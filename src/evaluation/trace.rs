@@ -0,0 +1,36 @@
+use crate::core::Color;
+
+/// Receives the derivative of each side's score by the parameters while the
+/// handcrafted evaluation runs, which the tuner uses for its gradient.
+/// Indices are those of `EvalParams::to_vector`, see `params::index`.
+pub trait Trace {
+    /// Whether the evaluation reports to this trace. Cached pawn structure
+    /// scores are not used while it does.
+    const ENABLED: bool;
+
+    /// Adds `coefficient` to the derivative of `color`'s score by the
+    /// parameter at `index`.
+    fn add(&mut self, color: Color, index: usize, coefficient: f64);
+}
+
+/// The evaluation without a trace.
+impl Trace for () {
+    const ENABLED: bool = false;
+
+    #[inline(always)]
+    fn add(&mut self, _: Color, _: usize, _: f64) {}
+}
+
+/// Collects the derivatives of the evaluation from white's point of view, as
+/// `(index, coefficient)` pairs. An index may appear more than once.
+impl Trace for Vec<(usize, f64)> {
+    const ENABLED: bool = true;
+
+    fn add(&mut self, color: Color, index: usize, coefficient: f64) {
+        if color == Color::White {
+            self.push((index, coefficient));
+        } else {
+            self.push((index, -coefficient));
+        }
+    }
+}
@@ -1,3 +1,10 @@
+pub mod book;
 pub mod core;
+pub mod dtm;
+pub mod evaluation;
 pub mod perft;
+pub mod search;
+pub mod syzygy;
 pub mod terminal;
+pub mod tournament;
+pub mod tuning;
@@ -1,10 +1,10 @@
-mod core;
-mod perft;
-mod terminal;
-use crate::core::movegen;
+use rust_chess_engine::core::movegen;
+use rust_chess_engine::evaluation::kpk;
+use rust_chess_engine::terminal;
 
 fn main() {
     movegen::init();
+    kpk::init();
     terminal::init();
     terminal::run();
 }
@@ -0,0 +1,207 @@
+use std::sync::atomic::Ordering;
+
+use super::tt::Bound;
+use super::{
+    first_legal_move, pick_move, PvLine, SearchContext, SearchResult, SharedState,
+    CHECK_TIME_INTERVAL, INFINITY, MATE_BOUND, MATE_SCORE, MAX_DEPTH,
+};
+use crate::core::{Board, Move};
+
+pub const HASH_SIZE: usize = 4;
+
+const MAX_MATE: u8 = (MAX_DEPTH / 2) as u8;
+const CHECK_BONUS: i32 = 2 * INFINITY;
+
+/// Looks for a forced mate in at most `mate` moves for the side to move. The
+/// bound is raised one move at a time, so the first mate found is the
+/// shortest. Nodes of the attacking side try checks first and on its last
+/// move only checks are searched, since no other move can give mate; nodes
+/// that cannot improve on a mate already found are pruned by their distance
+/// to the root. When no mate exists the result has no lines and a score of 0.
+pub(super) fn solve(board: &mut Board, shared: &SharedState, mate: u8) -> SearchResult {
+    let mut context = SearchContext::new(board, shared, true);
+    let mut result = SearchResult {
+        best_move: None,
+        ponder_move: None,
+        score: 0,
+        depth: 0,
+        nodes: 0,
+        lines: Vec::new(),
+    };
+
+    let max_mate = match first_legal_move(context.board) {
+        Some(_) => mate.clamp(1, MAX_MATE),
+        None => 0,
+    };
+
+    for moves in 1..=max_mate {
+        let depth = 2 * moves as usize - 1;
+        context.root_depth = depth;
+        context.seldepth = 0;
+
+        let score = context.mate_search(depth as i32, 0, -INFINITY, INFINITY);
+        if context.is_stopped() {
+            break;
+        }
+
+        if score > MATE_BOUND {
+            let pv = context.pv_table[0][..context.pv_length[0]].to_vec();
+            result = SearchResult {
+                best_move: Some(pv[0]),
+                ponder_move: pv.get(1).copied(),
+                score,
+                depth: depth as u8,
+                nodes: context.total_nodes(),
+                lines: vec![PvLine { score, pv }],
+            };
+            context.report_iteration(&result);
+            break;
+        }
+    }
+
+    shared
+        .nodes
+        .fetch_add(context.nodes % CHECK_TIME_INTERVAL, Ordering::Relaxed);
+
+    if result.lines.is_empty() {
+        if !context.is_stopped() && !shared.options.quiet {
+            println!("info string no mate in {} found", mate);
+        }
+        result.best_move = first_legal_move(context.board);
+    }
+
+    result
+}
+
+impl<'a> SearchContext<'a> {
+    fn mate_search(&mut self, depth: i32, ply: usize, mut alpha: i32, mut beta: i32) -> i32 {
+        self.pv_length[ply] = ply;
+        self.seldepth = self.seldepth.max(ply);
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        self.add_node();
+
+        if ply > 0 {
+            alpha = alpha.max(-MATE_SCORE + ply as i32);
+            beta = beta.min(MATE_SCORE - ply as i32 - 1);
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+
+        // The root depth is odd, so the defending side is always to move when
+        // the depth runs out and only a mate counts.
+        let in_check = self.board.is_in_check();
+        if depth <= 0 {
+            return if in_check && first_legal_move(self.board).is_none() {
+                -MATE_SCORE + ply as i32
+            } else {
+                0
+            };
+        }
+
+        let hash = self.board.hash;
+        let tt_entry = self.shared.tt.probe(hash, ply);
+        if let Some(entry) = tt_entry {
+            if ply > 0 && entry.depth as i32 >= depth {
+                match entry.bound {
+                    Bound::Exact => return entry.score,
+                    Bound::Lower if entry.score >= beta => return beta,
+                    Bound::Upper if entry.score <= alpha => return alpha,
+                    _ => {}
+                }
+            }
+        }
+        let tt_move = tt_entry.and_then(|entry| entry.best_move);
+
+        let mut moves = [Move(0); 218];
+        let moves_count = self.board.get_moves(&mut moves);
+        let mut scores = [0; 218];
+        self.score_moves(&moves, &mut scores, moves_count, ply, tt_move);
+
+        // Legal moves are packed to the front of the list. For the attacking
+        // side checks are moved ahead of everything else, and on its last
+        // move the quiet ones are dropped.
+        let color = self.board.color_to_move;
+        let attacking = ply.is_multiple_of(2);
+        let mut legal_moves = 0;
+        let mut candidates = 0;
+
+        for index in 0..moves_count {
+            let m = moves[index];
+            self.board.make_move(&m);
+            let is_legal = !self.board.is_king_in_check(color);
+            let gives_check = is_legal && self.board.is_in_check();
+            self.board.undo_move(&m);
+
+            if !is_legal {
+                continue;
+            }
+            legal_moves += 1;
+
+            if attacking && depth == 1 && !gives_check {
+                continue;
+            }
+
+            moves[candidates] = m;
+            scores[candidates] = scores[index] + if gives_check { CHECK_BONUS } else { 0 };
+            candidates += 1;
+        }
+
+        if legal_moves == 0 {
+            return if in_check {
+                -MATE_SCORE + ply as i32
+            } else {
+                0
+            };
+        }
+
+        // A dropped quiet move does not mate, which is worth the same as any
+        // other line that finds no mate.
+        if candidates < legal_moves && alpha < 0 {
+            if beta <= 0 {
+                return beta;
+            }
+            alpha = 0;
+        }
+
+        let mut best_move = None;
+
+        for index in 0..candidates {
+            let m = pick_move(&mut moves, &mut scores, index, candidates);
+
+            self.board.make_move(&m);
+            if ply == 0 {
+                self.report_current_move(m, index + 1);
+            }
+
+            let score = -self.mate_search(depth - 1, ply + 1, -beta, -alpha);
+            self.board.undo_move(&m);
+
+            if self.is_stopped() {
+                return 0;
+            }
+
+            if score > alpha {
+                alpha = score;
+                best_move = Some(m);
+                self.update_pv(ply, m);
+
+                if score >= beta {
+                    self.store(hash, best_move, beta, depth, Bound::Lower, ply);
+                    return beta;
+                }
+            }
+        }
+
+        match best_move {
+            Some(_) => self.store(hash, best_move, alpha, depth, Bound::Exact, ply),
+            None => self.store(hash, tt_move, alpha, depth, Bound::Upper, ply),
+        }
+
+        alpha
+    }
+}
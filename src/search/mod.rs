@@ -0,0 +1,770 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::core::{Board, Move, MoveFlags, Piece};
+use crate::evaluation::{self, PawnTable};
+use crate::syzygy::{Tablebase, Wdl};
+
+mod mate;
+pub mod time;
+pub mod tt;
+
+pub use time::{SearchLimits, TimeManager};
+pub use tt::TranspositionTable;
+
+use tt::{Bound, Entry};
+
+pub const MAX_DEPTH: usize = 64;
+pub const INFINITY: i32 = 32000;
+pub const MATE_SCORE: i32 = 31000;
+pub const MATE_BOUND: i32 = MATE_SCORE - MAX_DEPTH as i32;
+/// Score of a tablebase win at the root, below the mate scores so that a mate
+/// found by the search is still preferred.
+pub const TB_WIN_SCORE: i32 = MATE_BOUND - MAX_DEPTH as i32;
+/// Scores beyond this are mates or tablebase wins, which depend on the ply
+/// they are found at.
+pub const WIN_BOUND: i32 = TB_WIN_SCORE - MAX_DEPTH as i32;
+
+pub const MAX_THREADS: usize = 256;
+pub const MAX_MULTI_PV: usize = 256;
+
+const CHECK_TIME_INTERVAL: u64 = 2048;
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub ponder_move: Option<Move>,
+    pub score: i32,
+    /// Depth of the last completed iteration, 0 if none was.
+    pub depth: u8,
+    pub nodes: u64,
+    pub lines: Vec<PvLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PvLine {
+    pub score: i32,
+    pub pv: Vec<Move>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub threads: usize,
+    pub multi_pv: usize,
+    /// Suppresses the `info` output, for searches run by the engine itself.
+    pub quiet: bool,
+    /// Syzygy tables probed at the root and inside the search.
+    pub tablebase: Option<Arc<Tablebase>>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            threads: 1,
+            multi_pv: 1,
+            quiet: false,
+            tablebase: None,
+        }
+    }
+}
+
+struct SharedState<'a> {
+    limits: &'a SearchLimits,
+    options: &'a SearchOptions,
+    time_manager: &'a TimeManager,
+    tt: &'a TranspositionTable,
+    stop: &'a AtomicBool,
+    nodes: AtomicU64,
+    /// Moves the root is restricted to, all legal moves if empty.
+    root_moves: Vec<Move>,
+    /// Whether the tablebase is probed inside the search, which is not needed
+    /// when the root moves were already ranked by it.
+    probe_tablebase: bool,
+    tb_hits: AtomicU64,
+}
+
+struct SearchContext<'a> {
+    board: &'a mut Board,
+    shared: &'a SharedState<'a>,
+    is_main: bool,
+    nodes: u64,
+    seldepth: usize,
+    root_depth: usize,
+    last_report: Duration,
+    root_move: Option<Move>,
+    excluded_root_moves: Vec<Move>,
+    pv_table: [[Move; MAX_DEPTH]; MAX_DEPTH],
+    pv_length: [usize; MAX_DEPTH],
+    pawn_table: PawnTable,
+}
+
+/// Runs a Lazy SMP search: every thread searches the same root on its own copy
+/// of the board and they cooperate only through the shared transposition
+/// table. The main thread owns the clock and prints an `info` line after every
+/// completed iteration and about once a second in between; the deepest
+/// finished result among all threads is returned once the main thread stops.
+/// A `mate` limit runs the dedicated mate solver on the calling thread instead.
+pub fn run(
+    board: &Board,
+    limits: &SearchLimits,
+    options: &SearchOptions,
+    time_manager: &TimeManager,
+    tt: &TranspositionTable,
+    stop: &AtomicBool,
+) -> SearchResult {
+    if let Some(mate) = limits.mate {
+        // The solver's "no mate found" scores are not real evaluations, so
+        // they are kept out of the main transposition table.
+        let mate_tt = TranspositionTable::new(mate::HASH_SIZE);
+        let shared = SharedState {
+            limits,
+            options,
+            time_manager,
+            tt: &mate_tt,
+            stop,
+            nodes: AtomicU64::new(0),
+            root_moves: Vec::new(),
+            probe_tablebase: false,
+            tb_hits: AtomicU64::new(0),
+        };
+
+        let mut result = mate::solve(&mut board.clone(), &shared, mate);
+        result.nodes = shared.nodes.load(Ordering::Relaxed);
+        return result;
+    }
+
+    // With the root in the tablebase, only the moves which keep the best
+    // result are searched.
+    let root_moves = options
+        .tablebase
+        .as_ref()
+        .and_then(|tablebase| tablebase.rank_root_moves(board))
+        .map(|ranks| {
+            let best_rank = ranks.iter().map(|&(_, rank)| rank).max();
+            ranks
+                .iter()
+                .filter(|&&(_, rank)| Some(rank) == best_rank)
+                .map(|&(m, _)| m)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let shared = SharedState {
+        limits,
+        options,
+        time_manager,
+        tt,
+        stop,
+        nodes: AtomicU64::new(0),
+        probe_tablebase: options.tablebase.is_some() && root_moves.is_empty(),
+        tb_hits: AtomicU64::new(if root_moves.is_empty() { 0 } else { 1 }),
+        root_moves,
+    };
+
+    tt.new_search();
+
+    let mut result = thread::scope(|scope| {
+        let helpers: Vec<_> = (1..options.threads.clamp(1, MAX_THREADS))
+            .map(|thread_id| {
+                let mut board = board.clone();
+                let shared = &shared;
+                scope.spawn(move || iterative_deepening(&mut board, shared, thread_id))
+            })
+            .collect();
+
+        let mut board = board.clone();
+        let mut result = iterative_deepening(&mut board, &shared, 0);
+        stop.store(true, Ordering::Relaxed);
+
+        for helper in helpers {
+            let helper_result = helper.join().expect("Search thread panicked");
+
+            // Helpers only search a single line, so they cannot replace a
+            // MultiPV result. Only completed iterations count, as one cut
+            // short by the stop has no score.
+            if options.multi_pv <= 1
+                && helper_result.depth > result.depth
+                && helper_result.best_move.is_some()
+            {
+                result = helper_result;
+            }
+        }
+
+        result
+    });
+
+    result.nodes = shared.nodes.load(Ordering::Relaxed);
+    result
+}
+
+fn iterative_deepening(board: &mut Board, shared: &SharedState, thread_id: usize) -> SearchResult {
+    let limits = shared.limits;
+
+    let mut result = SearchResult {
+        best_move: shared
+            .root_moves
+            .first()
+            .copied()
+            .or_else(|| first_legal_move(board)),
+        ponder_move: None,
+        score: 0,
+        depth: 0,
+        nodes: 0,
+        lines: Vec::new(),
+    };
+
+    let mut context = SearchContext::new(board, shared, thread_id == 0);
+
+    let max_depth = limits.depth.map_or(MAX_DEPTH - 1, |depth| {
+        (depth as usize).clamp(1, MAX_DEPTH - 1)
+    });
+
+    // Helpers start one ply deeper on odd threads so that the threads do not
+    // all work on the same iteration at the same time.
+    let start_depth = 1 + thread_id % 2;
+    let mut instability = 0.0;
+
+    let multi_pv = if thread_id == 0 {
+        shared.options.multi_pv.clamp(1, MAX_MULTI_PV)
+    } else {
+        1
+    };
+
+    for depth in start_depth..=max_depth {
+        context.root_depth = depth;
+        context.seldepth = 0;
+        context.excluded_root_moves.clear();
+
+        // Each MultiPV pass searches the root again without the moves that
+        // earlier passes of this iteration already reported. A pass cut short
+        // by a stop has no score, so only completed passes make lines.
+        let mut lines = Vec::with_capacity(multi_pv);
+        for _ in 0..multi_pv {
+            let score = context.negamax(depth as i32, 0, -INFINITY, INFINITY);
+            if context.is_stopped() || context.pv_length[0] == 0 {
+                break;
+            }
+
+            let pv = context.pv_table[0][..context.pv_length[0]].to_vec();
+            context.excluded_root_moves.push(pv[0]);
+            lines.push(PvLine { score, pv });
+        }
+
+        // Without a completed line the previous result, or the first legal
+        // move, is kept.
+        if lines.is_empty() || (context.is_stopped() && depth > start_depth) {
+            break;
+        }
+
+        lines.sort_by_key(|line| -line.score);
+        let best_move = lines[0].pv[0];
+        let score = lines[0].score;
+
+        if depth > start_depth {
+            instability *= 0.5;
+            if result.best_move != Some(best_move) {
+                instability += 1.0;
+            }
+        }
+        let fail_low = depth > start_depth && score < result.score - 30;
+
+        result = SearchResult {
+            best_move: Some(best_move),
+            ponder_move: lines[0].pv.get(1).copied(),
+            score,
+            depth: depth as u8,
+            nodes: context.total_nodes(),
+            lines,
+        };
+
+        context.root_move = Some(best_move);
+
+        if thread_id != 0 {
+            if context.is_stopped() {
+                break;
+            }
+            continue;
+        }
+
+        context.report_iteration(&result);
+
+        let scale = 1.0 + 0.5 * instability + if fail_low { 0.5 } else { 0.0 };
+        if context.is_stopped() || shared.time_manager.soft_limit_reached(scale) {
+            break;
+        }
+    }
+
+    shared
+        .nodes
+        .fetch_add(context.nodes % CHECK_TIME_INTERVAL, Ordering::Relaxed);
+
+    result
+}
+
+impl<'a> SearchContext<'a> {
+    fn new(board: &'a mut Board, shared: &'a SharedState<'a>, is_main: bool) -> Self {
+        SearchContext {
+            board,
+            shared,
+            is_main,
+            nodes: 0,
+            seldepth: 0,
+            root_depth: 0,
+            last_report: Duration::ZERO,
+            root_move: None,
+            excluded_root_moves: Vec::new(),
+            pv_table: [[Move(0); MAX_DEPTH]; MAX_DEPTH],
+            pv_length: [0; MAX_DEPTH],
+            pawn_table: PawnTable::default(),
+        }
+    }
+
+    fn negamax(&mut self, mut depth: i32, ply: usize, mut alpha: i32, mut beta: i32) -> i32 {
+        self.pv_length[ply] = ply;
+        self.seldepth = self.seldepth.max(ply);
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        // A repetition is scored as a draw at once, as the side which gains
+        // from it can repeat again.
+        if ply > 0 && (self.board.half_move_clock >= 100 || self.board.repetitions() > 0) {
+            return 0;
+        }
+
+        let in_check = self.board.is_in_check();
+        if in_check {
+            depth += 1;
+        }
+
+        if depth <= 0 || ply >= MAX_DEPTH - 1 {
+            return self.quiescence(ply, alpha, beta);
+        }
+
+        self.add_node();
+
+        // Mate-distance pruning: no line through this node can beat a mate
+        // that has already been found closer to the root, so the window is
+        // narrowed to the mates still reachable from it.
+        if ply > 0 {
+            alpha = alpha.max(-MATE_SCORE + ply as i32);
+            beta = beta.min(MATE_SCORE - ply as i32 - 1);
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+
+        let hash = self.board.hash;
+        let tt_entry = self.shared.tt.probe(hash, ply);
+        if let Some(entry) = tt_entry {
+            if ply > 0 && entry.depth as i32 >= depth {
+                match entry.bound {
+                    Bound::Exact => return entry.score,
+                    Bound::Lower if entry.score >= beta => return beta,
+                    Bound::Upper if entry.score <= alpha => return alpha,
+                    _ => {}
+                }
+            }
+        }
+        let tt_move = tt_entry.and_then(|entry| entry.best_move);
+
+        if ply > 0 {
+            if let Some(score) = self.probe_tablebase(hash, depth, ply, alpha, beta) {
+                return score;
+            }
+        }
+
+        let mut moves = [Move(0); 218];
+        let moves_count = self.board.get_moves(&mut moves);
+        let mut scores = [0; 218];
+        self.score_moves(&moves, &mut scores, moves_count, ply, tt_move);
+
+        let color = self.board.color_to_move;
+        let mut legal_moves = 0;
+        let mut best_move = None;
+
+        for index in 0..moves_count {
+            let m = pick_move(&mut moves, &mut scores, index, moves_count);
+            if ply == 0
+                && (self.excluded_root_moves.contains(&m)
+                    || !self.shared.root_moves.is_empty() && !self.shared.root_moves.contains(&m))
+            {
+                continue;
+            }
+
+            self.board.make_move(&m);
+            if self.board.is_king_in_check(color) {
+                self.board.undo_move(&m);
+                continue;
+            }
+
+            legal_moves += 1;
+            if ply == 0 {
+                self.report_current_move(m, legal_moves);
+            }
+
+            let score = -self.negamax(depth - 1, ply + 1, -beta, -alpha);
+            self.board.undo_move(&m);
+
+            if self.is_stopped() {
+                return 0;
+            }
+
+            if score > alpha {
+                alpha = score;
+                best_move = Some(m);
+                self.update_pv(ply, m);
+
+                if score >= beta {
+                    self.store(hash, best_move, beta, depth, Bound::Lower, ply);
+                    return beta;
+                }
+            }
+        }
+
+        if legal_moves == 0 {
+            if ply == 0 && !self.excluded_root_moves.is_empty() {
+                return alpha;
+            }
+
+            return if in_check {
+                -MATE_SCORE + ply as i32
+            } else {
+                0
+            };
+        }
+
+        match best_move {
+            Some(_) => self.store(hash, best_move, alpha, depth, Bound::Exact, ply),
+            None => self.store(hash, tt_move, alpha, depth, Bound::Upper, ply),
+        }
+
+        alpha
+    }
+
+    fn quiescence(&mut self, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.pv_length[ply] = ply;
+        self.seldepth = self.seldepth.max(ply);
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        self.add_node();
+
+        let stand_pat = evaluation::evaluate(self.board, &mut self.pawn_table);
+        if stand_pat >= beta || ply >= MAX_DEPTH - 1 {
+            return stand_pat;
+        }
+        if stand_pat > alpha {
+            alpha = stand_pat;
+        }
+
+        let mut moves = [Move(0); 218];
+        let moves_count = self.board.get_moves(&mut moves);
+        let mut scores = [0; 218];
+        self.score_moves(&moves, &mut scores, moves_count, ply, None);
+
+        let color = self.board.color_to_move;
+
+        for index in 0..moves_count {
+            let m = pick_move(&mut moves, &mut scores, index, moves_count);
+            if !m.is_capture() && m.flags() != MoveFlags::PROMOTE_TO_QUEEN {
+                continue;
+            }
+
+            self.board.make_move(&m);
+            if self.board.is_king_in_check(color) {
+                self.board.undo_move(&m);
+                continue;
+            }
+
+            let score = -self.quiescence(ply + 1, -beta, -alpha);
+            self.board.undo_move(&m);
+
+            if self.is_stopped() {
+                return 0;
+            }
+
+            if score > alpha {
+                alpha = score;
+                self.update_pv(ply, m);
+
+                if score >= beta {
+                    return beta;
+                }
+            }
+        }
+
+        alpha
+    }
+
+    // Probes the WDL tables right after a capture or a pawn move, when the
+    // result cannot be spoiled by moves played before. A win or a loss only
+    // bounds the score, as the search may find a faster one.
+    fn probe_tablebase(
+        &mut self,
+        hash: u64,
+        depth: i32,
+        ply: usize,
+        alpha: i32,
+        beta: i32,
+    ) -> Option<i32> {
+        let tablebase = self.shared.options.tablebase.as_ref()?;
+        if !self.shared.probe_tablebase || self.board.half_move_clock != 0 {
+            return None;
+        }
+
+        let wdl = tablebase.probe_wdl_in_place(self.board)?;
+        self.shared.tb_hits.fetch_add(1, Ordering::Relaxed);
+
+        let (score, bound) = match wdl {
+            Wdl::Win => (TB_WIN_SCORE - ply as i32, Bound::Lower),
+            Wdl::Loss => (-TB_WIN_SCORE + ply as i32, Bound::Upper),
+            Wdl::CursedWin | Wdl::Draw | Wdl::BlessedLoss => (0, Bound::Exact),
+        };
+
+        let cutoff = match bound {
+            Bound::Exact => Some(score),
+            Bound::Lower if score >= beta => Some(beta),
+            Bound::Upper if score <= alpha => Some(alpha),
+            _ => None,
+        };
+        if cutoff.is_some() {
+            let depth = (depth + 6).min(MAX_DEPTH as i32 - 1);
+            self.store(hash, None, score, depth, bound, ply);
+        }
+
+        cutoff
+    }
+
+    fn store(
+        &self,
+        hash: u64,
+        best_move: Option<Move>,
+        score: i32,
+        depth: i32,
+        bound: Bound,
+        ply: usize,
+    ) {
+        let entry = Entry {
+            best_move,
+            score,
+            depth: depth as u8,
+            bound,
+        };
+
+        self.shared.tt.store(hash, entry, ply);
+    }
+
+    fn add_node(&mut self) {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(CHECK_TIME_INTERVAL) {
+            self.shared
+                .nodes
+                .fetch_add(CHECK_TIME_INTERVAL, Ordering::Relaxed);
+        }
+    }
+
+    fn total_nodes(&self) -> u64 {
+        self.shared.nodes.load(Ordering::Relaxed) + self.nodes % CHECK_TIME_INTERVAL
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.shared.stop.load(Ordering::Relaxed)
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.is_stopped() {
+            return true;
+        }
+
+        let check_time = self.nodes.is_multiple_of(CHECK_TIME_INTERVAL);
+        if check_time {
+            self.report_progress();
+        }
+
+        let nodes_exceeded =
+            matches!(self.shared.limits.nodes, Some(nodes) if self.total_nodes() >= nodes);
+        let time_exceeded = check_time && self.shared.time_manager.hard_limit_reached();
+
+        if nodes_exceeded || time_exceeded {
+            self.shared.stop.store(true, Ordering::Relaxed);
+            return true;
+        }
+
+        false
+    }
+
+    fn score_moves(
+        &self,
+        moves: &[Move],
+        scores: &mut [i32],
+        moves_count: usize,
+        ply: usize,
+        tt_move: Option<Move>,
+    ) {
+        let pv_move = if ply == 0 {
+            self.root_move.or(tt_move)
+        } else {
+            tt_move
+        };
+
+        for index in 0..moves_count {
+            let m = moves[index];
+
+            scores[index] = if Some(m) == pv_move {
+                INFINITY
+            } else if m.is_capture() {
+                let victim = if m.flags() == MoveFlags::EN_PASSANT {
+                    Piece::WP
+                } else {
+                    self.board
+                        .piece_at_square(m.to())
+                        .expect("No piece to capture")
+                };
+                let attacker = self
+                    .board
+                    .piece_at_square(m.from())
+                    .expect("No piece to move");
+
+                10 * evaluation::piece_value(victim) - evaluation::piece_value(attacker)
+                    + evaluation::QUEEN_VALUE
+            } else if m.is_promotion() {
+                evaluation::QUEEN_VALUE
+            } else {
+                0
+            };
+        }
+    }
+
+    fn report_iteration(&mut self, result: &SearchResult) {
+        if self.shared.options.quiet {
+            return;
+        }
+
+        let statistics = self.statistics();
+
+        for (index, line) in result.lines.iter().enumerate() {
+            let pv: Vec<String> = line.pv.iter().map(|m| m.as_string()).collect();
+
+            println!(
+                "info depth {} seldepth {} multipv {} score {} {} pv {}",
+                result.depth,
+                self.seldepth,
+                index + 1,
+                format_score(line.score),
+                statistics,
+                pv.join(" ")
+            );
+        }
+        self.last_report = self.shared.time_manager.elapsed();
+    }
+
+    fn report_progress(&mut self) {
+        let elapsed = self.shared.time_manager.elapsed();
+        if !self.is_main
+            || self.shared.options.quiet
+            || elapsed < self.last_report + REPORT_INTERVAL
+        {
+            return;
+        }
+
+        println!(
+            "info depth {} seldepth {} {}",
+            self.root_depth,
+            self.seldepth,
+            self.statistics()
+        );
+        self.last_report = elapsed;
+    }
+
+    fn report_current_move(&self, m: Move, move_number: usize) {
+        if self.is_main
+            && !self.shared.options.quiet
+            && self.shared.time_manager.elapsed() >= REPORT_INTERVAL
+        {
+            println!(
+                "info depth {} currmove {} currmovenumber {}",
+                self.root_depth,
+                m.as_string(),
+                move_number
+            );
+        }
+    }
+
+    fn statistics(&self) -> String {
+        let elapsed = self.shared.time_manager.elapsed();
+        let nodes = self.total_nodes();
+        let nps = (nodes as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+
+        let mut statistics = format!(
+            "nodes {} nps {} time {} hashfull {}",
+            nodes,
+            nps,
+            elapsed.as_millis(),
+            self.shared.tt.hashfull()
+        );
+        if self.shared.options.tablebase.is_some() {
+            statistics += &format!(" tbhits {}", self.shared.tb_hits.load(Ordering::Relaxed));
+        }
+
+        statistics
+    }
+
+    fn update_pv(&mut self, ply: usize, m: Move) {
+        self.pv_table[ply][ply] = m;
+        for next in (ply + 1)..self.pv_length[ply + 1] {
+            self.pv_table[ply][next] = self.pv_table[ply + 1][next];
+        }
+        self.pv_length[ply] = self.pv_length[ply + 1].max(ply + 1);
+    }
+}
+
+/// Formats a score for UCI output, as `mate N` in moves when a forced mate
+/// has been found and as `cp N` otherwise.
+pub fn format_score(score: i32) -> String {
+    if score > MATE_BOUND {
+        format!("mate {}", (MATE_SCORE - score + 1) / 2)
+    } else if score < -MATE_BOUND {
+        format!("mate -{}", (MATE_SCORE + score) / 2)
+    } else {
+        format!("cp {}", score)
+    }
+}
+
+fn pick_move(moves: &mut [Move], scores: &mut [i32], index: usize, moves_count: usize) -> Move {
+    let mut best_index = index;
+    for next in (index + 1)..moves_count {
+        if scores[next] > scores[best_index] {
+            best_index = next;
+        }
+    }
+
+    moves.swap(index, best_index);
+    scores.swap(index, best_index);
+
+    moves[index]
+}
+
+fn first_legal_move(board: &mut Board) -> Option<Move> {
+    let mut moves = [Move(0); 218];
+    let moves_count = board.get_moves(&mut moves);
+    let color = board.color_to_move;
+
+    for m in moves.iter().take(moves_count) {
+        board.make_move(m);
+        let is_legal = !board.is_king_in_check(color);
+        board.undo_move(m);
+
+        if is_legal {
+            return Some(*m);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::core::Color;
+
+const MOVE_OVERHEAD: u64 = 30;
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+const HARD_LIMIT_FACTOR: u64 = 4;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub white_time: Option<u64>,
+    pub black_time: Option<u64>,
+    pub white_increment: Option<u64>,
+    pub black_increment: Option<u64>,
+    pub moves_to_go: Option<u64>,
+    pub move_time: Option<u64>,
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    pub mate: Option<u8>,
+    pub infinite: bool,
+    pub ponder: bool,
+}
+
+impl SearchLimits {
+    pub fn time_for_color(&self, color: Color) -> (Option<u64>, u64) {
+        if color == Color::White {
+            (self.white_time, self.white_increment.unwrap_or(0))
+        } else {
+            (self.black_time, self.black_increment.unwrap_or(0))
+        }
+    }
+}
+
+/// Turns the clock parameters of a `go` command into a soft limit, checked
+/// between iterations, and a hard limit, checked every few nodes. While
+/// pondering neither limit applies; the clock starts running on `ponderhit`.
+pub struct TimeManager {
+    start: Instant,
+    soft_limit: Option<Duration>,
+    hard_limit: Option<Duration>,
+    pondering: AtomicBool,
+    ponderhit_elapsed: AtomicU64,
+}
+
+impl TimeManager {
+    pub fn new(limits: &SearchLimits, color: Color) -> Self {
+        let (soft_limit, hard_limit) = if limits.infinite {
+            (None, None)
+        } else if let Some(move_time) = limits.move_time {
+            let limit = Duration::from_millis(move_time.saturating_sub(MOVE_OVERHEAD));
+            (Some(limit), Some(limit))
+        } else {
+            match limits.time_for_color(color) {
+                (Some(time), increment) => {
+                    let available = time.saturating_sub(MOVE_OVERHEAD);
+                    let moves_to_go = limits.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+                    let hard = (available / moves_to_go * HARD_LIMIT_FACTOR + increment)
+                        .min(available * 3 / 4);
+                    let soft = (available / moves_to_go + increment * 3 / 4).min(hard);
+
+                    (
+                        Some(Duration::from_millis(soft)),
+                        Some(Duration::from_millis(hard)),
+                    )
+                }
+                (None, _) => (None, None),
+            }
+        };
+
+        TimeManager {
+            start: Instant::now(),
+            soft_limit,
+            hard_limit,
+            pondering: AtomicBool::new(limits.ponder),
+            ponderhit_elapsed: AtomicU64::new(0),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn soft_limit(&self) -> Option<Duration> {
+        self.soft_limit
+    }
+
+    pub fn hard_limit(&self) -> Option<Duration> {
+        self.hard_limit
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.pondering.load(Ordering::Relaxed)
+    }
+
+    pub fn ponderhit(&self) {
+        self.ponderhit_elapsed
+            .store(self.elapsed().as_micros() as u64, Ordering::Relaxed);
+        self.pondering.store(false, Ordering::Relaxed);
+    }
+
+    /// Checked after each completed iteration. `scale` stretches the soft
+    /// limit when the search is unstable, but never beyond the hard limit.
+    pub fn soft_limit_reached(&self, scale: f64) -> bool {
+        if self.is_pondering() {
+            return false;
+        }
+
+        match (self.soft_limit, self.hard_limit) {
+            (Some(soft), Some(hard)) => self.elapsed_on_clock() >= soft.mul_f64(scale).min(hard),
+            _ => false,
+        }
+    }
+
+    pub fn hard_limit_reached(&self) -> bool {
+        if self.is_pondering() {
+            return false;
+        }
+
+        match self.hard_limit {
+            Some(hard) => self.elapsed_on_clock() >= hard,
+            None => false,
+        }
+    }
+
+    fn elapsed_on_clock(&self) -> Duration {
+        let ponderhit_elapsed =
+            Duration::from_micros(self.ponderhit_elapsed.load(Ordering::Relaxed));
+        self.elapsed().saturating_sub(ponderhit_elapsed)
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use super::WIN_BOUND;
+use crate::core::Move;
+
+pub const DEFAULT_HASH_SIZE: usize = 16;
+
+const ENTRY_SIZE: usize = 16;
+const GENERATION_MASK: u8 = 0x3f;
+const HASHFULL_SAMPLE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub depth: u8,
+    pub bound: Bound,
+}
+
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/// Transposition table shared by all search threads without locking. Each slot
+/// stores the key xor-ed with its data, so a slot torn by two threads writing
+/// at once fails the key check on probe instead of returning bad data.
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> Self {
+        let count = 1 << (size_mb.max(1) * 1024 * 1024 / ENTRY_SIZE).ilog2();
+        let mut slots = Vec::with_capacity(count);
+        slots.resize_with(count, || Slot {
+            key: AtomicU64::new(0),
+            data: AtomicU64::new(0),
+        });
+
+        TranspositionTable {
+            slots,
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn new_search(&self) {
+        let generation = (self.generation() + 1) & GENERATION_MASK;
+        self.generation.store(generation, Ordering::Relaxed);
+    }
+
+    /// Permille of sampled slots written during the current search.
+    pub fn hashfull(&self) -> usize {
+        let generation = self.generation() as u64;
+
+        self.slots
+            .iter()
+            .take(HASHFULL_SAMPLE)
+            .filter(|slot| {
+                let data = slot.data.load(Ordering::Relaxed);
+                data != 0 && (data >> 42) & GENERATION_MASK as u64 == generation
+            })
+            .count()
+            * 1000
+            / HASHFULL_SAMPLE.min(self.slots.len())
+    }
+
+    pub fn probe(&self, hash: u64, ply: usize) -> Option<Entry> {
+        let slot = &self.slots[self.index(hash)];
+        let data = slot.data.load(Ordering::Relaxed);
+
+        if data == 0 || slot.key.load(Ordering::Relaxed) ^ data != hash {
+            return None;
+        }
+
+        Some(unpack(data, ply))
+    }
+
+    pub fn store(&self, hash: u64, entry: Entry, ply: usize) {
+        let slot = &self.slots[self.index(hash)];
+        let data = pack(entry, ply) | ((self.generation() as u64) << 42);
+
+        slot.key.store(hash ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    fn generation(&self) -> u8 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    fn index(&self, hash: u64) -> usize {
+        (hash as usize) & (self.slots.len() - 1)
+    }
+}
+
+// Mate and tablebase scores are stored relative to the node rather than the
+// root, so they stay correct when the position is reached at a different ply.
+fn pack(entry: Entry, ply: usize) -> u64 {
+    let score = if entry.score > WIN_BOUND {
+        entry.score + ply as i32
+    } else if entry.score < -WIN_BOUND {
+        entry.score - ply as i32
+    } else {
+        entry.score
+    };
+    let bound = match entry.bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3,
+    };
+    let best_move = entry.best_move.map_or(0, |m| m.0);
+
+    (best_move as u64)
+        | ((score as i16 as u16 as u64) << 16)
+        | ((entry.depth as u64) << 32)
+        | (bound << 40)
+}
+
+fn unpack(data: u64, ply: usize) -> Entry {
+    let best_move = (data & 0xffff) as u16;
+    let score = ((data >> 16) & 0xffff) as u16 as i16 as i32;
+    let score = if score > WIN_BOUND {
+        score - ply as i32
+    } else if score < -WIN_BOUND {
+        score + ply as i32
+    } else {
+        score
+    };
+    let bound = match (data >> 40) & 3 {
+        1 => Bound::Exact,
+        2 => Bound::Lower,
+        _ => Bound::Upper,
+    };
+
+    Entry {
+        best_move: if best_move == 0 {
+            None
+        } else {
+            Some(Move(best_move))
+        },
+        score,
+        depth: ((data >> 32) & 0xff) as u8,
+        bound,
+    }
+}
@@ -0,0 +1,500 @@
+mod table;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::ops::Neg;
+
+use crate::core::board::CastlingRights;
+use crate::core::{Board, Color, Move, Piece, PieceType, Square};
+use table::{LazyTable, Lookup, Material, ProbePosition, TableType};
+
+pub use table::MAX_PIECES;
+
+/// Rank of the root moves with a certain win or loss, see `rank_root_moves`.
+pub const MAX_DTZ: i32 = 1 << 18;
+
+#[cfg(windows)]
+const PATH_SEPARATOR: char = ';';
+#[cfg(not(windows))]
+const PATH_SEPARATOR: char = ':';
+
+/// Result of a position under the 50-move rule, for the side to move. Cursed
+/// wins and blessed losses are wins and losses which the 50-move rule turns
+/// into draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            -2 => Some(Wdl::Loss),
+            -1 => Some(Wdl::BlessedLoss),
+            0 => Some(Wdl::Draw),
+            1 => Some(Wdl::CursedWin),
+            2 => Some(Wdl::Win),
+            _ => None,
+        }
+    }
+
+    fn signum(self) -> i32 {
+        match self {
+            Wdl::Loss | Wdl::BlessedLoss => -1,
+            Wdl::Draw => 0,
+            Wdl::CursedWin | Wdl::Win => 1,
+        }
+    }
+
+    // Distance to zeroing of a position whose best move is a capture or a
+    // pawn move, counting that move.
+    fn dtz_before_zeroing(self) -> i32 {
+        match self {
+            Wdl::Loss => -1,
+            Wdl::BlessedLoss => -101,
+            Wdl::Draw => 0,
+            Wdl::CursedWin => 101,
+            Wdl::Win => 1,
+        }
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Wdl;
+
+    fn neg(self) -> Wdl {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::BlessedLoss => Wdl::CursedWin,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::CursedWin => Wdl::BlessedLoss,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+}
+
+/// The Syzygy tables found in a set of directories. Files are opened and
+/// memory mapped on their first probe.
+pub struct Tablebase {
+    path: String,
+    wdl: HashMap<String, LazyTable>,
+    dtz: HashMap<String, LazyTable>,
+    max_pieces: usize,
+}
+
+impl Tablebase {
+    /// Finds the tables in `path`, a list of directories separated by `:`
+    /// (`;` on Windows). Files which are not named after a material
+    /// signature, such as `KRvK.rtbw`, are ignored.
+    pub fn new(path: &str) -> Result<Self, String> {
+        let mut tablebase = Tablebase {
+            path: path.to_string(),
+            wdl: HashMap::new(),
+            dtz: HashMap::new(),
+            max_pieces: 0,
+        };
+
+        for directory in path.split(PATH_SEPARATOR).filter(|d| !d.is_empty()) {
+            let entries = fs::read_dir(directory).map_err(|e| format!("{}: {}", directory, e))?;
+
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let (Some(name), Some(extension)) = (
+                    path.file_stem().and_then(|name| name.to_str()),
+                    path.extension().and_then(|extension| extension.to_str()),
+                ) else {
+                    continue;
+                };
+                let (table_type, tables) = match extension {
+                    "rtbw" => (TableType::Wdl, &mut tablebase.wdl),
+                    "rtbz" => (TableType::Dtz, &mut tablebase.dtz),
+                    _ => continue,
+                };
+                let Some(material) = Material::parse(name) else {
+                    continue;
+                };
+
+                if table_type == TableType::Wdl {
+                    tablebase.max_pieces = tablebase.max_pieces.max(material.piece_count);
+                }
+                tables.insert(
+                    name.to_string(),
+                    LazyTable::new(path.to_string_lossy().into_owned(), table_type, material),
+                );
+            }
+        }
+
+        Ok(tablebase)
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Number of WDL tables found.
+    pub fn len(&self) -> usize {
+        self.wdl.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wdl.is_empty()
+    }
+
+    /// Largest number of pieces, kings included, of the WDL tables found.
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    /// Returns true if the tables can have `board`: it has few enough pieces
+    /// and no castling rights.
+    pub fn can_probe(&self, board: &Board) -> bool {
+        board.castling_rights == CastlingRights::empty()
+            && board.all_occupancy().pop_count() as usize <= self.max_pieces
+    }
+
+    /// Probes the WDL tables. Returns `None` if the position is not in the
+    /// tables or a table is missing.
+    pub fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        self.probe_wdl_in_place(&mut board.clone())
+    }
+
+    /// Probes the WDL tables like `probe_wdl`, playing the captures which
+    /// resolve the position on `board` itself and taking them back, so that
+    /// the search does not copy its board. The network is left out of these
+    /// moves and `board` is restored as it was.
+    pub fn probe_wdl_in_place(&self, board: &mut Board) -> Option<Wdl> {
+        if !self.can_probe(board) {
+            return None;
+        }
+
+        let nnue = board.nnue.take();
+        let wdl = self.search(board, false).map(|(wdl, _)| wdl);
+        board.nnue = nnue;
+
+        wdl
+    }
+
+    /// Probes the DTZ tables for the number of plies to the next capture or
+    /// pawn move of the best line, positive for a win and negative for a
+    /// loss. Cursed wins and blessed losses are counted 100 plies further
+    /// than their distance, and a draw is 0.
+    pub fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        if !self.can_probe(board) {
+            return None;
+        }
+
+        self.dtz(&mut board.clone())
+    }
+
+    /// Ranks the legal moves of `board` by the tables, higher ranks being
+    /// better. Wins which the 50-move rule cannot spoil rank `MAX_DTZ`, and
+    /// losses which it cannot save rank `-MAX_DTZ`, in between moves rank by
+    /// their distance to zeroing. The DTZ tables are used if they are all
+    /// there, else the moves are ranked by the WDL tables alone.
+    pub fn rank_root_moves(&self, board: &Board) -> Option<Vec<(Move, i32)>> {
+        if !self.can_probe(board) {
+            return None;
+        }
+
+        let mut board = board.clone();
+        self.rank_by_dtz(&mut board)
+            .or_else(|| self.rank_by_wdl(&mut board))
+    }
+
+    fn rank_by_dtz(&self, board: &mut Board) -> Option<Vec<(Move, i32)>> {
+        let half_move_clock = board.half_move_clock as i32;
+        let repeated = board.repetitions() > 0;
+        let mut ranks = Vec::new();
+
+        for m in legal_moves(board) {
+            board.make_move(&m);
+
+            let dtz = if board.half_move_clock == 0 {
+                self.search(board, false)
+                    .map(|(wdl, _)| (-wdl).dtz_before_zeroing())
+            } else if board.repetitions() > 0 {
+                Some(0)
+            } else {
+                self.dtz(board).map(|dtz| -dtz + (-dtz).signum())
+            };
+
+            // A mate is one ply from zeroing, as the game ends there.
+            let dtz = dtz.map(|dtz| {
+                if dtz == 2 && board.is_in_check() && legal_moves(board).is_empty() {
+                    1
+                } else {
+                    dtz
+                }
+            });
+
+            board.undo_move(&m);
+            let dtz = dtz?;
+
+            let rank = if dtz > 0 {
+                if dtz + half_move_clock <= 99 && !repeated {
+                    MAX_DTZ
+                } else {
+                    MAX_DTZ - (dtz + half_move_clock)
+                }
+            } else if dtz < 0 {
+                if -dtz * 2 + half_move_clock < 100 {
+                    -MAX_DTZ
+                } else {
+                    -MAX_DTZ + (-dtz + half_move_clock)
+                }
+            } else {
+                0
+            };
+            ranks.push((m, rank));
+        }
+
+        Some(ranks)
+    }
+
+    fn rank_by_wdl(&self, board: &mut Board) -> Option<Vec<(Move, i32)>> {
+        let mut ranks = Vec::new();
+
+        for m in legal_moves(board) {
+            board.make_move(&m);
+            let wdl = if board.repetitions() > 0 {
+                Some(Wdl::Draw)
+            } else {
+                self.search(board, false).map(|(wdl, _)| -wdl)
+            };
+            board.undo_move(&m);
+
+            let rank = match wdl? {
+                Wdl::Loss => -MAX_DTZ,
+                Wdl::BlessedLoss => -MAX_DTZ + 101,
+                Wdl::Draw => 0,
+                Wdl::CursedWin => MAX_DTZ - 101,
+                Wdl::Win => MAX_DTZ,
+            };
+            ranks.push((m, rank));
+        }
+
+        Some(ranks)
+    }
+
+    // The tables do not know about en passant and may store any value for a
+    // position whose best move is a capture, so the captures, and the pawn
+    // moves for DTZ, are searched before the table is probed. The second value
+    // is true if the best move is such a zeroing move.
+    fn search(&self, board: &mut Board, check_zeroing: bool) -> Option<(Wdl, bool)> {
+        let moves = legal_moves(board);
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+
+        for m in &moves {
+            let zeroing = m.is_capture() || check_zeroing && is_pawn_move(board, m);
+            if !zeroing {
+                continue;
+            }
+
+            searched += 1;
+            board.make_move(m);
+            let value = self.search(board, false).map(|(wdl, _)| -wdl);
+            board.undo_move(m);
+            let value = value?;
+
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        // When every legal move was searched, the table is not needed and
+        // could be wrong, as with en passant.
+        let no_more_moves = searched > 0 && searched == moves.len();
+        let value = if no_more_moves {
+            best
+        } else {
+            match self.probe_table(board, TableType::Wdl, Wdl::Draw)? {
+                Lookup::Value(value) => Wdl::from_i32(value)?,
+                Lookup::ChangeStm => return None,
+            }
+        };
+
+        if best >= value {
+            Some((best, best > Wdl::Draw || no_more_moves))
+        } else {
+            Some((value, false))
+        }
+    }
+
+    fn dtz(&self, board: &mut Board) -> Option<i32> {
+        let (wdl, zeroing) = self.search(board, true)?;
+
+        // The tables do not store draws, and may store anything when the best
+        // move zeroes the counter.
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if zeroing {
+            return Some(wdl.dtz_before_zeroing());
+        }
+
+        match self.probe_table(board, TableType::Dtz, wdl)? {
+            Lookup::Value(dtz) => {
+                let cursed = matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss);
+                return Some((dtz + if cursed { 100 } else { 0 }) * wdl.signum());
+            }
+            Lookup::ChangeStm => {}
+        }
+
+        // The table is stored for the other side to move, so look one ply
+        // ahead for the best distance of the moves which keep the result.
+        let mut min_dtz = i32::MAX;
+        for m in legal_moves(board) {
+            let zeroing = m.is_capture() || is_pawn_move(board, &m);
+            board.make_move(&m);
+
+            // A zeroing move starts over, so its distance is the one before
+            // the move, from the result after it.
+            let dtz = if zeroing {
+                self.search(board, false)
+                    .map(|(wdl, _)| -(wdl.dtz_before_zeroing()))
+            } else {
+                self.dtz(board).map(|dtz| -dtz)
+            };
+            let mates = dtz == Some(1) && board.is_in_check() && legal_moves(board).is_empty();
+            board.undo_move(&m);
+
+            let mut dtz = dtz?;
+            if mates {
+                min_dtz = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < min_dtz && dtz.signum() == wdl.signum() {
+                min_dtz = dtz;
+            }
+        }
+
+        Some(if min_dtz == i32::MAX { -1 } else { min_dtz })
+    }
+
+    fn probe_table(&self, board: &Board, table_type: TableType, wdl: Wdl) -> Option<Lookup> {
+        if board.all_occupancy().pop_count() == 2 {
+            return Some(Lookup::Value(0));
+        }
+
+        let tables = match table_type {
+            TableType::Wdl => &self.wdl,
+            TableType::Dtz => &self.dtz,
+        };
+
+        let white = material_name(board, Color::White);
+        let black = material_name(board, Color::Black);
+        let black_to_move = board.color_to_move == Color::Black;
+
+        // Tables are named with the stronger side first and store both
+        // colours of their material, so the colours of the board are swapped
+        // when black is the stronger side, or when the material is the same
+        // for both sides and black is to move.
+        let (table, flip) = match tables.get(&format!("{}v{}", white, black)) {
+            Some(table) => (table, white == black && black_to_move),
+            None => (tables.get(&format!("{}v{}", black, white))?, true),
+        };
+
+        let mut pieces = Vec::with_capacity(MAX_PIECES);
+        let mut occupancy = board.all_occupancy();
+        while occupancy.is_not_empty() {
+            let square = Square(occupancy.bit_scan());
+            let piece = board.piece_at_square(square).unwrap().to_u8();
+            pieces.push((square.to_usize(), piece % 6 + 1 + 8 * (piece / 6)));
+
+            occupancy = occupancy.pop_lsb();
+        }
+
+        let position = ProbePosition {
+            pieces,
+            flip,
+            stm: (flip ^ black_to_move) as usize,
+        };
+
+        table.get()?.probe(&position, wdl)
+    }
+}
+
+impl fmt::Debug for Tablebase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tablebase")
+            .field("path", &self.path)
+            .field("wdl", &self.wdl.len())
+            .field("dtz", &self.dtz.len())
+            .field("max_pieces", &self.max_pieces)
+            .finish()
+    }
+}
+
+// Two tablebases are the same if they were loaded from the same path.
+impl PartialEq for Tablebase {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl Eq for Tablebase {}
+
+// Material of one side as written in table names, such as `KRP`.
+fn material_name(board: &Board, color: Color) -> String {
+    let pieces = if color == Color::White {
+        [
+            Piece::WK,
+            Piece::WQ,
+            Piece::WR,
+            Piece::WB,
+            Piece::WN,
+            Piece::WP,
+        ]
+    } else {
+        [
+            Piece::BK,
+            Piece::BQ,
+            Piece::BR,
+            Piece::BB,
+            Piece::BN,
+            Piece::BP,
+        ]
+    };
+
+    pieces
+        .iter()
+        .zip("KQRBNP".chars())
+        .flat_map(|(piece, name)| {
+            let count = board.pieces[piece.to_usize()].pop_count() as usize;
+            std::iter::repeat_n(name, count)
+        })
+        .collect()
+}
+
+fn is_pawn_move(board: &Board, m: &Move) -> bool {
+    board.piece_at_square(m.from()).map(Piece::kind) == Some(PieceType::Pawn)
+}
+
+fn legal_moves(board: &mut Board) -> Vec<Move> {
+    let mut moves = [Move(0); 218];
+    let moves_count = board.get_moves(&mut moves);
+    let color = board.color_to_move;
+
+    moves[..moves_count]
+        .iter()
+        .copied()
+        .filter(|m| {
+            board.make_move(m);
+            let is_legal = !board.is_king_in_check(color);
+            board.undo_move(m);
+
+            is_legal
+        })
+        .collect()
+}
@@ -0,0 +1,915 @@
+use std::fs::File;
+use std::sync::OnceLock;
+
+use memmap2::Mmap;
+
+use super::Wdl;
+
+pub const MAX_PIECES: usize = 7;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+// Flags of the pairs data. All but the last refer to DTZ tables only.
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_WIDE: u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableType {
+    Wdl,
+    Dtz,
+}
+
+/// Indexing tables shared by every table file.
+struct Maps {
+    pawns: [usize; 64],
+    b1h1h7: [usize; 64],
+    a1d1d4: [usize; 64],
+    kk: [[u64; 64]; 10],
+    binomial: [[u64; 64]; 6],
+    lead_pawn_idx: [[u64; 64]; 6],
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+fn maps() -> &'static Maps {
+    static MAPS: OnceLock<Maps> = OnceLock::new();
+    MAPS.get_or_init(Maps::new)
+}
+
+fn off_a1h8(square: usize) -> i32 {
+    (square / 8) as i32 - (square % 8) as i32
+}
+
+impl Maps {
+    fn new() -> Self {
+        let mut maps = Maps {
+            pawns: [0; 64],
+            b1h1h7: [0; 64],
+            a1d1d4: [0; 64],
+            kk: [[0; 64]; 10],
+            binomial: [[0; 64]; 6],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        // Squares below the a1-h8 diagonal are numbered 0..27.
+        let mut code = 0;
+        for square in 0..64 {
+            if off_a1h8(square) < 0 {
+                maps.b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        // Squares of the a1-d1-d4 triangle are numbered 0..9, the diagonal last.
+        let mut diagonal = Vec::new();
+        code = 0;
+        for square in 0..28 {
+            if off_a1h8(square) < 0 && square % 8 <= 3 {
+                maps.a1d1d4[square] = code;
+                code += 1;
+            } else if off_a1h8(square) == 0 && square % 8 <= 3 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            maps.a1d1d4[square] = code;
+            code += 1;
+        }
+
+        // The 462 placements of two kings with the first in the a1-d1-d4
+        // triangle, and the second below the diagonal if the first is on it.
+        let mut both_on_diagonal = Vec::new();
+        let mut code = 0;
+        for index in 0..10 {
+            for first in 0..28 {
+                if maps.a1d1d4[first] != index || (index == 0 && first != 1) {
+                    continue;
+                }
+
+                for second in 0..64 {
+                    let touching = (first / 8).abs_diff(second / 8) <= 1
+                        && (first % 8).abs_diff(second % 8) <= 1;
+
+                    if touching || (off_a1h8(first) == 0 && off_a1h8(second) > 0) {
+                        continue;
+                    } else if off_a1h8(first) == 0 && off_a1h8(second) == 0 {
+                        both_on_diagonal.push((index, second));
+                    } else {
+                        maps.kk[index][second] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (index, second) in both_on_diagonal {
+            maps.kk[index][second] = code;
+            code += 1;
+        }
+
+        maps.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..6.min(n + 1) {
+                maps.binomial[k][n] = if k > 0 {
+                    maps.binomial[k - 1][n - 1]
+                } else {
+                    0
+                } + if k < n { maps.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        // Pawns are numbered from the edge files and the lowest ranks, so that
+        // the leading pawn is the one with the highest number.
+        let mut available = 47;
+        for lead_pawns in 1..=5 {
+            for file in 0..4 {
+                let mut index = 0;
+
+                for rank in 1..7 {
+                    let square = 8 * rank + file;
+                    if lead_pawns == 1 {
+                        maps.pawns[square] = available;
+                        maps.pawns[square ^ 7] = available - 1;
+                        available -= 2;
+                    }
+
+                    maps.lead_pawn_idx[lead_pawns][square] = index;
+                    index += maps.binomial[lead_pawns - 1][maps.pawns[square]];
+                }
+
+                maps.lead_pawns_size[lead_pawns][file] = index;
+            }
+        }
+
+        maps
+    }
+}
+
+/// Material of a table, parsed from its name such as `KRPvKR`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Material {
+    pub piece_count: usize,
+    pub has_pawns: bool,
+    pub has_unique_pieces: bool,
+    pub symmetric: bool,
+    /// Pawns of the leading side and of the other side.
+    pub pawn_count: [usize; 2],
+}
+
+impl Material {
+    pub fn parse(name: &str) -> Option<Self> {
+        let (white, black) = name.split_once('v')?;
+        let sides = [white, black];
+        if sides
+            .iter()
+            .any(|side| !side.starts_with('K') || !side.chars().all(|c| "KQRBNP".contains(c)))
+            || sides
+                .iter()
+                .map(|side| side.matches('K').count())
+                .sum::<usize>()
+                != 2
+        {
+            return None;
+        }
+
+        let count = |side: &str, piece: char| side.chars().filter(|&c| c == piece).count();
+        let piece_count = white.len() + black.len();
+        if piece_count > MAX_PIECES {
+            return None;
+        }
+
+        let has_unique_pieces = sides
+            .iter()
+            .any(|side| "QRBNP".chars().any(|piece| count(side, piece) == 1));
+
+        // The side with fewer pawns leads, as it compresses better.
+        let (white_pawns, black_pawns) = (count(white, 'P'), count(black, 'P'));
+        let white_leads = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+        let pawn_count = if white_leads {
+            [white_pawns, black_pawns]
+        } else {
+            [black_pawns, white_pawns]
+        };
+
+        Some(Material {
+            piece_count,
+            has_pawns: white_pawns + black_pawns > 0,
+            has_unique_pieces,
+            symmetric: white == black,
+            pawn_count,
+        })
+    }
+}
+
+// Decoding information of one of the sub-tables of a file. A file has one for
+// each side to move it stores and, with pawns, for each file of the leading
+// pawn.
+#[derive(Debug, Default, Clone)]
+struct PairsData {
+    flags: u8,
+    max_sym_len: u8,
+    min_sym_len: u8,
+    num_blocks: usize,
+    block_size: usize,
+    span: usize,
+    lowest_sym: usize,
+    btree: usize,
+    block_length: usize,
+    block_length_size: usize,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    data: usize,
+    base64: Vec<u64>,
+    symlen: Vec<u8>,
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    map_idx: [usize; 4],
+}
+
+/// A memory mapped WDL or DTZ file.
+pub struct Table {
+    mmap: Mmap,
+    table_type: TableType,
+    material: Material,
+    pairs: [[PairsData; 4]; 2],
+    dtz_map: usize,
+}
+
+/// A position prepared for a table probe: the squares and pieces, with
+/// pieces numbered as in the files (1 to 6 for white pawn to king, 9 to 14
+/// for black), from the point of view of the side the table is stored for.
+pub struct ProbePosition {
+    /// Squares and pieces of every piece on the board.
+    pub pieces: Vec<(usize, u8)>,
+    /// Whether the colours have to be swapped to match the table.
+    pub flip: bool,
+    /// Side to move after the colours have been swapped, 0 for white.
+    pub stm: usize,
+}
+
+/// Result of a table lookup: a DTZ table stores only one side to move.
+pub enum Lookup {
+    Value(i32),
+    ChangeStm,
+}
+
+impl Table {
+    pub fn open(path: &str, table_type: TableType, material: Material) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+
+        // Tablebase files are never modified while the engine runs.
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|e| format!("{}: {}", path, e))?;
+
+        let magic = match table_type {
+            TableType::Wdl => WDL_MAGIC,
+            TableType::Dtz => DTZ_MAGIC,
+        };
+        if mmap.len() < 5 || mmap[..4] != magic {
+            return Err(format!("{}: corrupted table", path));
+        }
+
+        let mut table = Table {
+            mmap,
+            table_type,
+            material,
+            pairs: Default::default(),
+            dtz_map: 0,
+        };
+        table
+            .init()
+            .ok_or_else(|| format!("{}: corrupted table", path))?;
+
+        Ok(table)
+    }
+
+    fn sides(&self) -> usize {
+        if self.table_type == TableType::Wdl && !self.material.symmetric {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn pairs(&self, stm: usize, file: usize) -> &PairsData {
+        let stm = if self.table_type == TableType::Wdl {
+            stm
+        } else {
+            0
+        };
+        let file = if self.material.has_pawns { file } else { 0 };
+
+        &self.pairs[stm][file]
+    }
+
+    fn byte(&self, offset: usize) -> Option<u8> {
+        self.mmap.get(offset).copied()
+    }
+
+    fn u16_le(&self, offset: usize) -> Option<u16> {
+        Some(u16::from_le_bytes(
+            self.mmap.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    }
+
+    fn u32_le(&self, offset: usize) -> Option<u32> {
+        Some(u32::from_le_bytes(
+            self.mmap.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    }
+
+    fn u32_be(&self, offset: usize) -> Option<u32> {
+        Some(u32::from_be_bytes(
+            self.mmap.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    }
+
+    fn u64_be(&self, offset: usize) -> Option<u64> {
+        Some(u64::from_be_bytes(
+            self.mmap.get(offset..offset + 8)?.try_into().ok()?,
+        ))
+    }
+
+    // Reads the headers of the file and sets up the pairs data of every
+    // sub-table.
+    fn init(&mut self) -> Option<()> {
+        let material = self.material.clone();
+        let sides = self.sides();
+        let files = if material.has_pawns { 4 } else { 1 };
+        let both_pawns = material.has_pawns && material.pawn_count[1] > 0;
+
+        // The first byte after the magic holds flags which the name tells too.
+        let mut offset = 5;
+
+        for file in 0..files {
+            let first = self.byte(offset)?;
+            let second = if both_pawns {
+                self.byte(offset + 1)?
+            } else {
+                0xff
+            };
+            let order = [
+                [(first & 0xf) as usize, (second & 0xf) as usize],
+                [(first >> 4) as usize, (second >> 4) as usize],
+            ];
+            offset += 1 + both_pawns as usize;
+
+            for index in 0..material.piece_count {
+                let byte = self.byte(offset)?;
+                for side in 0..sides {
+                    self.pairs[side][file].pieces[index] =
+                        if side == 0 { byte & 0xf } else { byte >> 4 };
+                }
+                offset += 1;
+            }
+
+            for (side, order) in order.iter().enumerate().take(sides) {
+                set_groups(&material, &mut self.pairs[side][file], order, file);
+            }
+        }
+
+        offset += offset & 1;
+
+        for file in 0..files {
+            for side in 0..sides {
+                offset = self.set_sizes(side, file, offset)?;
+            }
+        }
+
+        if self.table_type == TableType::Dtz {
+            offset = self.set_dtz_map(offset, files)?;
+        }
+
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut self.pairs[side][file];
+                pairs.sparse_index = offset;
+                offset += pairs.sparse_index_size * 6;
+            }
+        }
+
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut self.pairs[side][file];
+                pairs.block_length = offset;
+                offset += pairs.block_length_size * 2;
+            }
+        }
+
+        for file in 0..files {
+            for side in 0..sides {
+                offset = (offset + 0x3f) & !0x3f;
+                let pairs = &mut self.pairs[side][file];
+                pairs.data = offset;
+                offset += pairs.num_blocks * pairs.block_size;
+            }
+        }
+
+        (offset <= self.mmap.len()).then_some(())
+    }
+
+    fn set_sizes(&mut self, side: usize, file: usize, mut offset: usize) -> Option<usize> {
+        let mut pairs = std::mem::take(&mut self.pairs[side][file]);
+
+        pairs.flags = self.byte(offset)?;
+        offset += 1;
+
+        if pairs.flags & FLAG_SINGLE_VALUE != 0 {
+            pairs.min_sym_len = self.byte(offset)?;
+            self.pairs[side][file] = pairs;
+            return Some(offset + 1);
+        }
+
+        let groups = pairs.group_len.iter().position(|&len| len == 0)?;
+        let table_size = pairs.group_idx[groups] as usize;
+
+        pairs.block_size = 1 << self.byte(offset)?;
+        pairs.span = 1 << self.byte(offset + 1)?;
+        pairs.sparse_index_size = table_size.div_ceil(pairs.span);
+        let padding = self.byte(offset + 2)? as usize;
+        pairs.num_blocks = self.u32_le(offset + 3)? as usize;
+        pairs.block_length_size = pairs.num_blocks + padding;
+        pairs.max_sym_len = self.byte(offset + 7)?;
+        pairs.min_sym_len = self.byte(offset + 8)?;
+        offset += 9;
+
+        if pairs.max_sym_len < pairs.min_sym_len {
+            return None;
+        }
+        pairs.lowest_sym = offset;
+        let lengths = (pairs.max_sym_len - pairs.min_sym_len) as usize + 1;
+
+        // Canonical Huffman codes: longer codes have lower values, so the
+        // lowest code of every length, padded to 64 bits, decreases with the
+        // length and tells the length of a code.
+        pairs.base64 = vec![0; lengths];
+        for index in (0..lengths - 1).rev() {
+            let lowest = self.u16_le(pairs.lowest_sym + 2 * index)? as u64;
+            let next_lowest = self.u16_le(pairs.lowest_sym + 2 * (index + 1))? as u64;
+            pairs.base64[index] = (pairs.base64[index + 1] + lowest - next_lowest) / 2;
+        }
+        for (index, base) in pairs.base64.iter_mut().enumerate() {
+            *base = base
+                .checked_shl(64 - index as u32 - pairs.min_sym_len as u32)
+                .unwrap_or(0);
+        }
+
+        offset += lengths * 2;
+        let symbols = self.u16_le(offset)? as usize;
+        offset += 2;
+        pairs.btree = offset;
+        if offset + 3 * symbols > self.mmap.len() {
+            return None;
+        }
+
+        // Every symbol stands for a pair of symbols, down to the leaves which
+        // hold a value.
+        pairs.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for symbol in 0..symbols {
+            if !visited[symbol] {
+                let length = self.symlen(&mut pairs, symbol, &mut visited)?;
+                pairs.symlen[symbol] = length;
+            }
+        }
+
+        self.pairs[side][file] = pairs;
+        Some(offset + 3 * symbols + (symbols & 1))
+    }
+
+    // Number of values, minus one, that `symbol` expands to.
+    fn symlen(&self, pairs: &mut PairsData, symbol: usize, visited: &mut [bool]) -> Option<u8> {
+        visited[symbol] = true;
+
+        let (left, right) = self.children(pairs.btree, symbol);
+        if right == 0xfff {
+            return Some(0);
+        }
+        if left >= visited.len() || right >= visited.len() {
+            return None;
+        }
+
+        for child in [left, right] {
+            if !visited[child] {
+                pairs.symlen[child] = self.symlen(pairs, child, visited)?;
+            }
+        }
+
+        Some(
+            pairs.symlen[left]
+                .wrapping_add(pairs.symlen[right])
+                .wrapping_add(1),
+        )
+    }
+
+    fn children(&self, btree: usize, symbol: usize) -> (usize, usize) {
+        let lr = &self.mmap[btree + 3 * symbol..btree + 3 * symbol + 3];
+        let left = ((lr[1] as usize & 0xf) << 8) | lr[0] as usize;
+        let right = ((lr[2] as usize) << 4) | (lr[1] as usize >> 4);
+
+        (left, right)
+    }
+
+    fn set_dtz_map(&mut self, mut offset: usize, files: usize) -> Option<usize> {
+        self.dtz_map = offset;
+
+        for file in 0..files {
+            let flags = self.pairs[0][file].flags;
+            if flags & FLAG_MAPPED == 0 {
+                continue;
+            }
+
+            for index in 0..4 {
+                if flags & FLAG_WIDE != 0 {
+                    offset += offset & 1;
+                    self.pairs[0][file].map_idx[index] = (offset - self.dtz_map) / 2 + 1;
+                    offset += 2 * self.u16_le(offset)? as usize + 2;
+                } else {
+                    self.pairs[0][file].map_idx[index] = offset - self.dtz_map + 1;
+                    offset += self.byte(offset)? as usize + 1;
+                }
+            }
+        }
+
+        Some(offset + (offset & 1))
+    }
+
+    /// Looks up `position`, which must have the material of the table. The
+    /// value is a `Wdl` for WDL tables and the distance to zeroing in plies for
+    /// DTZ tables, which need the WDL value of the position.
+    pub fn probe(&self, position: &ProbePosition, wdl: Wdl) -> Option<Lookup> {
+        let maps = maps();
+        let material = &self.material;
+        let flip_color = if position.flip { 8 } else { 0 };
+        let flip_squares = if position.flip { 56 } else { 0 };
+
+        let mut squares = [0; MAX_PIECES];
+        let mut pieces = [0; MAX_PIECES];
+        let mut size = 0;
+        let mut lead_pawns = 0;
+        let mut table_file = 0;
+
+        // Pawns of the leading colour, as numbered on the board.
+        let lead_pawn = material
+            .has_pawns
+            .then(|| self.pairs[0][0].pieces[0] ^ flip_color);
+
+        // With pawns there is a sub-table for every file of the leading pawn,
+        // the one closest to the edge and on the lowest rank.
+        if material.has_pawns {
+            for &(square, piece) in &position.pieces {
+                if Some(piece) == lead_pawn {
+                    squares[size] = square ^ flip_squares;
+                    size += 1;
+                }
+            }
+            lead_pawns = size;
+
+            let lead = (0..lead_pawns).max_by_key(|&index| maps.pawns[squares[index]])?;
+            squares.swap(0, lead);
+
+            table_file = squares[0] % 8;
+            if table_file > 3 {
+                table_file = (squares[0] ^ 7) % 8;
+            }
+        }
+
+        if self.table_type == TableType::Dtz {
+            let flags = self.pairs(0, table_file).flags;
+            // Symmetric tables without pawns are the same for both sides.
+            let both_sides = material.symmetric && !material.has_pawns;
+            if (flags & FLAG_STM) as usize != position.stm && !both_sides {
+                return Some(Lookup::ChangeStm);
+            }
+        }
+
+        for &(square, piece) in &position.pieces {
+            if Some(piece) != lead_pawn {
+                squares[size] = square ^ flip_squares;
+                pieces[size] = piece ^ flip_color;
+                size += 1;
+            }
+        }
+
+        let pairs = self.pairs(position.stm, table_file);
+
+        // Order the pieces as the table does.
+        for index in lead_pawns..size.saturating_sub(1) {
+            if let Some(other) =
+                (index + 1..size).find(|&other| pairs.pieces[index] == pieces[other])
+            {
+                pieces.swap(index, other);
+                squares.swap(index, other);
+            }
+        }
+
+        // Mirror the board so that the leading piece is on files a to d.
+        if squares[0] % 8 > 3 {
+            for square in squares.iter_mut().take(size) {
+                *square ^= 7;
+            }
+        }
+
+        let mut index;
+        if material.has_pawns {
+            index = maps.lead_pawn_idx[lead_pawns][squares[0]];
+
+            squares[1..lead_pawns].sort_by_key(|&square| maps.pawns[square]);
+            for (pawn, &square) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                index += maps.binomial[pawn][maps.pawns[square]];
+            }
+        } else {
+            // Mirror the board so that the leading piece is on ranks 1 to 4,
+            // then along the diagonal so that the first piece of the leading
+            // group which is off the diagonal is below it.
+            if squares[0] / 8 > 3 {
+                for square in squares.iter_mut().take(size) {
+                    *square ^= 56;
+                }
+            }
+
+            for first in 0..pairs.group_len[0] {
+                match off_a1h8(squares[first]) {
+                    0 => continue,
+                    offset if offset > 0 => {
+                        for square in squares.iter_mut().take(size).skip(first) {
+                            *square = ((*square >> 3) | (*square << 3)) & 63;
+                        }
+                    }
+                    _ => {}
+                }
+                break;
+            }
+
+            index = if material.has_unique_pieces {
+                encode_unique_pieces(&squares)
+            } else {
+                maps.kk[maps.a1d1d4[squares[0]]][squares[1]]
+            };
+        }
+
+        index *= pairs.group_idx[0];
+
+        // The remaining groups are encoded with the squares left free by the
+        // previous groups.
+        let mut group_start = pairs.group_len[0];
+        let mut remaining_pawns = material.has_pawns && material.pawn_count[1] > 0;
+        let mut group = 1;
+
+        while pairs.group_len[group] != 0 {
+            let group_end = group_start + pairs.group_len[group];
+            squares[group_start..group_end].sort_unstable();
+
+            let mut combination = 0;
+            for (offset, &square) in squares[group_start..group_end].iter().enumerate() {
+                let adjust = squares[..group_start]
+                    .iter()
+                    .filter(|&&other| square > other)
+                    .count();
+                let available = square - adjust - if remaining_pawns { 8 } else { 0 };
+                combination += maps.binomial[offset + 1][available];
+            }
+
+            remaining_pawns = false;
+            index += combination * pairs.group_idx[group];
+            group_start = group_end;
+            group += 1;
+        }
+
+        let value = self.decompress(pairs, index)?;
+
+        Some(Lookup::Value(match self.table_type {
+            TableType::Wdl => value - 2,
+            TableType::Dtz => self.map_dtz(table_file, value, wdl)?,
+        }))
+    }
+
+    // Finds the value at `index` of the compressed sub-table.
+    fn decompress(&self, pairs: &PairsData, index: u64) -> Option<i32> {
+        if pairs.flags & FLAG_SINGLE_VALUE != 0 {
+            return Some(pairs.min_sym_len as i32);
+        }
+
+        // The sparse index points to the block and offset of every `span`-th
+        // value, from there the blocks are walked to the one with `index`.
+        let span = pairs.span as u64;
+        let sparse = pairs.sparse_index + 6 * (index / span) as usize;
+        let mut block = self.u32_le(sparse)? as usize;
+        let mut offset = self.u16_le(sparse + 4)? as i64;
+        offset += (index % span) as i64 - (span / 2) as i64;
+
+        let block_length = |block: usize| -> Option<i64> {
+            Some(self.u16_le(pairs.block_length + 2 * block)? as i64)
+        };
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        // Read the canonical Huffman codes of the block until the one which
+        // holds the value.
+        let mut pointer = pairs.data + block * pairs.block_size;
+        let mut buffer = self.u64_be(pointer)?;
+        let mut buffer_size = 64;
+        pointer += 8;
+
+        let min_sym_len = pairs.min_sym_len as u32;
+        let mut symbol;
+        loop {
+            let mut length = 0;
+            while buffer < *pairs.base64.get(length)? {
+                length += 1;
+            }
+
+            symbol =
+                ((buffer - pairs.base64[length]) >> (64 - length as u32 - min_sym_len)) as usize;
+            symbol += self.u16_le(pairs.lowest_sym + 2 * length)? as usize;
+            let symlen = *pairs.symlen.get(symbol)? as i64;
+
+            if offset < symlen + 1 {
+                break;
+            }
+
+            offset -= symlen + 1;
+            let length = length as u32 + min_sym_len;
+            buffer = buffer.checked_shl(length).unwrap_or(0);
+            buffer_size -= length as i32;
+
+            if buffer_size <= 32 {
+                buffer_size += 32;
+                buffer |= (self.u32_be(pointer)? as u64) << (64 - buffer_size);
+                pointer += 4;
+            }
+        }
+
+        // Expand the symbol into its pairs until the leaf with the value.
+        while pairs.symlen[symbol] != 0 {
+            let (left, right) = self.children(pairs.btree, symbol);
+            let left_length = *pairs.symlen.get(left)? as i64;
+
+            if offset < left_length + 1 {
+                symbol = left;
+            } else {
+                offset -= left_length + 1;
+                symbol = right;
+            }
+        }
+
+        Some(self.children(pairs.btree, symbol).0 as i32)
+    }
+
+    // Converts a stored DTZ value to plies. Values are stored as ranks by
+    // frequency for each WDL value, and as moves unless the flags say plies.
+    fn map_dtz(&self, file: usize, value: i32, wdl: Wdl) -> Option<i32> {
+        let pairs = self.pairs(0, file);
+        let map_index = match wdl {
+            Wdl::Loss => 1,
+            Wdl::BlessedLoss => 3,
+            Wdl::Draw => 0,
+            Wdl::CursedWin => 2,
+            Wdl::Win => 0,
+        };
+
+        let mut value = value;
+        if pairs.flags & FLAG_MAPPED != 0 {
+            let index = pairs.map_idx[map_index] + value as usize;
+            value = if pairs.flags & FLAG_WIDE != 0 {
+                self.u16_le(self.dtz_map + 2 * index)? as i32
+            } else {
+                self.byte(self.dtz_map + index)? as i32
+            };
+        }
+
+        let in_moves = match wdl {
+            Wdl::Win => pairs.flags & FLAG_WIN_PLIES == 0,
+            Wdl::Loss => pairs.flags & FLAG_LOSS_PLIES == 0,
+            Wdl::CursedWin | Wdl::BlessedLoss => true,
+            Wdl::Draw => false,
+        };
+        if in_moves {
+            value *= 2;
+        }
+
+        Some(value + 1)
+    }
+}
+
+// Encodes the first three pieces together when the table has a piece other
+// than the kings which is alone of its kind.
+fn encode_unique_pieces(squares: &[usize]) -> u64 {
+    let maps = maps();
+    let adjust1 = (squares[1] > squares[0]) as usize;
+    let adjust2 = (squares[2] > squares[0]) as usize + (squares[2] > squares[1]) as usize;
+    let rank = |square: usize| (square / 8) as u64;
+
+    if off_a1h8(squares[0]) != 0 {
+        ((maps.a1d1d4[squares[0]] * 63 + (squares[1] - adjust1)) * 62 + squares[2] - adjust2) as u64
+    } else if off_a1h8(squares[1]) != 0 {
+        (6 * 63 + rank(squares[0]) * 28 + maps.b1h1h7[squares[1]] as u64) * 62
+            + (squares[2] - adjust2) as u64
+    } else if off_a1h8(squares[2]) != 0 {
+        6 * 63 * 62
+            + 4 * 28 * 62
+            + rank(squares[0]) * 7 * 28
+            + (rank(squares[1]) - adjust1 as u64) * 28
+            + maps.b1h1h7[squares[2]] as u64
+    } else {
+        6 * 63 * 62
+            + 4 * 28 * 62
+            + 4 * 7 * 28
+            + rank(squares[0]) * 7 * 6
+            + (rank(squares[1]) - adjust1 as u64) * 6
+            + (rank(squares[2]) - adjust2 as u64)
+    }
+}
+
+// Splits the pieces into the groups which are encoded together and computes
+// the factor of every group in the index, in the order the table gives.
+fn set_groups(material: &Material, pairs: &mut PairsData, order: &[usize; 2], file: usize) {
+    let maps = maps();
+    let mut first_len: i32 = if material.has_pawns {
+        0
+    } else if material.has_unique_pieces {
+        3
+    } else {
+        2
+    };
+
+    let mut groups = 0;
+    pairs.group_len[0] = 1;
+    for index in 1..material.piece_count {
+        first_len -= 1;
+        if first_len > 0 || pairs.pieces[index] == pairs.pieces[index - 1] {
+            pairs.group_len[groups] += 1;
+        } else {
+            groups += 1;
+            pairs.group_len[groups] = 1;
+        }
+    }
+    groups += 1;
+    pairs.group_len[groups] = 0;
+
+    let both_pawns = material.has_pawns && material.pawn_count[1] > 0;
+    let mut next = if both_pawns { 2 } else { 1 };
+    let mut free_squares =
+        64 - pairs.group_len[0] - if both_pawns { pairs.group_len[1] } else { 0 };
+    let mut index: u64 = 1;
+
+    let mut k = 0;
+    while next < groups || k == order[0] || k == order[1] {
+        if k == order[0] {
+            pairs.group_idx[0] = index;
+            index *= if material.has_pawns {
+                maps.lead_pawns_size[pairs.group_len[0]][file]
+            } else if material.has_unique_pieces {
+                31332
+            } else {
+                462
+            };
+        } else if k == order[1] {
+            pairs.group_idx[1] = index;
+            index *= maps.binomial[pairs.group_len[1]][48 - pairs.group_len[0]];
+        } else {
+            pairs.group_idx[next] = index;
+            index *= maps.binomial[pairs.group_len[next]][free_squares];
+            free_squares -= pairs.group_len[next];
+            next += 1;
+        }
+        k += 1;
+    }
+
+    pairs.group_idx[groups] = index;
+}
+
+/// A table file which is opened on its first probe.
+pub struct LazyTable {
+    path: String,
+    table_type: TableType,
+    material: Material,
+    table: OnceLock<Option<Table>>,
+}
+
+impl LazyTable {
+    pub fn new(path: String, table_type: TableType, material: Material) -> Self {
+        LazyTable {
+            path,
+            table_type,
+            material,
+            table: OnceLock::new(),
+        }
+    }
+
+    pub fn get(&self) -> Option<&Table> {
+        self.table
+            .get_or_init(|| Table::open(&self.path, self.table_type, self.material.clone()).ok())
+            .as_ref()
+    }
+}
@@ -1,14 +1,45 @@
-use chrono::Utc;
 use std::io;
 use std::process;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use crate::core::{movegen, Board};
+use crate::book::{Book, BookBuilder};
+use crate::core::{movegen, pgn, Board, Color, Move};
+use crate::evaluation::nnue::Network;
+use crate::evaluation::{self, PawnTable, Term};
 use crate::perft;
+use crate::search::tt::DEFAULT_HASH_SIZE;
+use crate::search::{self, SearchLimits, SearchOptions, TimeManager, TranspositionTable};
+use crate::syzygy::Tablebase;
+use crate::tournament::game::{DrawAdjudication, ResignAdjudication};
+use crate::tournament::{self, EngineSpec, MatchSettings, Sprt, SprtResult, TimeControl};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
 const REPOSITORY: &str = env!("CARGO_PKG_REPOSITORY");
 
+const MAX_HASH_SIZE: usize = 65536;
+
+struct State {
+    board: Board,
+    options: SearchOptions,
+    tt: Arc<TranspositionTable>,
+    network: Option<Arc<Network>>,
+    book: Option<Book>,
+    own_book: bool,
+    search: Option<SearchThread>,
+}
+
+struct SearchThread {
+    handle: JoinHandle<()>,
+    stop: Arc<AtomicBool>,
+    time_manager: Arc<TimeManager>,
+}
+
 pub fn init() {
     println!("\n{}\n", "=".repeat(60));
     println!("Rust Chess Engine  v{} ,  by {}", VERSION, AUTHORS);
@@ -17,8 +48,21 @@ pub fn init() {
 }
 
 pub fn run() {
-    loop {
-        let input_string = read_line();
+    let mut state = State {
+        board: Board::new(),
+        options: SearchOptions::default(),
+        tt: Arc::new(TranspositionTable::new(DEFAULT_HASH_SIZE)),
+        network: None,
+        book: None,
+        own_book: false,
+        search: None,
+    };
+
+    // Input is read on its own thread so that `stop` and `ponderhit` arrive
+    // while a search is running.
+    let input_receiver = spawn_input_thread();
+
+    for input_string in input_receiver {
         let input: Vec<&str> = input_string.split(' ').collect();
         let command = input[0].trim().to_lowercase();
 
@@ -27,7 +71,19 @@ pub fn run() {
             "magic" => handle_magic(),
             "perft" => handle_perft(input),
             "perftd" => handle_divided_perft(input),
-            "quit" => handle_quit(),
+            "uci" => handle_uci(),
+            "isready" => handle_isready(),
+            "setoption" => handle_setoption(input, &mut state),
+            "ucinewgame" => handle_ucinewgame(&mut state),
+            "position" => handle_position(input, &mut state),
+            "go" => handle_go(input, &mut state),
+            "stop" => handle_stop(&mut state),
+            "ponderhit" => handle_ponderhit(&state),
+            "eval" => handle_eval(&state),
+            "nnue" => handle_nnue(&state),
+            "match" => handle_match(input),
+            "makebook" => handle_makebook(input),
+            "quit" => handle_quit(&mut state),
             _ => handle_unknown_command(command),
         }
     }
@@ -45,18 +101,52 @@ fn handle_help() {
     println!("    perftd [depth]                -  Run divided perft test with [depth].\n");
     println!("    perftd [depth] fen [fen]      -  Run divided perft test at position [fen] with [depth].\n");
     println!("    perftd [depth] moves [moves]  -  Run divided perft test at position after moves [moves] with [depth].\n");
+    println!("    uci                           -  Print engine information for UCI clients.\n");
+    println!("    isready                       -  Check if the engine is ready.\n");
+    println!("    setoption name [id] value [x] -  Set engine option [id] to [x], options are Hash, Threads, MultiPV, EvalFile, OwnBook, BookFile and SyzygyPath.\n");
+    println!("    ucinewgame                    -  Reset the current position and clear the hash table.\n");
+    println!("    position startpos [moves]     -  Set the current position to start position, then play [moves].\n");
+    println!("    position fen [fen] [moves]    -  Set the current position to [fen], then play [moves].\n");
+    println!("    go [limits]                   -  Search the current position, [limits] are UCI go parameters.\n");
+    println!("    stop                          -  Stop the running search.\n");
+    println!(
+        "    ponderhit                     -  Continue the running ponder search on the clock.\n"
+    );
+    println!("    eval                          -  Print the evaluation terms of the current position.\n");
+    println!("    nnue                          -  Print the network evaluation of the current position.\n");
+    println!("    match [settings]              -  Play a match between two engines, see below.\n");
+    println!("    makebook [pgn] [book] [depth] [min]  -  Build a Polyglot book from the first [depth] plies of the games");
+    println!("                                     in [pgn], keeping moves played at least [min] times.\n");
     println!("    quit                          -  Quit this program.\n");
+    println!("Match settings:\n");
+    println!("    engine1 [engine]              -  First engine: internal or the path of a UCI engine, followed by");
+    println!("                                     options such as internal,Hash=64,name=new (default: internal).\n");
+    println!("    engine2 [engine]              -  Second engine (default: internal).\n");
+    println!("    games [n]                     -  Number of games (default: 2).\n");
+    println!("    concurrency [n]               -  Number of games played at the same time (default: 1).\n");
+    println!("    tc [tc]                       -  Time control: seconds+increment, movetime=[ms], nodes=[n] or depth=[n].\n");
+    println!("    book [file]                   -  EPD or PGN file of openings, each played with both colours.\n");
+    println!("    pgn [file]                    -  Write all games to [file].\n");
+    println!(
+        "    sprt [elo0] [elo1]            -  Stop once the SPRT of elo0 against elo1 finishes.\n"
+    );
+    println!("    alpha [a] beta [b]            -  Error rates of the SPRT (default: 0.05).\n");
+    println!("    draw [number] [count] [score] -  Adjudicate draws from move [number] when both engines report");
+    println!("                                     scores within [score] cp for [count] moves.\n");
+    println!("    resign [count] [score]        -  Adjudicate a loss for an engine reporting [score] cp or worse");
+    println!("                                     for [count] moves.\n");
+    println!("    maxmoves [n]                  -  Adjudicate a draw after [n] moves.\n");
 }
 
 fn handle_magic() {
-    let start = Utc::now();
+    let start = Instant::now();
 
     println!("\nGenerating magic numbers for rook ...\n");
     movegen::magic::find_rook_magics();
     println!("\nGenerating magic numbers for bishop ...\n");
     movegen::magic::find_bishop_magics();
 
-    let interval = (Utc::now() - start).num_milliseconds();
+    let interval = start.elapsed().as_millis();
     println!("\nMagic numbers generated in {} ms", interval);
 }
 
@@ -84,7 +174,7 @@ fn handle_perft(input: Vec<&str>) {
 
     println!("Starting perft test of max depth {} ...", max_depth);
     for depth in 1..=max_depth {
-        let start = Utc::now();
+        let start = Instant::now();
 
         let count = match perft::run(depth, &mut board) {
             Ok(result) => result,
@@ -94,12 +184,15 @@ fn handle_perft(input: Vec<&str>) {
             }
         };
 
-        let interval = (Utc::now() - start).num_milliseconds() as f64;
-        let mnps = count as f64 / interval / 1000.0;
+        let interval = start.elapsed();
+        let mnps = count as f64 / interval.as_secs_f64() / 1_000_000.0;
 
         println!(
             "depth {} | {} nodes | {} ms | {} Mnps",
-            depth, count, interval, mnps
+            depth,
+            count,
+            interval.as_millis(),
+            mnps
         );
     }
 
@@ -146,7 +239,392 @@ fn handle_divided_perft(input: Vec<&str>) {
     println!("\nDivided perft test was successful.");
 }
 
-fn handle_quit() {
+fn handle_uci() {
+    println!("id name Rust Chess Engine {}", VERSION);
+    println!("id author {}", AUTHORS);
+    println!(
+        "option name Hash type spin default {} min 1 max {}",
+        DEFAULT_HASH_SIZE, MAX_HASH_SIZE
+    );
+    println!(
+        "option name Threads type spin default 1 min 1 max {}",
+        search::MAX_THREADS
+    );
+    println!(
+        "option name MultiPV type spin default 1 min 1 max {}",
+        search::MAX_MULTI_PV
+    );
+    println!("option name Ponder type check default false");
+    println!("option name EvalFile type string default <empty>");
+    println!("option name OwnBook type check default false");
+    println!("option name BookFile type string default <empty>");
+    println!("option name SyzygyPath type string default <empty>");
+    println!("uciok");
+}
+
+fn handle_isready() {
+    println!("readyok");
+}
+
+fn handle_setoption(input: Vec<&str>, state: &mut State) {
+    stop_search(state);
+
+    let (name, value) = match parse_option(&input[1..]) {
+        Ok(result) => result,
+        Err(message) => {
+            println!("{}", message);
+            return;
+        }
+    };
+
+    match name.to_lowercase().as_str() {
+        "hash" => match parse_value::<usize>(Some(value.as_str())) {
+            Ok(size) => state.tt = Arc::new(TranspositionTable::new(size.clamp(1, MAX_HASH_SIZE))),
+            Err(message) => println!("{}", message),
+        },
+        "threads" => match parse_value::<usize>(Some(value.as_str())) {
+            Ok(threads) => state.options.threads = threads.clamp(1, search::MAX_THREADS),
+            Err(message) => println!("{}", message),
+        },
+        "multipv" => match parse_value::<usize>(Some(value.as_str())) {
+            Ok(multi_pv) => state.options.multi_pv = multi_pv.clamp(1, search::MAX_MULTI_PV),
+            Err(message) => println!("{}", message),
+        },
+        "ponder" => {}
+        "evalfile" => {
+            state.network = match value.as_str() {
+                "" | "<empty>" => None,
+                path => match Network::load(path) {
+                    Ok(network) => {
+                        println!(
+                            "info string Loaded network {} with {} hidden neurons",
+                            path,
+                            network.hidden_size()
+                        );
+                        Some(Arc::new(network))
+                    }
+                    Err(message) => {
+                        println!("{}", message);
+                        return;
+                    }
+                },
+            };
+            state.board.set_network(state.network.clone());
+        }
+        "ownbook" => state.own_book = value.eq_ignore_ascii_case("true"),
+        "bookfile" => {
+            state.book = match value.as_str() {
+                "" | "<empty>" => None,
+                path => match Book::load(path) {
+                    Ok(book) => {
+                        println!(
+                            "info string Loaded book {} with {} entries",
+                            path,
+                            book.len()
+                        );
+                        Some(book)
+                    }
+                    Err(message) => {
+                        println!("{}", message);
+                        return;
+                    }
+                },
+            };
+        }
+        "syzygypath" => {
+            state.options.tablebase = match value.as_str() {
+                "" | "<empty>" => None,
+                path => match Tablebase::new(path) {
+                    Ok(tablebase) => {
+                        println!(
+                            "info string Found {} tablebases with up to {} pieces",
+                            tablebase.len(),
+                            tablebase.max_pieces()
+                        );
+                        Some(Arc::new(tablebase))
+                    }
+                    Err(message) => {
+                        println!("{}", message);
+                        return;
+                    }
+                },
+            };
+        }
+        _ => println!("Unknown option: {}", name),
+    }
+}
+
+fn handle_ucinewgame(state: &mut State) {
+    stop_search(state);
+    state.board = Board::new();
+    state.board.set_network(state.network.clone());
+    state.tt.clear();
+}
+
+fn handle_position(input: Vec<&str>, state: &mut State) {
+    stop_search(state);
+
+    match parse_position(&input[1..]) {
+        Ok(result) => {
+            state.board = result;
+            state.board.set_network(state.network.clone());
+        }
+        Err(message) => println!("{}", message),
+    }
+}
+
+fn handle_go(input: Vec<&str>, state: &mut State) {
+    stop_search(state);
+
+    let limits = match parse_search_limits(&input[1..]) {
+        Ok(result) => result,
+        Err(message) => {
+            println!("{}", message);
+            return;
+        }
+    };
+
+    // Ponder and infinite searches must wait for the client before answering,
+    // so only searches on the clock are played from the book.
+    if state.own_book && !limits.infinite && !limits.ponder && limits.mate.is_none() {
+        let book_move = state
+            .book
+            .as_ref()
+            .and_then(|book| book.pick(&state.board, &fastrand::Rng::new()));
+
+        if let Some(book_move) = book_move {
+            println!("bestmove {}", book_move.as_string());
+            return;
+        }
+    }
+
+    let time_manager = Arc::new(TimeManager::new(&limits, state.board.color_to_move));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let board = state.board.clone();
+    let options = state.options.clone();
+    let tt = Arc::clone(&state.tt);
+    let search_time_manager = Arc::clone(&time_manager);
+    let search_stop = Arc::clone(&stop);
+
+    let handle = thread::spawn(move || {
+        let result = search::run(
+            &board,
+            &limits,
+            &options,
+            &search_time_manager,
+            &tt,
+            &search_stop,
+        );
+
+        // A ponder or infinite search may not report its move before the
+        // client sends `stop` or `ponderhit`, even if the search is complete.
+        while (limits.infinite || search_time_manager.is_pondering())
+            && !search_stop.load(Ordering::Relaxed)
+        {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        match (result.best_move, result.ponder_move) {
+            (Some(best_move), Some(ponder_move)) => println!(
+                "bestmove {} ponder {}",
+                best_move.as_string(),
+                ponder_move.as_string()
+            ),
+            (Some(best_move), None) => println!("bestmove {}", best_move.as_string()),
+            (None, _) => println!("bestmove 0000"),
+        }
+    });
+
+    state.search = Some(SearchThread {
+        handle,
+        stop,
+        time_manager,
+    });
+}
+
+fn handle_stop(state: &mut State) {
+    stop_search(state);
+}
+
+fn handle_ponderhit(state: &State) {
+    if let Some(search) = &state.search {
+        search.time_manager.ponderhit();
+    }
+}
+
+fn handle_eval(state: &State) {
+    let breakdown = evaluation::breakdown(&state.board, &mut PawnTable::new(1));
+    let separator = format!(
+        "{}+{}+{}+{}",
+        "-".repeat(15),
+        "-".repeat(9),
+        "-".repeat(9),
+        "-".repeat(9)
+    );
+
+    println!(
+        "{:<14} | {:>7} | {:>7} | {:>7}",
+        "Term", "White", "Black", "Total"
+    );
+    println!("{}", separator);
+    for term in Term::ALL {
+        let white = breakdown.get(term, Color::White);
+        let black = breakdown.get(term, Color::Black);
+        println!(
+            "{:<14} | {:>7} | {:>7} | {:>7}",
+            term.as_string(),
+            white,
+            black,
+            white - black
+        );
+    }
+    println!("{}", separator);
+    println!(
+        "{:<14} | {:>7} | {:>7} | {:>7}",
+        "Total",
+        "",
+        "",
+        breakdown.total()
+    );
+    println!("\nEvaluation: {} cp (white side)", breakdown.total());
+}
+
+fn handle_nnue(state: &State) {
+    match &state.board.nnue {
+        Some(nnue) => println!(
+            "NNUE evaluation: {} cp (white side)",
+            nnue.evaluate(Color::White)
+        ),
+        None => println!("No network loaded, set one with setoption name EvalFile value [path]"),
+    }
+}
+
+fn handle_match(input: Vec<&str>) {
+    let settings = match parse_match_settings(&input[1..]) {
+        Ok(result) => result,
+        Err(message) => {
+            println!("{}", message);
+            return;
+        }
+    };
+    let engines = tournament::unique_names(&settings.engines);
+    let names = [&engines[0].name, &engines[1].name];
+
+    let result = tournament::run(&settings, |game| {
+        let record = game.record;
+        let (white, black) = if game.first_is_white {
+            (names[0], names[1])
+        } else {
+            (names[1], names[0])
+        };
+        let score = game.score;
+        let (elo, margin) = score.elo();
+
+        println!(
+            "Finished game {} ({} vs {}): {} {{{}}}",
+            game.number,
+            white,
+            black,
+            record.result.as_string(),
+            record.reason
+        );
+        println!(
+            "Score of {} vs {}: {} - {} - {}  [{:.3}] {}",
+            names[0],
+            names[1],
+            score.wins,
+            score.losses,
+            score.draws,
+            score.ratio(),
+            score.games()
+        );
+        println!("Elo difference: {:.1} +/- {:.1}", elo, margin);
+
+        if let (Some(sprt), Some(status)) = (settings.sprt, game.sprt) {
+            let (lower, upper) = sprt.bounds();
+            let status = match status {
+                SprtResult::Continue => "",
+                SprtResult::AcceptH0 => " - H0 was accepted",
+                SprtResult::AcceptH1 => " - H1 was accepted",
+            };
+
+            println!(
+                "SPRT: llr {:.3} ({:.3}, {:.3}){}",
+                score.llr(sprt.elo0, sprt.elo1),
+                lower,
+                upper,
+                status
+            );
+        }
+    });
+
+    match result {
+        Ok(_) => println!("Finished match"),
+        Err(message) => println!("{}", message),
+    }
+}
+
+fn handle_makebook(input: Vec<&str>) {
+    let param: Vec<&str> = input[1..]
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if param.len() < 2 {
+        println!("PGN and book paths not found!");
+        return;
+    }
+
+    let settings = param
+        .get(2)
+        .map_or(Ok(20), |depth| parse_setting("depth", depth))
+        .and_then(|depth| {
+            let min_frequency = param
+                .get(3)
+                .map_or(Ok(1), |min| parse_setting("min", min))?;
+            Ok((depth, min_frequency))
+        });
+    let (depth, min_frequency) = match settings {
+        Ok(result) => result,
+        Err(message) => {
+            println!("{}", message);
+            return;
+        }
+    };
+
+    let start = Instant::now();
+    let games = match std::fs::read_to_string(param[0])
+        .map_err(|e| format!("{}: {}", param[0], e))
+        .and_then(|content| pgn::parse(&content))
+    {
+        Ok(result) => result,
+        Err(message) => {
+            println!("{}", message);
+            return;
+        }
+    };
+
+    let mut builder = BookBuilder::new(depth, min_frequency);
+    for game in &games {
+        builder.add_game(game);
+    }
+    let book = builder.build();
+
+    match std::fs::write(param[1], book.to_bytes()) {
+        Ok(()) => println!(
+            "Wrote {} entries from {} games to {} in {} ms",
+            book.len(),
+            games.len(),
+            param[1],
+            start.elapsed().as_millis()
+        ),
+        Err(message) => println!("{}: {}", param[1], message),
+    }
+}
+
+fn handle_quit(state: &mut State) {
+    stop_search(state);
     process::exit(0);
 }
 
@@ -154,6 +632,36 @@ fn handle_unknown_command(command: String) {
     println!("Unknown command: {}", command);
 }
 
+fn stop_search(state: &mut State) {
+    if let Some(search) = state.search.take() {
+        search.stop.store(true, Ordering::Relaxed);
+        search.handle.join().expect("Search thread panicked");
+    }
+}
+
+fn spawn_input_thread() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || loop {
+        let input_string = read_line();
+        let is_eof = input_string.is_empty();
+
+        if sender
+            .send(if is_eof {
+                "quit".to_string()
+            } else {
+                input_string
+            })
+            .is_err()
+            || is_eof
+        {
+            break;
+        }
+    });
+
+    receiver
+}
+
 fn read_line() -> String {
     let mut buf = String::new();
     io::stdin().read_line(&mut buf).unwrap();
@@ -187,3 +695,146 @@ fn parse_board(param: &[&str]) -> Result<Board, &'static str> {
         }
     }
 }
+
+fn parse_position(param: &[&str]) -> Result<Board, &'static str> {
+    let param: Vec<&str> = param
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect();
+    let moves_index = param.iter().position(|p| *p == "moves");
+    let (setup, moves) = match moves_index {
+        Some(index) => (&param[..index], &param[index + 1..]),
+        None => (&param[..], &param[param.len()..]),
+    };
+
+    let mut board = match setup.first() {
+        Some(&"startpos") => Board::new(),
+        Some(&"fen") => Board::from(setup[1..].join(" ").as_str())?,
+        _ => return Err("Invalid method for position setup"),
+    };
+
+    for move_str in moves {
+        let m = Move::from_string(move_str, &board)?;
+        board.make_move(&m);
+    }
+
+    Ok(board)
+}
+
+fn parse_option(param: &[&str]) -> Result<(String, String), &'static str> {
+    let param: Vec<&str> = param
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect();
+    let value_index = param.iter().position(|p| *p == "value");
+
+    if param.first() != Some(&"name") {
+        return Err("Option name not found!");
+    }
+
+    match value_index {
+        Some(index) => Ok((param[1..index].join(" "), param[index + 1..].join(" "))),
+        None => Ok((param[1..].join(" "), String::new())),
+    }
+}
+
+fn parse_search_limits(param: &[&str]) -> Result<SearchLimits, &'static str> {
+    let mut limits = SearchLimits::default();
+    let mut param = param.iter().map(|p| p.trim()).filter(|p| !p.is_empty());
+
+    while let Some(name) = param.next() {
+        match name {
+            "wtime" => limits.white_time = Some(parse_time(param.next())?),
+            "btime" => limits.black_time = Some(parse_time(param.next())?),
+            "winc" => limits.white_increment = Some(parse_time(param.next())?),
+            "binc" => limits.black_increment = Some(parse_time(param.next())?),
+            "movestogo" => limits.moves_to_go = Some(parse_value(param.next())?),
+            "movetime" => limits.move_time = Some(parse_time(param.next())?),
+            "depth" => limits.depth = Some(parse_value(param.next())?),
+            "nodes" => limits.nodes = Some(parse_value(param.next())?),
+            "mate" => limits.mate = Some(parse_value(param.next())?),
+            "infinite" => limits.infinite = true,
+            "ponder" => limits.ponder = true,
+            _ => return Err("Invalid search parameter"),
+        }
+    }
+
+    Ok(limits)
+}
+
+fn parse_match_settings(param: &[&str]) -> Result<MatchSettings, String> {
+    let internal = EngineSpec::parse("internal")?;
+    let mut settings = MatchSettings::new(
+        [internal.clone(), internal],
+        TimeControl::Clock {
+            time: 10000,
+            increment: 100,
+        },
+    );
+    let mut sprt: Option<(f64, f64)> = None;
+    let (mut alpha, mut beta) = (0.05, 0.05);
+    let mut param = param.iter().map(|p| p.trim()).filter(|p| !p.is_empty());
+
+    while let Some(name) = param.next() {
+        let mut next = || param.next().ok_or(format!("Missing value for {}", name));
+
+        match name {
+            "engine1" => settings.engines[0] = EngineSpec::parse(next()?)?,
+            "engine2" => settings.engines[1] = EngineSpec::parse(next()?)?,
+            "games" => settings.games = parse_setting(name, next()?)?,
+            "concurrency" => settings.concurrency = parse_setting(name, next()?)?,
+            "tc" => settings.time_control = TimeControl::parse(next()?)?,
+            "book" => settings.openings = tournament::load_openings(next()?)?,
+            "pgn" => settings.pgn_path = Some(next()?.to_string()),
+            "sprt" => sprt = Some((parse_setting(name, next()?)?, parse_setting(name, next()?)?)),
+            "alpha" => alpha = parse_setting(name, next()?)?,
+            "beta" => beta = parse_setting(name, next()?)?,
+            "draw" => {
+                settings.adjudication.draw = Some(DrawAdjudication {
+                    move_number: parse_setting(name, next()?)?,
+                    move_count: parse_setting(name, next()?)?,
+                    score: parse_setting(name, next()?)?,
+                })
+            }
+            "resign" => {
+                settings.adjudication.resign = Some(ResignAdjudication {
+                    move_count: parse_setting(name, next()?)?,
+                    score: parse_setting(name, next()?)?,
+                })
+            }
+            "maxmoves" => settings.adjudication.max_moves = Some(parse_setting(name, next()?)?),
+            _ => return Err(format!("Invalid match setting: {}", name)),
+        }
+    }
+
+    settings.sprt = sprt.map(|(elo0, elo1)| Sprt {
+        elo0,
+        elo1,
+        alpha,
+        beta,
+    });
+
+    Ok(settings)
+}
+
+fn parse_setting<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", name, value))
+}
+
+fn parse_value<T: FromStr>(param: Option<&str>) -> Result<T, &'static str> {
+    match param.map(|p| p.parse()) {
+        Some(Ok(value)) => Ok(value),
+        _ => Err("Invalid search parameter value"),
+    }
+}
+
+fn parse_time(param: Option<&str>) -> Result<u64, &'static str> {
+    // Some clients send negative clock values when the time is nearly used up
+    let time: i64 = parse_value(param)?;
+
+    Ok(time.max(0) as u64)
+}
@@ -0,0 +1,396 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::book::Book;
+use crate::core::{Board, Move};
+use crate::evaluation::nnue::Network;
+use crate::search::tt::DEFAULT_HASH_SIZE;
+use crate::search::{
+    self, SearchLimits, SearchOptions, TimeManager, TranspositionTable, MATE_SCORE,
+};
+use crate::syzygy::Tablebase;
+
+// How long a UCI engine may take to start up or to answer `isready`.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+// How long a UCI engine may take beyond its time limit to answer `go`, or in
+// total when the search has no time limit.
+const MOVE_TIMEOUT_MARGIN: Duration = Duration::from_secs(5);
+const UNLIMITED_MOVE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The move an engine chose and the score it reported for it, relative to
+/// the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EngineMove {
+    pub best_move: Move,
+    pub score: Option<i32>,
+}
+
+/// A player in a match, either this engine or an external engine over UCI.
+pub trait Engine: Send {
+    fn name(&self) -> &str;
+
+    fn new_game(&mut self) -> Result<(), String>;
+
+    /// Searches the position reached by playing `moves` from `start`, which is
+    /// `board`.
+    fn go(
+        &mut self,
+        start: &Board,
+        moves: &[Move],
+        board: &Board,
+        limits: &SearchLimits,
+    ) -> Result<EngineMove, String>;
+}
+
+/// Describes how to start an engine: `internal` for this engine or the path
+/// of a UCI engine, followed by comma separated `name=value` options. The
+/// `name` option sets the name used in the PGN, all others are UCI options,
+/// for example `internal,Hash=64,name=base` or `./engine,Threads=2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineSpec {
+    pub name: String,
+    pub command: Option<String>,
+    pub options: Vec<(String, String)>,
+}
+
+impl EngineSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut fields = spec.split(',');
+        let command = match fields.next() {
+            Some("internal") => None,
+            Some(command) if !command.is_empty() => Some(command.to_string()),
+            _ => return Err(format!("Invalid engine: {}", spec)),
+        };
+
+        let mut name = match &command {
+            Some(command) => command
+                .rsplit(['/', '\\'])
+                .next()
+                .unwrap_or(command)
+                .to_string(),
+            None => "internal".to_string(),
+        };
+        let mut options = Vec::new();
+
+        for field in fields {
+            let (option, value) = field
+                .split_once('=')
+                .ok_or(format!("Invalid engine option: {}", field))?;
+
+            if option == "name" {
+                name = value.to_string();
+            } else {
+                options.push((option.to_string(), value.to_string()));
+            }
+        }
+
+        Ok(EngineSpec {
+            name,
+            command,
+            options,
+        })
+    }
+
+    pub fn start(&self) -> Result<Box<dyn Engine>, String> {
+        match &self.command {
+            Some(command) => Ok(Box::new(UciEngine::start(
+                &self.name,
+                command,
+                &self.options,
+            )?)),
+            None => Ok(Box::new(InternalEngine::new(&self.name, &self.options)?)),
+        }
+    }
+}
+
+/// This engine, searching on the calling thread with its own hash table.
+pub struct InternalEngine {
+    name: String,
+    options: SearchOptions,
+    tt: TranspositionTable,
+    network: Option<Arc<Network>>,
+    book: Option<Book>,
+    rng: fastrand::Rng,
+}
+
+impl InternalEngine {
+    /// Creates the engine with the UCI options `Hash`, `Threads`, `EvalFile`,
+    /// `BookFile` and `SyzygyPath`. A book file is played from as if `OwnBook`
+    /// was set.
+    pub fn new(name: &str, options: &[(String, String)]) -> Result<Self, String> {
+        let mut engine = InternalEngine {
+            name: name.to_string(),
+            options: SearchOptions {
+                quiet: true,
+                ..Default::default()
+            },
+            tt: TranspositionTable::new(DEFAULT_HASH_SIZE),
+            network: None,
+            book: None,
+            rng: fastrand::Rng::new(),
+        };
+
+        for (option, value) in options {
+            let invalid = || format!("Invalid value for {}: {}", option, value);
+
+            match option.to_lowercase().as_str() {
+                "hash" => {
+                    engine.tt = TranspositionTable::new(value.parse().map_err(|_| invalid())?)
+                }
+                "threads" => {
+                    let threads: usize = value.parse().map_err(|_| invalid())?;
+                    engine.options.threads = threads.clamp(1, search::MAX_THREADS);
+                }
+                "evalfile" => engine.network = Some(Arc::new(Network::load(value)?)),
+                "bookfile" => engine.book = Some(Book::load(value)?),
+                "syzygypath" => engine.options.tablebase = Some(Arc::new(Tablebase::new(value)?)),
+                _ => return Err(format!("Unknown option: {}", option)),
+            }
+        }
+
+        Ok(engine)
+    }
+}
+
+impl Engine for InternalEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn new_game(&mut self) -> Result<(), String> {
+        self.tt.clear();
+        Ok(())
+    }
+
+    fn go(
+        &mut self,
+        _start: &Board,
+        _moves: &[Move],
+        board: &Board,
+        limits: &SearchLimits,
+    ) -> Result<EngineMove, String> {
+        if let Some(best_move) = self
+            .book
+            .as_ref()
+            .and_then(|book| book.pick(board, &self.rng))
+        {
+            return Ok(EngineMove {
+                best_move,
+                score: None,
+            });
+        }
+
+        let mut board = board.clone();
+        board.set_network(self.network.clone());
+
+        let time_manager = TimeManager::new(limits, board.color_to_move);
+        let stop = AtomicBool::new(false);
+        let result = search::run(
+            &board,
+            limits,
+            &self.options,
+            &time_manager,
+            &self.tt,
+            &stop,
+        );
+
+        match result.best_move {
+            Some(best_move) => Ok(EngineMove {
+                best_move,
+                score: Some(result.score),
+            }),
+            None => Err("No move found".to_string()),
+        }
+    }
+}
+
+/// An external engine running as a subprocess, spoken to over UCI.
+pub struct UciEngine {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl UciEngine {
+    pub fn start(name: &str, command: &str, options: &[(String, String)]) -> Result<Self, String> {
+        let mut child = Command::new(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Could not start {}: {}", command, e))?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        // Output is read on its own thread, so that a hanging engine can be
+        // timed out.
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = UciEngine {
+            name: name.to_string(),
+            child,
+            stdin,
+            lines,
+        };
+
+        engine.send("uci")?;
+        engine.wait_for("uciok", STARTUP_TIMEOUT)?;
+        for (option, value) in options {
+            engine.send(&format!("setoption name {} value {}", option, value))?;
+        }
+        engine.send("isready")?;
+        engine.wait_for("readyok", STARTUP_TIMEOUT)?;
+
+        Ok(engine)
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), String> {
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|_| format!("{} disconnected", self.name))
+    }
+
+    fn receive(&self, deadline: Instant) -> Result<String, String> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => Err(format!("{} stopped responding", self.name)),
+            Err(RecvTimeoutError::Disconnected) => Err(format!("{} disconnected", self.name)),
+        }
+    }
+
+    fn wait_for(&self, token: &str, timeout: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + timeout;
+        while self.receive(deadline)?.trim() != token {}
+
+        Ok(())
+    }
+}
+
+impl Engine for UciEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn new_game(&mut self) -> Result<(), String> {
+        self.send("ucinewgame")?;
+        self.send("isready")?;
+        self.wait_for("readyok", STARTUP_TIMEOUT)
+    }
+
+    fn go(
+        &mut self,
+        start: &Board,
+        moves: &[Move],
+        board: &Board,
+        limits: &SearchLimits,
+    ) -> Result<EngineMove, String> {
+        let mut position = format!("position fen {}", start.to_fen());
+        if !moves.is_empty() {
+            position.push_str(" moves");
+            for m in moves {
+                position.push(' ');
+                position.push_str(&m.as_string());
+            }
+        }
+        self.send(&position)?;
+        self.send(&go_command(limits))?;
+
+        let timeout = match limits.time_for_color(board.color_to_move) {
+            (Some(time), _) => Duration::from_millis(time),
+            _ => match limits.move_time {
+                Some(move_time) => Duration::from_millis(move_time),
+                None => UNLIMITED_MOVE_TIMEOUT,
+            },
+        };
+        let deadline = Instant::now() + timeout + MOVE_TIMEOUT_MARGIN;
+        let mut score = None;
+
+        loop {
+            let line = self.receive(deadline)?;
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+
+            match tokens.first() {
+                Some(&"info") => {
+                    if let Some(index) = tokens.iter().position(|t| *t == "score") {
+                        score = parse_score(&tokens[index + 1..]).or(score);
+                    }
+                }
+                Some(&"bestmove") => {
+                    let best_move = tokens
+                        .get(1)
+                        .and_then(|m| Move::from_string(m, board).ok())
+                        .ok_or(format!("{} played an illegal move: {}", self.name, line))?;
+
+                    return Ok(EngineMove { best_move, score });
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn go_command(limits: &SearchLimits) -> String {
+    let mut command = "go".to_string();
+    let parameters = [
+        ("wtime", limits.white_time),
+        ("btime", limits.black_time),
+        ("winc", limits.white_increment),
+        ("binc", limits.black_increment),
+        ("movestogo", limits.moves_to_go),
+        ("movetime", limits.move_time),
+        ("depth", limits.depth.map(u64::from)),
+        ("nodes", limits.nodes),
+    ];
+
+    for (name, value) in parameters {
+        if let Some(value) = value {
+            command.push_str(&format!(" {} {}", name, value));
+        }
+    }
+
+    command
+}
+
+// Parses `cp <x>` or `mate <n>` into a score in the engine's own scale.
+fn parse_score(tokens: &[&str]) -> Option<i32> {
+    let value: i32 = tokens.get(1)?.parse().ok()?;
+
+    match *tokens.first()? {
+        "cp" => Some(value),
+        "mate" if value > 0 => Some(MATE_SCORE - (2 * value - 1)),
+        "mate" => Some(-MATE_SCORE - 2 * value),
+        _ => None,
+    }
+}
@@ -0,0 +1,378 @@
+use std::time::Instant;
+
+use super::engine::Engine;
+use crate::core::pgn::PgnGame;
+use crate::core::{Board, Color, Move};
+use crate::search::SearchLimits;
+
+// Time an engine may exceed its clock by before it loses on time, to allow
+// for the scheduling of the engines' threads.
+const TIME_MARGIN: u64 = 50;
+
+/// Limits of every move of a game. Clock times are in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeControl {
+    Clock { time: u64, increment: u64 },
+    MoveTime(u64),
+    Nodes(u64),
+    Depth(u8),
+}
+
+impl TimeControl {
+    /// Parses `<seconds>+<increment>` such as `10+0.1`, or one of
+    /// `movetime=<ms>`, `nodes=<n>` and `depth=<n>`.
+    pub fn parse(string: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid time control: {}", string);
+
+        if let Some((name, value)) = string.split_once('=') {
+            return match name {
+                "movetime" => Ok(TimeControl::MoveTime(value.parse().map_err(|_| invalid())?)),
+                "nodes" => Ok(TimeControl::Nodes(value.parse().map_err(|_| invalid())?)),
+                "depth" => Ok(TimeControl::Depth(value.parse().map_err(|_| invalid())?)),
+                _ => Err(invalid()),
+            };
+        }
+
+        let (time, increment) = string.split_once('+').unwrap_or((string, "0"));
+        let seconds = |value: &str| -> Result<u64, String> {
+            let seconds: f64 = value.parse().map_err(|_| invalid())?;
+            if seconds < 0.0 {
+                return Err(invalid());
+            }
+
+            Ok((seconds * 1000.0).round() as u64)
+        };
+
+        Ok(TimeControl::Clock {
+            time: seconds(time)?,
+            increment: seconds(increment)?,
+        })
+    }
+
+    /// Formats the time control as in the PGN `TimeControl` tag.
+    pub fn as_string(&self) -> String {
+        match self {
+            TimeControl::Clock { time, increment } => {
+                format!("{}+{}", *time as f64 / 1000.0, *increment as f64 / 1000.0)
+            }
+            TimeControl::MoveTime(time) => format!("movetime={}", time),
+            TimeControl::Nodes(nodes) => format!("nodes={}", nodes),
+            TimeControl::Depth(depth) => format!("depth={}", depth),
+        }
+    }
+
+    fn limits(&self, clocks: [u64; 2]) -> SearchLimits {
+        match *self {
+            TimeControl::Clock { increment, .. } => SearchLimits {
+                white_time: Some(clocks[0]),
+                black_time: Some(clocks[1]),
+                white_increment: Some(increment),
+                black_increment: Some(increment),
+                ..Default::default()
+            },
+            TimeControl::MoveTime(time) => SearchLimits {
+                move_time: Some(time),
+                ..Default::default()
+            },
+            TimeControl::Nodes(nodes) => SearchLimits {
+                nodes: Some(nodes),
+                ..Default::default()
+            },
+            TimeControl::Depth(depth) => SearchLimits {
+                depth: Some(depth),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Rules for ending games early based on the scores the engines report.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Adjudication {
+    /// A game is drawn once both engines have reported a score within `score`
+    /// centipawns of zero for `move_count` consecutive moves each, from move
+    /// `move_number` on.
+    pub draw: Option<DrawAdjudication>,
+    /// A game is lost by an engine which has reported a score of `score`
+    /// centipawns or more below zero for `move_count` consecutive moves.
+    pub resign: Option<ResignAdjudication>,
+    /// A game is drawn after this many moves, if it has not ended before.
+    pub max_moves: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrawAdjudication {
+    pub move_number: usize,
+    pub move_count: usize,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResignAdjudication {
+    pub move_count: usize,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl GameResult {
+    pub fn win_for(color: Color) -> Self {
+        if color == Color::White {
+            GameResult::WhiteWins
+        } else {
+            GameResult::BlackWins
+        }
+    }
+
+    pub fn as_string(&self) -> &str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+        }
+    }
+
+    /// Returns white's score: 1.0 for a win, 0.5 for a draw and 0.0 for a loss.
+    pub fn white_score(&self) -> f64 {
+        match self {
+            GameResult::WhiteWins => 1.0,
+            GameResult::BlackWins => 0.0,
+            GameResult::Draw => 0.5,
+        }
+    }
+}
+
+/// A finished game with the reason it ended and the score each engine
+/// reported for its moves, relative to the side to move.
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub game: PgnGame,
+    pub result: GameResult,
+    pub reason: String,
+    pub scores: Vec<Option<i32>>,
+}
+
+/// Plays a game from `start` between two engines, which are told about the
+/// new game first. Engine errors, illegal moves and time forfeits lose the
+/// game for the engine at fault.
+pub fn play(
+    white: &mut dyn Engine,
+    black: &mut dyn Engine,
+    start: &Board,
+    time_control: TimeControl,
+    adjudication: &Adjudication,
+) -> GameRecord {
+    let mut game = PgnGame::new(start.clone());
+    game.set_tag("White", white.name());
+    game.set_tag("Black", black.name());
+
+    let mut scores = Vec::new();
+
+    let (result, reason, termination) = match white.new_game().and_then(|_| black.new_game()) {
+        Ok(()) => play_moves(
+            white,
+            black,
+            time_control,
+            adjudication,
+            &mut game,
+            &mut scores,
+        ),
+        Err(error) => (GameResult::Draw, error, "abandoned"),
+    };
+
+    game.set_tag("Result", result.as_string());
+    game.set_tag("TimeControl", &time_control.as_string());
+    game.set_tag("Termination", termination);
+
+    GameRecord {
+        game,
+        result,
+        reason,
+        scores,
+    }
+}
+
+// Plays the moves of `game` until it ends, returning the result, the reason
+// and the PGN termination.
+fn play_moves(
+    white: &mut dyn Engine,
+    black: &mut dyn Engine,
+    time_control: TimeControl,
+    adjudication: &Adjudication,
+    game: &mut PgnGame,
+    scores: &mut Vec<Option<i32>>,
+) -> (GameResult, String, &'static str) {
+    let mut board = game.start.clone();
+    let mut clocks = match time_control {
+        TimeControl::Clock { time, .. } => [time; 2],
+        _ => [0; 2],
+    };
+    let mut adjudicator = Adjudicator::default();
+
+    loop {
+        if let Some((result, reason)) = game_over(&mut board) {
+            break (result, reason, "normal");
+        }
+
+        let color = board.color_to_move;
+        let engine: &mut dyn Engine = if color == Color::White {
+            &mut *white
+        } else {
+            &mut *black
+        };
+        let loss = GameResult::win_for(color.enemy());
+
+        let start_time = Instant::now();
+        let engine_move = match engine.go(
+            &game.start,
+            &game.moves,
+            &board,
+            &time_control.limits(clocks),
+        ) {
+            Ok(engine_move) => engine_move,
+            Err(error) => break (loss, error, "abandoned"),
+        };
+
+        if let TimeControl::Clock { increment, .. } = time_control {
+            let clock = &mut clocks[color.to_usize()];
+            let elapsed = start_time.elapsed().as_millis() as u64;
+
+            if elapsed > *clock + TIME_MARGIN {
+                let reason = format!("{} loses on time", engine.name());
+                break (loss, reason, "time forfeit");
+            }
+            *clock = clock.saturating_sub(elapsed) + increment;
+        }
+
+        if !is_legal(&mut board, engine_move.best_move) {
+            let reason = format!(
+                "{} played an illegal move: {}",
+                engine.name(),
+                engine_move.best_move.as_string()
+            );
+            break (loss, reason, "rules infraction");
+        }
+
+        board.make_move(&engine_move.best_move);
+        game.moves.push(engine_move.best_move);
+        scores.push(engine_move.score);
+
+        let name = engine.name().to_string();
+        if let Some((result, reason)) =
+            adjudicator.update(adjudication, &board, color, engine_move.score, &name)
+        {
+            break (result, reason, "adjudication");
+        }
+    }
+}
+
+/// Returns the result and the reason if the game has ended by the rules.
+pub fn game_over(board: &mut Board) -> Option<(GameResult, String)> {
+    let mut moves = [Move(0); 218];
+    let moves_count = board.get_moves(&mut moves);
+    let color = board.color_to_move;
+    let has_legal_move = moves[..moves_count].iter().any(|m| {
+        board.make_move(m);
+        let is_legal = !board.is_king_in_check(color);
+        board.undo_move(m);
+
+        is_legal
+    });
+
+    if !has_legal_move {
+        return Some(if board.is_in_check() {
+            let winner = board.color_to_move.enemy();
+            (
+                GameResult::win_for(winner),
+                format!("{} mates", winner.as_string()),
+            )
+        } else {
+            (GameResult::Draw, "Stalemate".to_string())
+        });
+    }
+
+    if board.half_move_clock >= 100 {
+        Some((GameResult::Draw, "Draw by fifty moves rule".to_string()))
+    } else if board.repetitions() >= 2 {
+        Some((GameResult::Draw, "Draw by threefold repetition".to_string()))
+    } else if board.is_insufficient_material() {
+        Some((
+            GameResult::Draw,
+            "Draw by insufficient material".to_string(),
+        ))
+    } else {
+        None
+    }
+}
+
+fn is_legal(board: &mut Board, m: Move) -> bool {
+    let mut moves = [Move(0); 218];
+    let moves_count = board.get_moves(&mut moves);
+    if !moves[..moves_count].contains(&m) {
+        return false;
+    }
+
+    let color = board.color_to_move;
+    board.make_move(&m);
+    let is_legal = !board.is_king_in_check(color);
+    board.undo_move(&m);
+
+    is_legal
+}
+
+// Counts consecutive moves with scores that satisfy the adjudication rules.
+#[derive(Default)]
+struct Adjudicator {
+    draw_moves: usize,
+    resign_moves: [usize; 2],
+}
+
+impl Adjudicator {
+    fn update(
+        &mut self,
+        adjudication: &Adjudication,
+        board: &Board,
+        color: Color,
+        score: Option<i32>,
+        name: &str,
+    ) -> Option<(GameResult, String)> {
+        let side = color.to_usize();
+
+        if let Some(draw) = adjudication.draw {
+            match score {
+                Some(score) if score.abs() <= draw.score => self.draw_moves += 1,
+                _ => self.draw_moves = 0,
+            }
+
+            if board.full_move_number > draw.move_number && self.draw_moves >= 2 * draw.move_count {
+                return Some((GameResult::Draw, "Draw by adjudication".to_string()));
+            }
+        }
+
+        if let Some(resign) = adjudication.resign {
+            match score {
+                Some(score) if score <= -resign.score => self.resign_moves[side] += 1,
+                _ => self.resign_moves[side] = 0,
+            }
+
+            if self.resign_moves[side] >= resign.move_count {
+                return Some((
+                    GameResult::win_for(color.enemy()),
+                    format!("{} resigns", name),
+                ));
+            }
+        }
+
+        match adjudication.max_moves {
+            Some(max_moves) if board.full_move_number > max_moves => {
+                Some((GameResult::Draw, "Draw by maximum game length".to_string()))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,215 @@
+pub mod engine;
+pub mod game;
+pub mod stats;
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::core::{pgn, Board};
+
+pub use engine::{Engine, EngineSpec};
+pub use game::{Adjudication, GameRecord, GameResult, TimeControl};
+pub use stats::{Score, Sprt, SprtResult};
+
+/// Settings of a match between two engines. Every opening is played twice,
+/// with the engines swapping colours.
+#[derive(Debug, Clone)]
+pub struct MatchSettings {
+    pub engines: [EngineSpec; 2],
+    pub games: usize,
+    pub concurrency: usize,
+    pub time_control: TimeControl,
+    pub openings: Vec<Board>,
+    pub adjudication: Adjudication,
+    pub pgn_path: Option<String>,
+    pub sprt: Option<Sprt>,
+}
+
+impl MatchSettings {
+    pub fn new(engines: [EngineSpec; 2], time_control: TimeControl) -> Self {
+        MatchSettings {
+            engines,
+            games: 2,
+            concurrency: 1,
+            time_control,
+            openings: vec![Board::new()],
+            adjudication: Adjudication::default(),
+            pgn_path: None,
+            sprt: None,
+        }
+    }
+}
+
+/// A game of a match, as passed to the progress callback of `run`.
+pub struct FinishedGame<'a> {
+    /// Number of the game, starting from 1.
+    pub number: usize,
+    pub record: &'a GameRecord,
+    /// Whether the first engine played white.
+    pub first_is_white: bool,
+    pub score: Score,
+    pub sprt: Option<SprtResult>,
+}
+
+/// Plays the match on `concurrency` threads, each running its own pair of
+/// engines, and returns the score of the first engine. `report` is called
+/// after every game. The match ends early once the SPRT accepts either
+/// hypothesis, after the games in progress have finished.
+pub fn run(
+    settings: &MatchSettings,
+    report: impl Fn(&FinishedGame) + Sync,
+) -> Result<Score, String> {
+    let pgn_file = match &settings.pgn_path {
+        Some(path) => Some(File::create(path).map_err(|e| format!("{}: {}", path, e))?),
+        None => None,
+    };
+    if settings.openings.is_empty() {
+        return Err("No openings to play".to_string());
+    }
+
+    let next_game = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let results = Mutex::new((Score::default(), pgn_file));
+    let date = chrono::Local::now().format("%Y.%m.%d").to_string();
+
+    let engines = unique_names(&settings.engines);
+
+    let errors: Vec<String> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..settings.concurrency.clamp(1, settings.games.max(1)))
+            .map(|_| {
+                scope.spawn(|| -> Result<(), String> {
+                    let mut engines = [engines[0].start()?, engines[1].start()?];
+
+                    while !stop.load(Ordering::Relaxed) {
+                        let index = next_game.fetch_add(1, Ordering::Relaxed);
+                        if index >= settings.games {
+                            break;
+                        }
+
+                        let first_is_white = index.is_multiple_of(2);
+                        let opening = &settings.openings[(index / 2) % settings.openings.len()];
+                        let [first, second] = &mut engines;
+                        let (white, black) = if first_is_white {
+                            (first.as_mut(), second.as_mut())
+                        } else {
+                            (second.as_mut(), first.as_mut())
+                        };
+
+                        let mut record = game::play(
+                            white,
+                            black,
+                            opening,
+                            settings.time_control,
+                            &settings.adjudication,
+                        );
+
+                        let mut tags = vec![
+                            ("Event".to_string(), "Engine match".to_string()),
+                            ("Site".to_string(), "?".to_string()),
+                            ("Date".to_string(), date.clone()),
+                            ("Round".to_string(), (index + 1).to_string()),
+                        ];
+                        tags.append(&mut record.game.tags);
+                        record.game.tags = tags;
+
+                        let mut results = results.lock().unwrap();
+                        let (score, pgn_file) = &mut *results;
+
+                        match (record.result, first_is_white) {
+                            (GameResult::Draw, _) => score.draws += 1,
+                            (GameResult::WhiteWins, true) | (GameResult::BlackWins, false) => {
+                                score.wins += 1
+                            }
+                            _ => score.losses += 1,
+                        }
+
+                        if let Some(file) = pgn_file {
+                            file.write_all(record.game.to_pgn().as_bytes())
+                                .map_err(|e| e.to_string())?;
+                        }
+
+                        let sprt = settings.sprt.map(|sprt| sprt.test(score));
+                        if sprt.is_some_and(|sprt| sprt != SprtResult::Continue) {
+                            stop.store(true, Ordering::Relaxed);
+                        }
+
+                        report(&FinishedGame {
+                            number: index + 1,
+                            record: &record,
+                            first_is_white,
+                            score: *score,
+                            sprt,
+                        });
+                    }
+
+                    Ok(())
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .filter_map(|worker| worker.join().expect("Match thread panicked").err())
+            .collect()
+    });
+
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error);
+    }
+
+    let (score, _) = results.into_inner().unwrap();
+    Ok(score)
+}
+
+/// Loads opening positions from a PGN file, taking the final position of
+/// every game, or from an EPD file with one position per line.
+pub fn load_openings(path: &str) -> Result<Vec<Board>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+
+    if path.ends_with(".pgn") {
+        return Ok(pgn::parse(&content)?
+            .iter()
+            .map(|game| game.end())
+            .collect());
+    }
+
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            // EPD lines only have the first four FEN fields, followed by
+            // operations.
+            let fields: Vec<&str> = line.split_whitespace().take(6).collect();
+            let counters = match fields.get(4..6) {
+                Some(counters) if counters.iter().all(|c| c.parse::<usize>().is_ok()) => {
+                    counters.join(" ")
+                }
+                _ => "0 1".to_string(),
+            };
+
+            Board::from(&format!(
+                "{} {}",
+                fields[..4.min(fields.len())].join(" "),
+                counters
+            ))
+            .map_err(|e| format!("{}: {}", e, line))
+        })
+        .collect()
+}
+
+/// Returns the engines with a number appended to their names if both have the
+/// same name, so that the games tell them apart.
+pub fn unique_names(engines: &[EngineSpec; 2]) -> [EngineSpec; 2] {
+    let mut engines = engines.clone();
+
+    if engines[0].name == engines[1].name {
+        engines[0].name.push_str("-1");
+        engines[1].name.push_str("-2");
+    }
+
+    engines
+}
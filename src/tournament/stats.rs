@@ -0,0 +1,124 @@
+// Two-sided 95% quantile of the normal distribution.
+const Z_95: f64 = 1.959964;
+
+/// Results of a match from the first engine's point of view.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl Score {
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Returns the fraction of points scored, from 0.0 to 1.0.
+    pub fn ratio(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    /// Returns the Elo difference and the margin of its 95% confidence
+    /// interval. The margin is infinite while the interval reaches a score of
+    /// 0% or 100%.
+    pub fn elo(&self) -> (f64, f64) {
+        let games = self.games() as f64;
+        let ratio = self.ratio();
+        let elo = score_to_elo(ratio);
+
+        if games == 0.0 || !elo.is_finite() {
+            return (elo, f64::INFINITY);
+        }
+
+        let deviation = (self.variance() / games).sqrt();
+        let lower = score_to_elo((ratio - Z_95 * deviation).max(0.0));
+        let upper = score_to_elo((ratio + Z_95 * deviation).min(1.0));
+
+        (elo, (upper - lower) / 2.0)
+    }
+
+    /// Returns the log-likelihood ratio of the hypothesis that the Elo
+    /// difference is `elo1` against the hypothesis that it is `elo0`, using
+    /// the normal approximation of the trinomial model.
+    pub fn llr(&self, elo0: f64, elo1: f64) -> f64 {
+        let variance = self.variance();
+        if variance == 0.0 {
+            return 0.0;
+        }
+
+        let score0 = elo_to_score(elo0);
+        let score1 = elo_to_score(elo1);
+
+        (score1 - score0) * (2.0 * self.ratio() - score0 - score1) * self.games() as f64
+            / (2.0 * variance)
+    }
+
+    // Variance of the result of a single game.
+    fn variance(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+
+        let games = self.games() as f64;
+        let ratio = self.ratio();
+
+        (self.wins as f64 * (1.0 - ratio).powi(2)
+            + self.draws as f64 * (0.5 - ratio).powi(2)
+            + self.losses as f64 * ratio.powi(2))
+            / games
+    }
+}
+
+pub fn score_to_elo(score: f64) -> f64 {
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+pub fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// Sequential probability ratio test of whether the first engine is `elo1`
+/// rather than `elo0` Elo stronger, with false positive rate `alpha` and
+/// false negative rate `beta`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtResult {
+    Continue,
+    AcceptH0,
+    AcceptH1,
+}
+
+impl Sprt {
+    /// Returns the lower and upper bound of the log-likelihood ratio.
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    pub fn test(&self, score: &Score) -> SprtResult {
+        let llr = score.llr(self.elo0, self.elo1);
+        let (lower, upper) = self.bounds();
+
+        if llr >= upper {
+            SprtResult::AcceptH1
+        } else if llr <= lower {
+            SprtResult::AcceptH0
+        } else {
+            SprtResult::Continue
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use super::Position;
+use crate::core::board::CastlingRights;
+use crate::core::{BitBoard, Board, Color, Move, Piece, Square};
+use crate::search::{
+    self, SearchLimits, SearchOptions, TimeManager, TranspositionTable, MATE_BOUND,
+};
+use crate::tournament::game::{self, GameResult};
+
+/// Size in bytes of a position in the binary format.
+pub const RECORD_SIZE: usize = 32;
+
+// Pieces in the order of their index, used to decode the piece nibbles.
+const PIECES: [Piece; 12] = [
+    Piece::WP,
+    Piece::WN,
+    Piece::WB,
+    Piece::WR,
+    Piece::WQ,
+    Piece::WK,
+    Piece::BP,
+    Piece::BN,
+    Piece::BB,
+    Piece::BR,
+    Piece::BQ,
+    Piece::BK,
+];
+
+const NO_EN_PASSANT: u8 = 64;
+
+/// Settings of a self-play data generation run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatagenSettings {
+    pub games: usize,
+    pub threads: usize,
+    /// Node limit of the search for every move.
+    pub nodes: u64,
+    /// Number of random moves played from the start position before the
+    /// engine takes over.
+    pub random_plies: usize,
+    /// Openings whose first search scores further than this from zero are
+    /// thrown away, as the game would be decided from the start.
+    pub max_opening_score: i32,
+    /// A game is drawn after this many moves, if it has not ended before.
+    pub max_moves: usize,
+    /// Hash table size of every thread, in megabytes.
+    pub hash: usize,
+    pub seed: u64,
+}
+
+impl Default for DatagenSettings {
+    fn default() -> Self {
+        DatagenSettings {
+            games: 100,
+            threads: 1,
+            nodes: 5000,
+            random_plies: 8,
+            max_opening_score: 300,
+            max_moves: 300,
+            hash: 8,
+            seed: 0,
+        }
+    }
+}
+
+/// A position of a self-play game with the score of its search and the
+/// result of the game, both from white's point of view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    pub board: Board,
+    pub score: i16,
+    pub result: GameResult,
+}
+
+impl Sample {
+    /// Packs the sample into `RECORD_SIZE` bytes: the occupancy, the piece of
+    /// every occupied square in four bits each, the side to move and castling
+    /// rights, the en passant square, the move counters, the score and the
+    /// result.
+    pub fn pack(&self) -> [u8; RECORD_SIZE] {
+        let board = &self.board;
+        let occupancy = board.all_occupancy();
+        let mut record = [0; RECORD_SIZE];
+        record[0..8].copy_from_slice(&occupancy.to_u64().to_le_bytes());
+
+        let mut squares = occupancy;
+        let mut index = 0;
+        while squares.is_not_empty() {
+            let square = Square(squares.bit_scan());
+            let piece = board.piece_at_square(square).unwrap().to_u8();
+            record[8 + index / 2] |= piece << (4 * (index % 2));
+
+            squares = squares.pop_lsb();
+            index += 1;
+        }
+
+        record[24] = board.color_to_move.to_u8() | board.castling_rights.bits() << 4;
+        record[25] = if board.en_passant.is_empty() {
+            NO_EN_PASSANT
+        } else {
+            board.en_passant.bit_scan()
+        };
+        record[26] = board.half_move_clock.min(u8::MAX as usize) as u8;
+        record[27..29]
+            .copy_from_slice(&(board.full_move_number.min(u16::MAX as usize) as u16).to_le_bytes());
+        record[29..31].copy_from_slice(&self.score.to_le_bytes());
+        record[31] = pack_result(self.result);
+
+        record
+    }
+
+    pub fn unpack(record: &[u8]) -> Result<Self, String> {
+        if record.len() != RECORD_SIZE {
+            return Err("Invalid record size".to_string());
+        }
+
+        let mut board = Board::new_empty();
+        let occupancy = u64::from_le_bytes(record[0..8].try_into().unwrap());
+        if occupancy.count_ones() > 32 {
+            return Err("Invalid record: too many pieces".to_string());
+        }
+
+        let mut squares = occupancy;
+        let mut index = 0;
+        while squares != 0 {
+            let square = Square(squares.trailing_zeros() as u8);
+            let piece = (record[8 + index / 2] >> (4 * (index % 2))) & 0xf;
+            let piece = *PIECES
+                .get(piece as usize)
+                .ok_or("Invalid record: unknown piece")?;
+            board.add_piece_to_square(piece, square);
+
+            squares &= squares - 1;
+            index += 1;
+        }
+
+        board.color_to_move = if record[24] & 1 == 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        board.castling_rights = CastlingRights::from_bits_truncate(record[24] >> 4);
+        board.en_passant = match record[25] {
+            NO_EN_PASSANT => BitBoard::EMPTY,
+            square if square < 64 => BitBoard::new(Square(square)),
+            _ => return Err("Invalid record: en passant square".to_string()),
+        };
+        board.half_move_clock = record[26] as usize;
+        board.full_move_number = u16::from_le_bytes([record[27], record[28]]) as usize;
+        board.hash = board.compute_hash();
+        board.update_check_info();
+
+        let result = match record[31] {
+            0 => GameResult::BlackWins,
+            1 => GameResult::Draw,
+            2 => GameResult::WhiteWins,
+            _ => return Err("Invalid record: result".to_string()),
+        };
+
+        Ok(Sample {
+            board,
+            score: i16::from_le_bytes([record[29], record[30]]),
+            result,
+        })
+    }
+
+    /// Formats the sample as `<fen> <score> [<result>]`, which the tuner's
+    /// FEN loader reads directly.
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} {} [{:.1}]",
+            self.board.to_fen(),
+            self.score,
+            self.result.white_score()
+        )
+    }
+}
+
+/// Progress of a run, as passed to the callback of `generate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub games: usize,
+    pub positions: usize,
+}
+
+/// Plays `settings.games` self-play games on `settings.threads` threads and
+/// writes every quiet position to `writer` in the binary format. Positions in
+/// check, positions whose best move is a capture or a promotion and positions
+/// with a mate score are skipped. `report` is called after every game, and
+/// the number of positions written is returned.
+pub fn generate<W: Write + Send>(
+    settings: &DatagenSettings,
+    writer: W,
+    report: impl Fn(&Progress) + Sync,
+) -> Result<usize, String> {
+    let next_game = AtomicUsize::new(0);
+    let output = Mutex::new((
+        writer,
+        Progress {
+            games: 0,
+            positions: 0,
+        },
+    ));
+
+    let errors: Vec<String> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..settings.threads.clamp(1, settings.games.max(1)))
+            .map(|_| {
+                scope.spawn(|| -> Result<(), String> {
+                    let tt = TranspositionTable::new(settings.hash);
+
+                    loop {
+                        let index = next_game.fetch_add(1, Ordering::Relaxed);
+                        if index >= settings.games {
+                            break;
+                        }
+
+                        let mut rng = fastrand::Rng::with_seed(settings.seed ^ index as u64);
+                        let records = loop {
+                            if let Some(records) = play_game(settings, &mut rng, &tt) {
+                                break records;
+                            }
+                        };
+
+                        let mut output = output.lock().unwrap();
+                        let (writer, progress) = &mut *output;
+                        for record in &records {
+                            writer.write_all(record).map_err(|e| e.to_string())?;
+                        }
+
+                        progress.games += 1;
+                        progress.positions += records.len();
+                        report(progress);
+                    }
+
+                    Ok(())
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .filter_map(|worker| worker.join().expect("Datagen thread panicked").err())
+            .collect()
+    });
+
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error);
+    }
+
+    let (mut writer, progress) = output.into_inner().unwrap();
+    writer.flush().map_err(|e| e.to_string())?;
+
+    Ok(progress.positions)
+}
+
+// Plays a game from a random opening and returns its packed positions, or
+// `None` if the opening was unbalanced or already over.
+fn play_game(
+    settings: &DatagenSettings,
+    rng: &mut fastrand::Rng,
+    tt: &TranspositionTable,
+) -> Option<Vec<[u8; RECORD_SIZE]>> {
+    let mut board = random_opening(rng, settings.random_plies)?;
+    let limits = SearchLimits {
+        nodes: Some(settings.nodes),
+        ..Default::default()
+    };
+    let options = SearchOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let mut records = Vec::new();
+    tt.clear();
+
+    let result = loop {
+        if let Some((result, _)) = game::game_over(&mut board) {
+            break result;
+        }
+        if board.full_move_number > settings.max_moves {
+            break GameResult::Draw;
+        }
+
+        let color = board.color_to_move;
+        let time_manager = TimeManager::new(&limits, color);
+        let stop = AtomicBool::new(false);
+        let search = search::run(&board, &limits, &options, &time_manager, tt, &stop);
+        let best_move = search.best_move?;
+
+        if records.is_empty() && search.score.abs() > settings.max_opening_score {
+            return None;
+        }
+
+        // A found mate decides the game, the rest of it would add nothing.
+        if search.score.abs() > MATE_BOUND {
+            break if search.score > 0 {
+                GameResult::win_for(color)
+            } else {
+                GameResult::win_for(color.enemy())
+            };
+        }
+
+        if !board.is_in_check() && !best_move.is_capture() && !best_move.is_promotion() {
+            let score = if color == Color::White {
+                search.score
+            } else {
+                -search.score
+            };
+            let sample = Sample {
+                board: board.clone(),
+                score: score as i16,
+                result: GameResult::Draw,
+            };
+            records.push(sample.pack());
+        }
+
+        board.make_move(&best_move);
+    };
+
+    for record in &mut records {
+        record[RECORD_SIZE - 1] = pack_result(result);
+    }
+
+    Some(records)
+}
+
+fn pack_result(result: GameResult) -> u8 {
+    match result {
+        GameResult::BlackWins => 0,
+        GameResult::Draw => 1,
+        GameResult::WhiteWins => 2,
+    }
+}
+
+// Plays `plies` random legal moves from the start position, returning `None`
+// if the game ends on the way.
+fn random_opening(rng: &mut fastrand::Rng, plies: usize) -> Option<Board> {
+    let mut board = Board::new();
+
+    for _ in 0..plies {
+        let mut moves = [Move(0); 218];
+        let moves_count = board.get_moves(&mut moves);
+        let color = board.color_to_move;
+        let legal_moves: Vec<Move> = moves[..moves_count]
+            .iter()
+            .copied()
+            .filter(|m| {
+                board.make_move(m);
+                let is_legal = !board.is_king_in_check(color);
+                board.undo_move(m);
+
+                is_legal
+            })
+            .collect();
+
+        if legal_moves.is_empty() {
+            return None;
+        }
+        board.make_move(&legal_moves[rng.usize(..legal_moves.len())]);
+    }
+
+    // Start from a fresh board, so that the random moves do not count for
+    // repetitions.
+    Board::from(&board.to_fen()).ok()
+}
+
+/// Reads every sample of a binary file written by `generate`.
+pub fn read(path: &str) -> Result<Vec<Sample>, String> {
+    let content = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    if !content.len().is_multiple_of(RECORD_SIZE) {
+        return Err(format!("{}: truncated record", path));
+    }
+
+    content.chunks(RECORD_SIZE).map(Sample::unpack).collect()
+}
+
+/// Loads a binary file as tuning positions labelled with the game results.
+pub fn load_positions(path: &str) -> Result<Vec<Position>, String> {
+    Ok(read(path)?
+        .into_iter()
+        .map(|sample| Position {
+            result: sample.result.white_score(),
+            board: sample.board,
+        })
+        .collect())
+}
+
+/// Writes the samples of the binary file at `input` to `output` as text, one
+/// per line, and returns their number.
+pub fn export_text(input: &str, output: &str) -> Result<usize, String> {
+    let samples = read(input)?;
+    let file = File::create(output).map_err(|e| format!("{}: {}", output, e))?;
+    let mut writer = BufWriter::new(file);
+
+    for sample in &samples {
+        writeln!(writer, "{}", sample.to_text()).map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+
+    Ok(samples.len())
+}
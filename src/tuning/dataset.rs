@@ -0,0 +1,102 @@
+use crate::core::{pgn, Board};
+use std::fs;
+
+/// A quiet position labelled with the result of its game from white's point
+/// of view: 1.0 for a win, 0.5 for a draw and 0.0 for a loss.
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub board: Board,
+    pub result: f64,
+}
+
+/// Loads positions from a file with one position per line, given as a FEN
+/// followed by the result. Results may be written as `1-0`, `0-1`, `1/2-1/2`
+/// or as a number, optionally in quotes or brackets, so both `<fen> [0.5]`
+/// and `<fen> c9 "1/2-1/2";` are understood. FENs may omit the move counters.
+pub fn load_fens(path: &str) -> Result<Vec<Position>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+
+    parse_fens(&content)
+}
+
+pub fn parse_fens(content: &str) -> Result<Vec<Position>, String> {
+    let mut positions = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let position = parse_line(line).ok_or(format!("Invalid position on line {}", index + 1))?;
+        positions.push(position);
+    }
+
+    Ok(positions)
+}
+
+fn parse_line(line: &str) -> Option<Position> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 5 {
+        return None;
+    }
+
+    let result = parse_result(fields[fields.len() - 1])?;
+
+    // The move counters are optional, anything else between the FEN and the
+    // result (such as an opcode) is ignored.
+    let counters = &fields[4..fields.len() - 1];
+    let counters =
+        if counters.len() >= 2 && counters[..2].iter().all(|c| c.parse::<usize>().is_ok()) {
+            counters[..2].join(" ")
+        } else {
+            "0 1".to_string()
+        };
+
+    let board = Board::from(&format!("{} {}", fields[..4].join(" "), counters)).ok()?;
+
+    Some(Position { board, result })
+}
+
+fn parse_result(result: &str) -> Option<f64> {
+    match result.trim_matches(|c| "[]\";".contains(c)) {
+        "1-0" => Some(1.0),
+        "0-1" => Some(0.0),
+        "1/2-1/2" => Some(0.5),
+        number => number.parse().ok().filter(|r| (0.0..=1.0).contains(r)),
+    }
+}
+
+/// Loads the positions of every decided or drawn game in a PGN file. Positions
+/// where the side to move is in check, or which were reached by a capture or
+/// promotion, are skipped as they are unlikely to be quiet.
+pub fn load_pgn(path: &str) -> Result<Vec<Position>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+
+    parse_pgn(&content)
+}
+
+pub fn parse_pgn(content: &str) -> Result<Vec<Position>, String> {
+    let mut positions = Vec::new();
+
+    for game in pgn::parse(content)? {
+        let result = match parse_result(game.result()) {
+            Some(result) => result,
+            None => continue,
+        };
+
+        let mut board = game.start.clone();
+        for m in &game.moves {
+            board.make_move(m);
+
+            if !m.is_capture() && !m.is_promotion() && !board.is_in_check() {
+                positions.push(Position {
+                    board: Board::from(&board.to_fen()).map_err(|e| e.to_string())?,
+                    result,
+                });
+            }
+        }
+    }
+
+    Ok(positions)
+}
@@ -0,0 +1,280 @@
+pub mod datagen;
+pub mod dataset;
+
+use crate::evaluation::{self, params, EvalParams, PawnTable};
+use std::f64::consts::LN_10;
+use std::thread;
+
+pub use dataset::Position;
+
+// Pawn table size of each thread while computing the error, the positions of
+// a dataset rarely share their pawn structure.
+const PAWN_TABLE_SIZE: usize = 1 << 12;
+
+// Parameters of the Adam optimiser used by `Method::GradientDescent`.
+const BETA1: f64 = 0.9;
+const BETA2: f64 = 0.999;
+const EPSILON: f64 = 1e-8;
+
+/// Optimisation methods supported by the tuner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    /// Tries to move every parameter by one in either direction and keeps the
+    /// changes which reduce the error.
+    LocalSearch,
+    /// Adam with the given learning rate, in centipawns per iteration.
+    GradientDescent(f64),
+}
+
+/// Texel tuner which fits the evaluation parameters to the results of the
+/// games the positions were taken from.
+pub struct Tuner {
+    pub positions: Vec<Position>,
+    pub params: EvalParams,
+    pub k: f64,
+    pub threads: usize,
+}
+
+impl Tuner {
+    pub fn new(positions: Vec<Position>, params: EvalParams, threads: usize) -> Self {
+        Tuner {
+            positions,
+            params,
+            k: 1.0,
+            threads: threads.max(1),
+        }
+    }
+
+    /// Returns the mean squared error between the game results and the
+    /// evaluations of the positions, mapped to an expected score by `sigmoid`.
+    pub fn error(&self, params: &EvalParams) -> f64 {
+        error(&self.positions, params, self.k, self.threads)
+    }
+
+    /// Sets the scaling constant `k` to the value which minimises the error of
+    /// the current parameters, and returns that error.
+    pub fn fit_k(&mut self) -> f64 {
+        let mut best = self.error(&self.params);
+        let mut step = 1.0;
+
+        // Refine k one decimal place at a time, walking in the direction which
+        // reduces the error.
+        for _ in 0..6 {
+            for direction in [1.0, -1.0] {
+                loop {
+                    let k = self.k + direction * step;
+                    if k <= 0.0 {
+                        break;
+                    }
+
+                    let error = error(&self.positions, &self.params, k, self.threads);
+                    if error >= best {
+                        break;
+                    }
+
+                    self.k = k;
+                    best = error;
+                }
+            }
+
+            step /= 10.0;
+        }
+
+        best
+    }
+
+    /// Runs `iterations` passes of `method`, calling `report` with the
+    /// iteration, the parameters and their error after each of them. Stops
+    /// early if an iteration of the local search finds no improvement.
+    pub fn tune(
+        &mut self,
+        method: Method,
+        iterations: usize,
+        mut report: impl FnMut(usize, &EvalParams, f64),
+    ) -> f64 {
+        let mut error = self.error(&self.params);
+
+        match method {
+            Method::LocalSearch => {
+                for iteration in 1..=iterations {
+                    let improved;
+                    (improved, error) = self.local_search_pass(error);
+                    report(iteration, &self.params, error);
+
+                    if !improved {
+                        break;
+                    }
+                }
+            }
+            Method::GradientDescent(rate) => {
+                let mut weights: Vec<f64> =
+                    self.params.to_vector().iter().map(|&w| w as f64).collect();
+                let mut moment = vec![0.0; weights.len()];
+                let mut velocity = vec![0.0; weights.len()];
+
+                for iteration in 1..=iterations {
+                    let gradient = self.gradient();
+
+                    for (index, weight) in weights.iter_mut().enumerate() {
+                        let g = gradient[index];
+                        moment[index] = BETA1 * moment[index] + (1.0 - BETA1) * g;
+                        velocity[index] = BETA2 * velocity[index] + (1.0 - BETA2) * g * g;
+
+                        let moment = moment[index] / (1.0 - BETA1.powi(iteration as i32));
+                        let velocity = velocity[index] / (1.0 - BETA2.powi(iteration as i32));
+                        *weight -= rate * moment / (velocity.sqrt() + EPSILON);
+                    }
+
+                    let vector: Vec<i32> = weights.iter().map(|w| w.round() as i32).collect();
+                    self.params.set_vector(&vector);
+                    error = self.error(&self.params);
+                    report(iteration, &self.params, error);
+                }
+            }
+        }
+
+        error
+    }
+
+    fn local_search_pass(&mut self, mut best: f64) -> (bool, f64) {
+        let mut vector = self.params.to_vector();
+        let mut params = self.params.clone();
+        let mut improved = false;
+
+        for index in 0..vector.len() {
+            for delta in [1, -1] {
+                vector[index] += delta;
+                params.set_vector(&vector);
+
+                let error = self.error(&params);
+                if error < best {
+                    best = error;
+                    improved = true;
+                    break;
+                }
+
+                vector[index] -= delta;
+            }
+        }
+
+        params.set_vector(&vector);
+        self.params = params;
+
+        (improved, best)
+    }
+
+    /// Returns the derivative of the error by each parameter, in the order of
+    /// `EvalParams::to_vector`.
+    pub fn gradient(&self) -> Vec<f64> {
+        gradient(&self.positions, &self.params, self.k, self.threads)
+    }
+}
+
+/// Maps an evaluation from white's point of view to white's expected score.
+pub fn sigmoid(score: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * score / 400.0))
+}
+
+/// Mean squared error of `params` over `positions`, split between `threads`.
+pub fn error(positions: &[Position], params: &EvalParams, k: f64, threads: usize) -> f64 {
+    if positions.is_empty() {
+        return 0.0;
+    }
+
+    let chunk_size = positions.len().div_ceil(threads.max(1));
+    let total: f64 = thread::scope(|scope| {
+        let handles: Vec<_> = positions
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut pawn_table = PawnTable::new(PAWN_TABLE_SIZE);
+
+                    chunk
+                        .iter()
+                        .map(|position| {
+                            let score = evaluation::breakdown_with(
+                                &position.board,
+                                &mut pawn_table,
+                                params,
+                            )
+                            .total();
+
+                            (position.result - sigmoid(score as f64, k)).powi(2)
+                        })
+                        .sum::<f64>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .sum()
+    });
+
+    total / positions.len() as f64
+}
+
+/// Derivative of the mean squared error of `params` over `positions` by each
+/// parameter, split between `threads`. One traced evaluation of each position
+/// gives the derivatives of its score, which the chain rule turns into those
+/// of its error.
+pub fn gradient(positions: &[Position], params: &EvalParams, k: f64, threads: usize) -> Vec<f64> {
+    let mut gradient = vec![0.0; params::index::COUNT];
+    if positions.is_empty() {
+        return gradient;
+    }
+
+    let chunk_size = positions.len().div_ceil(threads.max(1));
+    thread::scope(|scope| {
+        let handles: Vec<_> = positions
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut pawn_table = PawnTable::new(1);
+                    let mut gradient = vec![0.0; params::index::COUNT];
+                    let mut trace = Vec::new();
+
+                    for position in chunk {
+                        trace.clear();
+                        let score = evaluation::breakdown_traced(
+                            &position.board,
+                            &mut pawn_table,
+                            params,
+                            &mut trace,
+                        )
+                        .total();
+
+                        // d/dscore of (result - sigmoid(score))^2.
+                        let expected = sigmoid(score as f64, k);
+                        let derivative = -2.0
+                            * (position.result - expected)
+                            * expected
+                            * (1.0 - expected)
+                            * k
+                            * LN_10
+                            / 400.0;
+
+                        for &(index, coefficient) in &trace {
+                            gradient[index] += derivative * coefficient;
+                        }
+                    }
+
+                    gradient
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            for (total, value) in gradient.iter_mut().zip(handle.join().unwrap()) {
+                *total += value;
+            }
+        }
+    });
+
+    for value in gradient.iter_mut() {
+        *value /= positions.len() as f64;
+    }
+
+    gradient
+}
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod bitboard_tests {
-    use rust_chess_engine::core::{square, BitBoard};
+    use rust_chess_engine::core::movegen::Direction;
+    use rust_chess_engine::core::{square, BitBoard, Square};
 
     #[test]
     fn test_lsb() {
@@ -34,4 +35,113 @@ mod bitboard_tests {
             bitboard
         );
     }
+
+    #[test]
+    fn test_bitboard_iter() {
+        let bitboard = BitBoard::from_squares(&[square::H8, square::A1, square::D4]);
+        let squares: Vec<Square> = bitboard.into_iter().collect();
+        assert_eq!(vec![square::A1, square::D4, square::H8], squares);
+        assert_eq!(3, bitboard.into_iter().len());
+        assert_eq!(bitboard, squares.into_iter().collect());
+        assert_eq!(None, BitBoard::EMPTY.into_iter().next());
+    }
+
+    #[test]
+    fn test_bitboard_set_operations() {
+        let bitboard = BitBoard::from_squares(&[square::A1, square::D4]);
+        assert!(bitboard.contains(BitBoard::new(square::D4)));
+        assert!(bitboard.contains(BitBoard::EMPTY));
+        assert!(!bitboard.contains(BitBoard::from_squares(&[square::D4, square::E4])));
+
+        assert!(bitboard.more_than_one());
+        assert!(!BitBoard::new(square::D4).more_than_one());
+        assert!(!BitBoard::EMPTY.more_than_one());
+    }
+
+    #[test]
+    fn test_bitboard_shift() {
+        let bitboard = BitBoard::from_squares(&[square::A4, square::H4]);
+        assert_eq!(
+            BitBoard::from_squares(&[square::A5, square::H5]),
+            bitboard.shift(Direction::North)
+        );
+        assert_eq!(
+            BitBoard::new(square::B5),
+            bitboard.shift(Direction::NorthEast)
+        );
+        assert_eq!(BitBoard::new(square::B4), bitboard.shift(Direction::East));
+        assert_eq!(
+            BitBoard::new(square::G3),
+            bitboard.shift(Direction::SouthWest)
+        );
+        assert_eq!(
+            BitBoard::new(square::G5),
+            bitboard.shift(Direction::NorthWest)
+        );
+        assert_eq!(BitBoard::EMPTY, BitBoard::RANK_8.shift(Direction::North));
+    }
+
+    #[test]
+    fn test_bitboard_fill() {
+        let bitboard = BitBoard::new(square::D4);
+        assert_eq!(
+            BitBoard::FILE_D & !(BitBoard::RANK_1 | BitBoard::RANK_2 | BitBoard::RANK_3),
+            bitboard.north_fill()
+        );
+        assert_eq!(
+            BitBoard::FILE_D
+                & (BitBoard::RANK_1 | BitBoard::RANK_2 | BitBoard::RANK_3 | BitBoard::RANK_4),
+            bitboard.south_fill()
+        );
+        assert_eq!(BitBoard::FILE_D, bitboard.file_fill());
+    }
+
+    #[test]
+    fn test_bitboard_flips() {
+        let bitboard = BitBoard::from_squares(&[square::A1, square::C2]);
+        assert_eq!(
+            BitBoard::from_squares(&[square::A8, square::C7]),
+            bitboard.flip_vertical()
+        );
+        assert_eq!(
+            BitBoard::from_squares(&[square::H1, square::F2]),
+            bitboard.mirror_horizontal()
+        );
+        assert_eq!(
+            BitBoard::from_squares(&[square::H8, square::F7]),
+            bitboard.rotate()
+        );
+    }
+
+    #[test]
+    fn test_bitboard_lines() {
+        assert_eq!(BitBoard::FILE_C, BitBoard::file(2));
+        assert_eq!(BitBoard::RANK_6, BitBoard::rank(5));
+        assert_eq!(
+            BitBoard::from_squares(&[
+                square::B1,
+                square::C2,
+                square::D3,
+                square::E4,
+                square::F5,
+                square::G6,
+                square::H7
+            ]),
+            BitBoard::diagonal(square::E4)
+        );
+        assert_eq!(BitBoard::DIAGONAL_A1_H8, BitBoard::diagonal(square::D4));
+        assert_eq!(BitBoard::new(square::A8), BitBoard::diagonal(square::A8));
+        assert_eq!(
+            BitBoard::from_squares(&[square::A2, square::B1]),
+            BitBoard::anti_diagonal(square::B1)
+        );
+        assert_eq!(
+            BitBoard::DIAGONAL_A8_H1,
+            BitBoard::anti_diagonal(square::E4)
+        );
+        assert_eq!(
+            BitBoard::new(square::H8),
+            BitBoard::anti_diagonal(square::H8)
+        );
+    }
 }
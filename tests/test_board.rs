@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod board_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{square, BitBoard, Board, Color, Move, Piece, PieceType, Square};
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    fn init() {
+        START.call_once(|| {
+            movegen::init();
+        });
+    }
+
+    // Plays every legal move up to `depth` plies, checking `gives_check` and
+    // the cached check information against a board read from the FEN, and
+    // that undoing each move restores the board exactly.
+    fn check_moves(board: &mut Board, depth: usize) {
+        let before = board.clone();
+        let color = board.color_to_move;
+        let mut moves = [Move(0); 218];
+        let moves_count = board.get_moves(&mut moves);
+
+        for m in &moves[..moves_count] {
+            let gives_check = board.gives_check(m);
+            board.make_move(m);
+            if board.is_king_in_check(color) {
+                board.undo_move(m);
+                continue;
+            }
+            assert_eq!(gives_check, board.is_in_check(), "{}", m.as_string());
+
+            let fresh = Board::from(&board.to_fen()).unwrap();
+            assert_eq!(board.check_info, fresh.check_info, "{}", m.as_string());
+
+            if depth > 1 {
+                check_moves(board, depth - 1);
+            }
+            board.undo_move(m);
+            assert_eq!(*board, before, "{}", m.as_string());
+        }
+    }
+
+    #[test]
+    fn test_board_undo_move() {
+        init();
+
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 12 30",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+
+        for fen in fens {
+            let mut board = Board::from(fen).unwrap();
+            check_moves(&mut board, 3);
+            assert!(board.states.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_board_check_info() {
+        init();
+
+        // The rook pins the knight on d2 and the bishop the pawn on f3.
+        let board = Board::from("3r2k1/8/8/7b/8/5P2/3N4/3K4 w - - 0 1").unwrap();
+        assert_eq!(board.checkers(), BitBoard::EMPTY);
+        assert_eq!(
+            board.pinned(Color::White),
+            BitBoard::new(square::D2) | BitBoard::new(square::F3)
+        );
+        assert_eq!(board.pinned(Color::Black), BitBoard::EMPTY);
+
+        // Two pieces between the bishop and the king are not pinned.
+        let board = Board::from("3r2k1/8/8/7b/6P1/5P2/3N4/3K4 w - - 0 1").unwrap();
+        assert_eq!(board.pinned(Color::White), BitBoard::new(square::D2));
+
+        let board = Board::from("3r2k1/8/8/7b/8/8/8/3K4 w - - 0 1").unwrap();
+        assert_eq!(
+            board.checkers(),
+            BitBoard::new(square::D8) | BitBoard::new(square::H5)
+        );
+        assert_eq!(board.pinned(Color::White), BitBoard::EMPTY);
+
+        // Pieces of the side to move between its slider and the enemy king
+        // give discovered checks, and only those leaving the line.
+        let board = Board::from("4k3/8/8/8/4N3/8/4R3/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.blockers_for_king(Color::Black),
+            BitBoard::new(square::E4)
+        );
+        let moves = [
+            ("e4c5", true),
+            ("e4d6", true),
+            ("e2e3", false),
+            ("e1d1", false),
+        ];
+        for (m, gives_check) in moves {
+            let m = Move::from_string(m, &board).unwrap();
+            assert_eq!(board.gives_check(&m), gives_check, "{}", m.as_string());
+        }
+    }
+
+    #[test]
+    fn test_board_gives_check_special_moves() {
+        init();
+
+        let fens = [
+            // Castling checks with the rook.
+            "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+            "3k4/8/8/8/8/8/8/R3K3 w Q - 0 1",
+            // En passant uncovers the rook and the bishop.
+            "8/8/8/K2pP2k/8/8/8/8 w - d6 0 1",
+            "8/8/8/3pP3/8/8/8/K3k2B w - d6 0 1",
+            // Promotions, with captures and on a line opened by the pawn.
+            "1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+            "3k4/1P6/8/8/8/8/8/4K3 w - - 0 1",
+            "7k/1P6/8/8/8/8/8/4K3 w - - 0 1",
+        ];
+
+        for fen in fens {
+            let mut board = Board::from(fen).unwrap();
+            check_moves(&mut board, 2);
+        }
+    }
+
+    #[test]
+    fn test_board_attacks() {
+        init();
+
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let board = Board::from(fen).unwrap();
+
+        // The white knight on e5 attacks d7, and so do the black knights,
+        // queen and king.
+        let attackers = board.attackers_to(square::D7, board.all_occupancy());
+        assert_eq!(
+            attackers,
+            BitBoard::from_squares(&[square::E5, square::B6, square::F6, square::E7, square::E8])
+        );
+
+        for color in [Color::White, Color::Black] {
+            let attacks = board.attacks_by(color);
+            for index in 0..64 {
+                let square = Square::try_from(index).unwrap();
+                assert_eq!(
+                    attacks.is_set(square),
+                    board.are_squares_attacked(&[square], color.enemy()),
+                    "{} {}",
+                    color,
+                    square.as_string()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_board_pieces() {
+        init();
+
+        for (index, piece) in Piece::ALL.iter().enumerate() {
+            assert_eq!(Piece::from_index(index), Some(*piece));
+            assert_eq!(piece.to_usize(), index);
+            assert_eq!(Piece::new(piece.color(), piece.kind()), *piece);
+        }
+        assert_eq!(Piece::from_index(12), None);
+        assert_eq!(Piece::BQ.color(), Color::Black);
+        assert_eq!(Piece::BQ.kind(), PieceType::Queen);
+        assert_eq!(Piece::WN.to_char(), 'N');
+        assert_eq!(Piece::BK.to_char(), 'k');
+        assert_eq!(Color::White.enemy(), Color::Black);
+
+        let board = Board::new();
+        assert_eq!(board.piece_at_square(square::E1), Some(Piece::WK));
+        assert_eq!(board.piece_at_square(square::D8), Some(Piece::BQ));
+        assert_eq!(board.piece_at_square(square::E4), None);
+
+        let board = Board::from("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let m = Move::from_string("a7b8n", &board).unwrap();
+        assert_eq!(m.promotion_kind(), Some(PieceType::Knight));
+        assert_eq!(m.promotion(Color::White), Some(Piece::WN));
+        assert_eq!(m.promotion_char(), Some('n'));
+        let m = Move::from_string("e1e2", &board).unwrap();
+        assert_eq!(m.promotion(Color::White), None);
+        assert_eq!(m.promotion_char(), None);
+    }
+}
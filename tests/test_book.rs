@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod book_tests {
+    use rust_chess_engine::book::{self, Book, BookBuilder, BookEntry};
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{pgn, Board, Move, Square};
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    fn init() {
+        START.call_once(|| {
+            movegen::init();
+        });
+    }
+
+    #[test]
+    fn test_polyglot_keys() {
+        init();
+
+        // Reference keys from the Polyglot book format specification.
+        let positions = [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                0x463b96181691fc9c,
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                0x823c9b50fd114196,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                0x0756b94461c50fb0,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+                0x662fafb965db29d4,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                0x22a48b5a8e47ff78,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3",
+                0x652a607ca3f242c1,
+            ),
+            (
+                "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4",
+                0x00fdd303c946bdd9,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+                0x3c8123ea7b067637,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4",
+                0x5c3f9b829b279560,
+            ),
+        ];
+
+        for (fen, key) in positions {
+            assert_eq!(
+                key,
+                book::polyglot_key(&Board::from(fen).unwrap()),
+                "{}",
+                fen
+            );
+        }
+
+        let board = Board::from_moves(&["e2e4", "d7d5", "e4e5", "f7f5"]).unwrap();
+        assert_eq!(0x22a48b5a8e47ff78, book::polyglot_key(&board));
+    }
+
+    #[test]
+    fn test_move_encoding() {
+        init();
+
+        let board = Board::from("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let moves = [
+            ("e1g1", "e1h1"),
+            ("e1c1", "e1a1"),
+            ("b7a8q", "b7a8q"),
+            ("b7b8n", "b7b8n"),
+            ("a1a7", "a1a7"),
+        ];
+
+        for (uci, polyglot) in moves {
+            let m = Move::from_string(uci, &board).unwrap();
+            let raw_move = book::encode_move(m);
+            let from = Square::from_string(&polyglot[0..2]).unwrap().to_u16();
+            let to = Square::from_string(&polyglot[2..4]).unwrap().to_u16();
+            assert_eq!(from << 6 | to, raw_move & 0xfff);
+            assert_eq!(Some(m), book::decode_move(raw_move, &board));
+        }
+
+        // A king move to the rook's square is castling only from the king's
+        // starting square.
+        let board = Board::from("4k3/8/8/8/8/8/8/R2K3R w - - 0 1").unwrap();
+        let raw_move = (3 << 6) | 7;
+        assert_eq!(None, book::decode_move(raw_move, &board));
+    }
+
+    #[test]
+    fn test_book_lookup() {
+        init();
+
+        let start = Board::new();
+        let after_e4 = Board::from_moves(&["e2e4"]).unwrap();
+        let e4 = Move::from_string("e2e4", &start).unwrap();
+        let d4 = Move::from_string("d2d4", &start).unwrap();
+        let c5 = Move::from_string("c7c5", &after_e4).unwrap();
+
+        let book = Book::from_entries(vec![
+            BookEntry {
+                key: book::polyglot_key(&after_e4),
+                raw_move: book::encode_move(c5),
+                weight: 1,
+                learn: 0,
+            },
+            BookEntry {
+                key: book::polyglot_key(&start),
+                raw_move: book::encode_move(d4),
+                weight: 0,
+                learn: 0,
+            },
+            BookEntry {
+                key: book::polyglot_key(&start),
+                raw_move: book::encode_move(e4),
+                weight: 5,
+                learn: 0,
+            },
+        ]);
+
+        let book = Book::from_bytes(&book.to_bytes()).unwrap();
+        assert_eq!(3, book.len());
+        assert_eq!(vec![(e4, 5), (d4, 0)], book.moves(&start));
+        assert_eq!(vec![(c5, 1)], book.moves(&after_e4));
+        assert!(book
+            .moves(&Board::from_moves(&["d2d4"]).unwrap())
+            .is_empty());
+
+        let rng = fastrand::Rng::with_seed(7);
+        for _ in 0..20 {
+            assert_eq!(Some(e4), book.pick(&start, &rng));
+        }
+
+        assert!(Book::from_bytes(&[0; 15]).is_err());
+    }
+
+    #[test]
+    fn test_book_builder() {
+        init();
+
+        let games = pgn::parse(
+            r#"[Result "1-0"]
+
+1. e4 e5 2. Nf3 Nc6 1-0
+
+[Result "1/2-1/2"]
+
+1. e4 c5 2. Nf3 1/2-1/2
+
+[Result "0-1"]
+
+1. d4 d5 0-1
+"#,
+        )
+        .unwrap();
+
+        let mut builder = BookBuilder::new(2, 1);
+        for game in &games {
+            builder.add_game(game);
+        }
+        let book = builder.build();
+
+        let start = Board::new();
+        let after_e4 = Board::from_moves(&["e2e4"]).unwrap();
+        let e4 = Move::from_string("e2e4", &start).unwrap();
+        let d4 = Move::from_string("d2d4", &start).unwrap();
+        assert_eq!(vec![(e4, 3), (d4, 0)], book.moves(&start));
+        assert_eq!(2, book.moves(&after_e4).len());
+        assert!(book
+            .moves(&Board::from_moves(&["e2e4", "e7e5"]).unwrap())
+            .is_empty());
+
+        let mut builder = BookBuilder::new(2, 2);
+        for game in &games {
+            builder.add_game(game);
+        }
+        assert_eq!(vec![(e4, 3)], builder.build().moves(&start));
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod datagen_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::Board;
+    use rust_chess_engine::tournament::GameResult;
+    use rust_chess_engine::tuning::datagen::{self, DatagenSettings, Sample, RECORD_SIZE};
+    use rust_chess_engine::tuning::dataset;
+    use std::sync::Mutex;
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    fn init() {
+        START.call_once(|| {
+            movegen::init();
+        });
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        init();
+
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "8/8/8/8/8/8/k7/7K b - - 57 120",
+        ];
+
+        for (fen, result) in fens.iter().zip([
+            GameResult::WhiteWins,
+            GameResult::Draw,
+            GameResult::BlackWins,
+            GameResult::Draw,
+        ]) {
+            let sample = Sample {
+                board: Board::from(fen).unwrap(),
+                score: -123,
+                result,
+            };
+            let record = sample.pack();
+
+            assert_eq!(RECORD_SIZE, record.len());
+            assert_eq!(sample, Sample::unpack(&record).unwrap());
+        }
+
+        assert!(Sample::unpack(&[0; RECORD_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_text_is_a_tuning_position() {
+        init();
+
+        let sample = Sample {
+            board: Board::from("4k3/8/8/8/8/8/4PPPP/4K3 w - - 3 40").unwrap(),
+            score: 250,
+            result: GameResult::WhiteWins,
+        };
+        let text = sample.to_text();
+        assert_eq!("4k3/8/8/8/8/8/4PPPP/4K3 w - - 3 40 250 [1.0]", text);
+
+        let positions = dataset::parse_fens(&text).unwrap();
+        assert_eq!(1, positions.len());
+        assert_eq!(sample.board.to_fen(), positions[0].board.to_fen());
+        assert_eq!(1.0, positions[0].result);
+    }
+
+    #[test]
+    fn test_generate() {
+        init();
+
+        let settings = DatagenSettings {
+            games: 2,
+            threads: 2,
+            nodes: 200,
+            max_moves: 20,
+            hash: 1,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        let reports = Mutex::new(Vec::new());
+        let count = datagen::generate(&settings, &mut output, |progress| {
+            reports.lock().unwrap().push(*progress)
+        })
+        .unwrap();
+
+        assert!(count > 0);
+        assert_eq!(count * RECORD_SIZE, output.len());
+
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(2, reports.len());
+        assert_eq!(2, reports[1].games);
+        assert_eq!(count, reports[1].positions);
+
+        for record in output.chunks(RECORD_SIZE) {
+            let sample = Sample::unpack(record).unwrap();
+            assert!(!sample.board.is_in_check());
+            assert!(sample.board.full_move_number > 4);
+            assert!(sample.board.full_move_number <= 21);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod dtm_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{Board, Color, Piece, Square};
+    use rust_chess_engine::dtm::{self, Dtm, DtmTable, DtmTables, Material};
+    use rust_chess_engine::evaluation::kpk;
+    use std::fs;
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    fn generate(name: &str) -> DtmTables {
+        START.call_once(|| {
+            movegen::init();
+        });
+
+        dtm::generate(&Material::parse(name).unwrap()).unwrap()
+    }
+
+    fn probe(tables: &DtmTables, fen: &str) -> Option<Dtm> {
+        tables.probe(&Board::from(fen).unwrap())
+    }
+
+    #[test]
+    fn test_material() {
+        let material = Material::parse("KRQvKN").unwrap();
+        assert_eq!("KQRvKN", material.name());
+        assert_eq!(5, material.piece_count());
+        assert_eq!(2 * 10 * 64 * 64 * 64 * 64, material.size());
+
+        assert_eq!(2 * 32 * 64 * 64, Material::parse("KvKP").unwrap().size());
+        assert_eq!("KvK", Material::parse("KvK").unwrap().name());
+
+        assert!(Material::parse("KQK").is_err());
+        assert!(Material::parse("QvK").is_err());
+        assert!(Material::parse("KXvK").is_err());
+        assert!(Material::parse("KQRBvKR").is_err());
+
+        let board = Board::from("4k3/8/8/8/8/8/3R4/2Q1K3 w - - 0 1").unwrap();
+        assert_eq!(
+            Some(Material::parse("KQRvK").unwrap()),
+            Material::of(&board)
+        );
+    }
+
+    #[test]
+    fn test_longest_mates() {
+        // The longest mates are known to be 10 moves with a queen and 16
+        // with a rook.
+        let tables = generate("KQvK");
+        let material = Material::parse("KQvK").unwrap();
+        assert_eq!(19, tables.get(&material).unwrap().longest_win());
+        assert_eq!(2, tables.len());
+
+        let tables = generate("KRvK");
+        let material = Material::parse("KRvK").unwrap();
+        assert_eq!(31, tables.get(&material).unwrap().longest_win());
+
+        let tables = generate("KBvK");
+        let material = Material::parse("KBvK").unwrap();
+        assert_eq!(0, tables.get(&material).unwrap().longest_win());
+    }
+
+    #[test]
+    fn test_probe() {
+        let tables = generate("KQvK");
+
+        assert_eq!(
+            Some(Dtm::Win(1)),
+            probe(&tables, "7k/8/6K1/8/8/8/8/1Q6 w - - 0 1")
+        );
+        assert_eq!(
+            Some(Dtm::Loss(0)),
+            probe(&tables, "1Q5k/8/6K1/8/8/8/8/8 b - - 0 1")
+        );
+        // Symmetric positions share their entry.
+        assert_eq!(
+            Some(Dtm::Win(1)),
+            probe(&tables, "k7/8/1K6/8/8/8/8/6Q1 w - - 0 1")
+        );
+        assert_eq!(
+            Some(Dtm::Draw),
+            probe(&tables, "k7/2Q5/1K6/8/8/8/8/8 b - - 0 1")
+        );
+        // The queen is taken.
+        assert_eq!(
+            Some(Dtm::Draw),
+            probe(&tables, "8/8/8/8/8/8/kQ6/4K3 b - - 0 1")
+        );
+
+        // Positions which are not in the table.
+        assert_eq!(None, probe(&tables, "7k/8/6K1/8/8/8/8/Q7 w - - 0 1"));
+        assert_eq!(None, probe(&tables, "6k1/8/8/8/8/8/8/Q3K3 w Q - 0 1"));
+        assert_eq!(None, probe(&tables, "7k/8/6K1/8/8/8/8/RR6 w - - 0 1"));
+    }
+
+    // Generating a table of four pieces takes minutes without optimisations.
+    #[test]
+    #[cfg_attr(debug_assertions, ignore)]
+    fn test_winning_captures() {
+        let tables = generate("KQvKQ");
+
+        // Taking the checking queen is the only move, and wins.
+        let Some(Dtm::Loss(plies)) = probe(&tables, "8/8/8/8/8/2k5/1Q6/K7 b - - 0 1") else {
+            panic!("KQvK after the capture is not lost");
+        };
+        assert_eq!(
+            Some(Dtm::Win(plies + 1)),
+            probe(&tables, "1Q6/8/8/8/8/2k5/1q6/K7 w - - 0 1")
+        );
+        // Either capture of the checking queen wins.
+        assert!(matches!(
+            probe(&tables, "8/8/8/3k4/8/8/1q6/KQ6 w - - 0 1"),
+            Some(Dtm::Win(_))
+        ));
+    }
+
+    #[test]
+    fn test_kpk_bitbase() {
+        let tables = generate("KPvK");
+        let table = tables.get(&Material::parse("KPvK").unwrap()).unwrap();
+
+        // Every legal position is won exactly when the bitbase says so.
+        for pawn in 8..56 {
+            for white_king in 0..64 {
+                for black_king in 0..64 {
+                    if white_king == pawn || black_king == pawn || white_king == black_king {
+                        continue;
+                    }
+                    let [white_king, pawn, black_king] = [white_king, pawn, black_king]
+                        .map(|index: u8| Square::try_from(index).unwrap());
+
+                    for color in [Color::White, Color::Black] {
+                        let mut board = Board::new_empty();
+                        board.add_piece_to_square(Piece::WK, white_king);
+                        board.add_piece_to_square(Piece::WP, pawn);
+                        board.add_piece_to_square(Piece::BK, black_king);
+                        board.color_to_move = color;
+
+                        let Some(dtm) = table.probe(&board) else {
+                            continue;
+                        };
+                        let white_to_move = color == Color::White;
+                        let wins = kpk::probe(white_king, pawn, black_king, white_to_move);
+                        let expected = match dtm {
+                            Dtm::Win(_) => white_to_move,
+                            Dtm::Loss(_) => !white_to_move,
+                            Dtm::Draw => false,
+                        };
+                        assert_eq!(wins, expected, "{}", board.to_fen());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_table_files() {
+        let tables = generate("KRvK");
+        let material = Material::parse("KRvK").unwrap();
+        let table = tables.get(&material).unwrap();
+
+        let bytes = table.to_bytes();
+        assert_eq!(table, &DtmTable::from_bytes(&bytes).unwrap());
+        assert!(DtmTable::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(DtmTable::from_bytes(b"KRvK").is_err());
+
+        let path = std::env::temp_dir().join("rust_chess_engine_dtm");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        let directory = path.to_str().unwrap();
+
+        tables.save(directory).unwrap();
+        fs::write(path.join("README.txt"), "").unwrap();
+        let loaded = DtmTables::load(directory).unwrap();
+        assert_eq!(tables, loaded);
+        assert_eq!(
+            Some(Dtm::Loss(4)),
+            probe(&loaded, "8/8/8/8/8/8/8/k1K4R b - - 0 1")
+        );
+
+        fs::write(path.join("KQvK.dtm"), "").unwrap();
+        assert!(DtmTables::load(directory).is_err());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod endgame_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{square, Board};
+    use rust_chess_engine::evaluation::endgame::{self, KNOWN_WIN};
+    use rust_chess_engine::evaluation::{self, kpk, PawnTable};
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    fn evaluate(fen: &str) -> i32 {
+        START.call_once(|| {
+            movegen::init();
+        });
+
+        evaluation::evaluate(&Board::from(fen).unwrap(), &mut PawnTable::new(16))
+    }
+
+    #[test]
+    fn test_kpk_bitbase() {
+        START.call_once(|| {
+            movegen::init();
+        });
+
+        // The king in front of its pawn on the sixth rank wins with either
+        // side to move.
+        assert!(kpk::probe(square::E6, square::E5, square::E8, true));
+        assert!(kpk::probe(square::E6, square::E5, square::E8, false));
+        // Mirrored to the other wing.
+        assert!(kpk::probe(square::D6, square::D5, square::D8, true));
+
+        // The defending king in the corner holds against a rook pawn.
+        assert!(!kpk::probe(square::A1, square::A2, square::A8, true));
+        assert!(!kpk::probe(square::H1, square::H2, square::H8, false));
+
+        // The pawn runs when the king is outside its square.
+        assert!(kpk::probe(square::A1, square::A2, square::H8, true));
+
+        // Positions with the side not to move in check are never won.
+        assert!(!kpk::probe(square::E6, square::D7, square::E8, true));
+    }
+
+    #[test]
+    fn test_kpk_evaluation() {
+        assert!(evaluate("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1") > KNOWN_WIN);
+        assert!(evaluate("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1") < -KNOWN_WIN);
+        assert!(evaluate("8/8/8/8/4p3/4k3/8/4K3 b - - 0 1") > KNOWN_WIN);
+        assert_eq!(0, evaluate("k7/8/8/8/8/8/P7/K7 w - - 0 1"));
+        assert_eq!(0, evaluate("k7/p7/8/8/8/8/8/K7 b - - 0 1"));
+    }
+
+    #[test]
+    fn test_mating_material() {
+        assert!(evaluate("8/8/3k4/8/8/8/8/QK6 w - - 0 1") > KNOWN_WIN);
+        assert!(evaluate("8/8/3k4/8/8/8/8/RK6 b - - 0 1") < -KNOWN_WIN);
+        assert!(evaluate("8/8/3K4/8/8/8/8/qk6 w - - 0 1") < -KNOWN_WIN);
+
+        // The weak king is driven to the edge.
+        assert!(
+            evaluate("8/8/7k/8/8/4K3/8/Q7 w - - 0 1") > evaluate("8/8/4k3/8/8/4K3/8/Q7 w - - 0 1")
+        );
+
+        // A stalemate is not a win.
+        assert_eq!(0, evaluate("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1"));
+    }
+
+    #[test]
+    fn test_kbnk() {
+        // With a dark squared bishop the king is mated in a1 or h8.
+        let dark_corner = evaluate("7k/8/8/4K3/8/8/8/2B1N3 w - - 0 1");
+        let light_corner = evaluate("k7/8/8/4K3/8/8/8/2B1N3 w - - 0 1");
+
+        assert!(light_corner > KNOWN_WIN);
+        assert!(dark_corner > light_corner);
+    }
+
+    #[test]
+    fn test_known_draws() {
+        assert_eq!(0, evaluate("4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1"));
+        assert_eq!(0, evaluate("k7/8/8/8/8/8/P7/K1B5 w - - 0 1"));
+
+        // Bishops on squares of one colour cannot mate, unlike a pair.
+        assert_eq!(0, evaluate("4k3/8/8/8/8/8/8/2B1K1B1 w - - 0 1"));
+        assert!(evaluate("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1") > KNOWN_WIN);
+
+        // The bishop of the right colour wins.
+        let board = Board::from("k7/8/8/8/8/8/P7/KB6 w - - 0 1").unwrap();
+        assert_eq!(None, endgame::evaluate(&board));
+        assert!(evaluate("k7/8/8/8/8/8/P7/KB6 w - - 0 1") > 0);
+    }
+}
@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod evaluation_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{square, BitBoard, Board, Color};
+    use rust_chess_engine::evaluation::{self, pawns, PawnTable, Term, DEFAULT_PARAMS};
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    fn breakdown(fen: &str) -> evaluation::Breakdown {
+        START.call_once(|| {
+            movegen::init();
+        });
+
+        evaluation::breakdown(&Board::from(fen).unwrap(), &mut PawnTable::new(16))
+    }
+
+    // Flips the board vertically and swaps the colors of all pieces.
+    fn mirror_fen(fen: &str) -> String {
+        let fields: Vec<&str> = fen.split(' ').collect();
+        let swap_case = |s: &str| -> String {
+            s.chars()
+                .map(|c| {
+                    if c.is_ascii_uppercase() {
+                        c.to_ascii_lowercase()
+                    } else {
+                        c.to_ascii_uppercase()
+                    }
+                })
+                .collect()
+        };
+        let ranks: Vec<&str> = fields[0].split('/').rev().collect();
+        let color = if fields[1] == "w" { "b" } else { "w" };
+        let en_passant = match fields[3] {
+            "-" => "-".to_string(),
+            ep => format!("{}{}", &ep[..1], 9 - ep[1..].parse::<u8>().unwrap()),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            swap_case(&ranks.join("/")),
+            color,
+            swap_case(fields[2]),
+            en_passant,
+            fields[4],
+            fields[5]
+        )
+    }
+
+    fn pawn_score(fen: &str) -> i32 {
+        pawns::evaluate(
+            &Board::from(fen).unwrap(),
+            &mut PawnTable::new(16),
+            &DEFAULT_PARAMS,
+        )
+    }
+
+    #[test]
+    fn test_evaluate_start_position() {
+        START.call_once(|| {
+            movegen::init();
+        });
+        let board = Board::new();
+        let mut pawn_table = PawnTable::default();
+
+        assert_eq!(0, pawns::evaluate(&board, &mut pawn_table, &DEFAULT_PARAMS));
+        assert_eq!(0, evaluation::evaluate(&board, &mut pawn_table));
+    }
+
+    #[test]
+    fn test_passed_pawns() {
+        let board = Board::from("4k3/8/7p/3P4/8/2p5/1P6/4K3 w - - 0 1").unwrap();
+        let entry = PawnTable::default().probe(&board, &DEFAULT_PARAMS);
+
+        assert_eq!(BitBoard::new(square::D5), entry.passed[0]);
+        assert_eq!(BitBoard::new(square::H6), entry.passed[1]);
+    }
+
+    #[test]
+    fn test_pawn_weaknesses() {
+        // Doubled, isolated pawns against a connected chain.
+        assert!(pawn_score("4k3/1ppp4/8/8/8/2P5/2P5/4K3 w - - 0 1") < 0);
+        // The same material with healthy pawns is balanced.
+        assert_eq!(0, pawn_score("4k3/2pp4/8/8/8/8/2PP4/4K3 w - - 0 1"));
+    }
+
+    #[test]
+    fn test_passed_pawn_rank_and_path() {
+        let advanced = pawn_score("4k3/8/P7/8/8/8/8/4K3 w - - 0 1");
+        let behind = pawn_score("4k3/8/8/8/P7/8/8/4K3 w - - 0 1");
+        let blocked = pawn_score("4k3/n7/P7/8/8/8/8/4K3 w - - 0 1");
+
+        assert!(advanced > behind);
+        assert!(advanced > blocked);
+        assert!(blocked > 0);
+    }
+
+    #[test]
+    fn test_pawn_table() {
+        let board = Board::from("4k3/n7/P7/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mut pawn_table = PawnTable::new(16);
+
+        let entry = pawn_table.probe(&board, &DEFAULT_PARAMS);
+        assert_eq!(board.pawn_hash, entry.key);
+        assert_eq!(entry, pawn_table.probe(&board, &DEFAULT_PARAMS));
+
+        // Only the pawns matter, so the entry is shared with other positions.
+        let free = Board::from("4k3/8/P7/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(entry, pawn_table.probe(&free, &DEFAULT_PARAMS));
+    }
+
+    #[test]
+    fn test_breakdown_symmetry() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r1bq1rk1/pp3ppp/2n1pn2/2bp4/2P5/2N1PN2/PPQ1BPPP/R1B2RK1 b - - 0 8",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ] {
+            let original = breakdown(fen);
+            let mirrored = breakdown(&mirror_fen(fen));
+
+            for term in Term::ALL {
+                assert_eq!(
+                    original.get(term, Color::White),
+                    mirrored.get(term, Color::Black),
+                    "{} {}",
+                    term.as_string(),
+                    fen
+                );
+                assert_eq!(
+                    original.get(term, Color::Black),
+                    mirrored.get(term, Color::White),
+                    "{} {}",
+                    term.as_string(),
+                    fen
+                );
+            }
+            assert_eq!(original.total(), -mirrored.total());
+        }
+    }
+
+    #[test]
+    fn test_piece_terms() {
+        let bishops = breakdown("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1");
+        assert!(bishops.get(Term::BishopPair, Color::White) > 0);
+        assert_eq!(0, bishops.get(Term::BishopPair, Color::Black));
+
+        let open = breakdown("4k3/p7/8/8/8/8/P7/3RK3 w - - 0 1");
+        let closed = breakdown("4k3/p7/8/8/8/8/P7/R3K3 w - - 0 1");
+        let seventh = breakdown("4k3/p2R4/8/8/8/8/P7/4K3 w - - 0 1");
+        assert!(open.get(Term::Rooks, Color::White) > closed.get(Term::Rooks, Color::White));
+        assert!(seventh.get(Term::Rooks, Color::White) > open.get(Term::Rooks, Color::White));
+
+        let outpost = breakdown("4k3/pp6/8/3N4/4P3/8/8/4K3 w - - 0 1");
+        let exposed = breakdown("4k3/pp2p3/8/3N4/4P3/8/8/4K3 w - - 0 1");
+        assert!(outpost.get(Term::Outposts, Color::White) > 0);
+        assert_eq!(0, exposed.get(Term::Outposts, Color::White));
+    }
+
+    #[test]
+    fn test_king_safety() {
+        let sheltered = breakdown("4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1");
+        let exposed = breakdown("4k3/8/8/8/8/8/8/6K1 w - - 0 1");
+        assert!(
+            sheltered.get(Term::KingSafety, Color::White)
+                > exposed.get(Term::KingSafety, Color::White)
+        );
+
+        let quiet = breakdown("6k1/5ppp/8/8/8/8/8/3QR1K1 w - - 0 1");
+        let attacked = breakdown("6k1/5ppp/8/6N1/7Q/8/8/6K1 w - - 0 1");
+        assert!(
+            attacked.get(Term::KingSafety, Color::Black)
+                < quiet.get(Term::KingSafety, Color::Black)
+        );
+    }
+
+    #[test]
+    fn test_mobility() {
+        let centralised = breakdown("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        let cornered = breakdown("4k3/8/8/8/8/8/8/N3K3 w - - 0 1");
+
+        assert!(
+            centralised.get(Term::Mobility, Color::White)
+                > cornered.get(Term::Mobility, Color::White)
+        );
+    }
+}
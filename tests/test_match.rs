@@ -0,0 +1,343 @@
+#[cfg(test)]
+mod match_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{pgn, Board, Move};
+    use rust_chess_engine::tournament::{
+        self, EngineSpec, GameResult, MatchSettings, Score, Sprt, SprtResult, TimeControl,
+    };
+    use std::sync::Mutex;
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    fn init() {
+        START.call_once(|| {
+            movegen::init();
+        });
+    }
+
+    fn play(board: &mut Board, moves: &[&str]) -> Vec<Move> {
+        moves
+            .iter()
+            .map(|m| {
+                let m = Move::from_string(m, board).unwrap();
+                board.make_move(&m);
+                m
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_elo() {
+        let even = Score {
+            wins: 10,
+            draws: 20,
+            losses: 10,
+        };
+        let (elo, margin) = even.elo();
+        assert!(elo.abs() < 1e-9);
+        assert!(margin > 0.0 && margin.is_finite());
+
+        let better = Score {
+            wins: 30,
+            draws: 20,
+            losses: 10,
+        };
+        let (elo, _) = better.elo();
+        assert!((elo - 120.4).abs() < 0.1);
+
+        let (_, margin) = Score {
+            wins: 300,
+            draws: 200,
+            losses: 100,
+        }
+        .elo();
+        let (_, small_margin) = better.elo();
+        assert!(margin < small_margin);
+    }
+
+    #[test]
+    fn test_sprt() {
+        let sprt = Sprt {
+            elo0: 0.0,
+            elo1: 10.0,
+            alpha: 0.05,
+            beta: 0.05,
+        };
+        let (lower, upper) = sprt.bounds();
+        assert!((lower + 2.944).abs() < 0.001);
+        assert!((upper - 2.944).abs() < 0.001);
+
+        assert_eq!(sprt.test(&Score::default()), SprtResult::Continue);
+
+        let winning = Score {
+            wins: 1000,
+            draws: 1000,
+            losses: 700,
+        };
+        assert!(winning.llr(0.0, 10.0) > 0.0);
+        assert_eq!(sprt.test(&winning), SprtResult::AcceptH1);
+
+        let losing = Score {
+            wins: 700,
+            draws: 1000,
+            losses: 1000,
+        };
+        assert!(losing.llr(0.0, 10.0) < 0.0);
+        assert_eq!(sprt.test(&losing), SprtResult::AcceptH0);
+    }
+
+    #[test]
+    fn test_time_control() {
+        assert_eq!(
+            TimeControl::parse("10+0.1"),
+            Ok(TimeControl::Clock {
+                time: 10000,
+                increment: 100
+            })
+        );
+        assert_eq!(
+            TimeControl::parse("60"),
+            Ok(TimeControl::Clock {
+                time: 60000,
+                increment: 0
+            })
+        );
+        assert_eq!(
+            TimeControl::parse("movetime=50"),
+            Ok(TimeControl::MoveTime(50))
+        );
+        assert_eq!(
+            TimeControl::parse("nodes=5000"),
+            Ok(TimeControl::Nodes(5000))
+        );
+        assert_eq!(TimeControl::parse("depth=3"), Ok(TimeControl::Depth(3)));
+        assert!(TimeControl::parse("depth=x").is_err());
+        assert!(TimeControl::parse("-1+0").is_err());
+        assert!(TimeControl::parse("time=10").is_err());
+    }
+
+    #[test]
+    fn test_engine_spec() {
+        let spec = EngineSpec::parse("internal,Hash=64,name=base").unwrap();
+        assert_eq!(spec.name, "base");
+        assert_eq!(spec.command, None);
+        assert_eq!(spec.options, vec![("Hash".to_string(), "64".to_string())]);
+
+        let spec = EngineSpec::parse("/usr/bin/engine,Threads=2").unwrap();
+        assert_eq!(spec.name, "engine");
+        assert_eq!(spec.command.as_deref(), Some("/usr/bin/engine"));
+
+        assert!(EngineSpec::parse("internal,Hash").is_err());
+        assert!(EngineSpec::parse("").is_err());
+    }
+
+    #[test]
+    fn test_to_san() {
+        init();
+
+        let mut board = Board::new();
+        let moves = play(
+            &mut board,
+            &["e2e4", "d7d5", "e4d5", "g8f6", "f1b5", "c7c6", "g1f3"],
+        );
+        let mut replay = Board::new();
+        let san: Vec<String> = moves
+            .iter()
+            .map(|m| {
+                let san = m.to_san(&replay);
+                replay.make_move(m);
+                san
+            })
+            .collect();
+        assert_eq!(san, ["e4", "d5", "exd5", "Nf6", "Bb5+", "c6", "Nf3"]);
+
+        let board = Board::from("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert_eq!(
+            Move::from_string("e1g1", &board).unwrap().to_san(&board),
+            "O-O"
+        );
+        assert_eq!(
+            Move::from_string("a1a8", &board).unwrap().to_san(&board),
+            "Ra8+"
+        );
+
+        let board = Board::from("6k1/5ppp/8/8/8/8/8/R4RK1 w - - 0 1").unwrap();
+        assert_eq!(
+            Move::from_string("a1e1", &board).unwrap().to_san(&board),
+            "Rae1"
+        );
+        assert_eq!(
+            Move::from_string("a1a8", &board).unwrap().to_san(&board),
+            "Ra8#"
+        );
+
+        let board = Board::from("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            Move::from_string("b7b8q", &board).unwrap().to_san(&board),
+            "b8=Q+"
+        );
+    }
+
+    #[test]
+    fn test_pgn_round_trip() {
+        init();
+
+        let start = Board::from("4k3/8/8/8/8/8/4P3/R3K3 w Q - 0 1").unwrap();
+        let mut board = start.clone();
+        let mut game = pgn::PgnGame::new(start);
+        game.moves = play(&mut board, &["e2e4", "e8d7", "e1c1", "d7e6", "d1d6"]);
+        game.set_tag("Event", "Test");
+        game.set_tag("Result", "1/2-1/2");
+
+        let text = game.to_pgn();
+        assert!(text.contains("[FEN \"4k3/8/8/8/8/8/4P3/R3K3 w Q - 0 1\"]"));
+        assert!(text.contains("1. e4 Kd7 2. O-O-O+ Ke6 3. Rd6+ 1/2-1/2"));
+
+        let games = pgn::parse(&text).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].moves, game.moves);
+        assert_eq!(games[0].tag("Event"), Some("Test"));
+        assert_eq!(games[0].result(), "1/2-1/2");
+        assert_eq!(games[0].end().to_fen(), board.to_fen());
+    }
+
+    #[test]
+    fn test_move_counters() {
+        init();
+
+        let mut board = Board::new();
+        let moves = play(&mut board, &["g1f3", "g8f6", "f3g1"]);
+        assert_eq!(board.half_move_clock, 3);
+        assert_eq!(board.full_move_number, 2);
+
+        let capture = play(&mut board, &["e7e5", "d2d4", "e5d4"]);
+        assert_eq!(board.half_move_clock, 0);
+        assert_eq!(board.full_move_number, 4);
+
+        for m in capture.iter().rev() {
+            board.undo_move(m);
+        }
+        assert_eq!(board.half_move_clock, 3);
+        assert_eq!(board.full_move_number, 2);
+
+        for m in moves.iter().rev() {
+            board.undo_move(m);
+        }
+        assert_eq!(board.to_fen(), Board::new().to_fen());
+    }
+
+    #[test]
+    fn test_repetitions() {
+        init();
+
+        let mut board = Board::new();
+        assert_eq!(board.repetitions(), 0);
+
+        play(&mut board, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+        assert_eq!(board.repetitions(), 1);
+
+        play(&mut board, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+        assert_eq!(board.repetitions(), 2);
+
+        // A pawn move resets the clock, so earlier positions cannot repeat.
+        play(&mut board, &["e2e4"]);
+        assert_eq!(board.repetitions(), 0);
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        let drawn = [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4KN2 w - - 0 1",
+            "4k3/8/8/8/8/8/8/3BK3 b - - 0 1",
+        ];
+        let not_drawn = [
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/3RK3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2NBK3 w - - 0 1",
+        ];
+
+        for fen in drawn {
+            assert!(
+                Board::from(fen).unwrap().is_insufficient_material(),
+                "{}",
+                fen
+            );
+        }
+        for fen in not_drawn {
+            assert!(
+                !Board::from(fen).unwrap().is_insufficient_material(),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_internal_match() {
+        init();
+
+        let path = std::env::temp_dir().join("test_internal_match.pgn");
+        let path = path.to_str().unwrap().to_string();
+        let mut settings = MatchSettings::new(
+            [
+                EngineSpec::parse("internal,Hash=1").unwrap(),
+                EngineSpec::parse("internal,Hash=1").unwrap(),
+            ],
+            TimeControl::Depth(1),
+        );
+        settings.adjudication.max_moves = Some(20);
+        settings.pgn_path = Some(path.clone());
+
+        let reported = Mutex::new(Vec::new());
+        let score = tournament::run(&settings, |game| {
+            reported
+                .lock()
+                .unwrap()
+                .push((game.number, game.first_is_white));
+        })
+        .unwrap();
+        assert_eq!(score.games(), 2);
+
+        let mut reported = reported.into_inner().unwrap();
+        reported.sort();
+        assert_eq!(reported, [(1, true), (2, false)]);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let games = pgn::parse(&content).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("White"), Some("internal-1"));
+        assert_eq!(games[1].tag("White"), Some("internal-2"));
+
+        for game in &games {
+            assert!(!game.moves.is_empty());
+            assert!(["1-0", "0-1", "1/2-1/2"].contains(&game.result()));
+        }
+
+        // Both engines search identically, so the colours decide the games.
+        assert_eq!(games[0].moves, games[1].moves);
+        if games[0].result() != GameResult::Draw.as_string() {
+            assert_eq!((score.wins, score.losses), (1, 1));
+        }
+    }
+
+    #[test]
+    fn test_uci_engine() {
+        init();
+
+        let engine = env!("CARGO_BIN_EXE_rust_chess_engine");
+        let mut settings = MatchSettings::new(
+            [
+                EngineSpec::parse("internal").unwrap(),
+                EngineSpec::parse(&format!("{},name=uci", engine)).unwrap(),
+            ],
+            TimeControl::Depth(1),
+        );
+        settings.adjudication.max_moves = Some(10);
+
+        let score = tournament::run(&settings, |_| ()).unwrap();
+        assert_eq!(score.games(), 2);
+    }
+}
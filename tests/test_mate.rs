@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod mate_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{Board, Move};
+    use rust_chess_engine::search::{
+        self, SearchLimits, SearchOptions, TimeManager, TranspositionTable, MATE_SCORE,
+    };
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    const MATE_SUITE: &str = include_str!("data/mate.epd");
+
+    fn solve(board: &Board, mate: u8) -> search::SearchResult {
+        START.call_once(|| {
+            movegen::init();
+        });
+
+        let limits = SearchLimits {
+            mate: Some(mate),
+            ..Default::default()
+        };
+        let time_manager = TimeManager::new(&limits, board.color_to_move);
+        let tt = TranspositionTable::new(1);
+        let stop = AtomicBool::new(false);
+
+        search::run(
+            board,
+            &limits,
+            &SearchOptions::default(),
+            &time_manager,
+            &tt,
+            &stop,
+        )
+    }
+
+    fn parse_epd(line: &str) -> (Board, u8) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let fen = format!("{} 0 1", fields[..4].join(" "));
+        let dm_index = fields.iter().position(|f| *f == "dm").unwrap();
+        let dm = fields[dm_index + 1].trim_end_matches(';').parse().unwrap();
+
+        (Board::from(fen.as_str()).unwrap(), dm)
+    }
+
+    fn is_checkmate(board: &mut Board) -> bool {
+        let mut moves = [Move(0); 218];
+        let moves_count = board.get_moves(&mut moves);
+        let color = board.color_to_move;
+
+        board.is_in_check()
+            && moves.iter().take(moves_count).all(|m| {
+                board.make_move(m);
+                let is_legal = !board.is_king_in_check(color);
+                board.undo_move(m);
+                !is_legal
+            })
+    }
+
+    #[test]
+    fn test_mate_suite() {
+        for line in MATE_SUITE.lines().filter(|line| !line.trim().is_empty()) {
+            let (mut board, dm) = parse_epd(line);
+            let result = solve(&board, dm);
+
+            assert_eq!(1, result.lines.len(), "{}", line);
+            assert_eq!(MATE_SCORE - (2 * dm as i32 - 1), result.score, "{}", line);
+
+            let pv = &result.lines[0].pv;
+            assert_eq!(2 * dm as usize - 1, pv.len(), "{}", line);
+            for m in pv {
+                board.make_move(m);
+            }
+            assert!(is_checkmate(&mut board), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_mate_shortest() {
+        let (board, dm) = parse_epd(MATE_SUITE.lines().nth(7).unwrap());
+        let result = solve(&board, dm + 2);
+
+        assert_eq!(MATE_SCORE - (2 * dm as i32 - 1), result.score);
+    }
+
+    #[test]
+    fn test_mate_not_found() {
+        let board =
+            Board::from("rn3rk1/pbppq1pp/1p2pb2/4N2Q/3PN3/3B4/PPP2PPP/R3K2R w KQ - 0 1").unwrap();
+        let result = solve(&board, 2);
+
+        assert!(result.lines.is_empty());
+        assert_eq!(0, result.score);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_format_score() {
+        assert_eq!("cp 35", search::format_score(35));
+        assert_eq!("cp -120", search::format_score(-120));
+        assert_eq!("mate 1", search::format_score(MATE_SCORE - 1));
+        assert_eq!("mate 3", search::format_score(MATE_SCORE - 5));
+        assert_eq!("mate -1", search::format_score(-MATE_SCORE + 2));
+        assert_eq!("mate -2", search::format_score(-MATE_SCORE + 4));
+    }
+}
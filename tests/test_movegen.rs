@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod movegen_tests {
     use rust_chess_engine::core::movegen::*;
-    use rust_chess_engine::core::{square, BitBoard};
+    use rust_chess_engine::core::{square, BitBoard, Color};
 
     #[test]
     fn test_get_non_sliding_attacks() {
@@ -47,4 +47,84 @@ mod movegen_tests {
             get_queen_attacks(square::A2, BitBoard(0x0008001504002200))
         );
     }
+
+    #[test]
+    fn test_get_pawn_attacks() {
+        init_non_sliding_attacks();
+
+        assert_eq!(
+            BitBoard::from_squares(&[square::C5, square::E5]),
+            get_pawn_attacks(square::D4, Color::White)
+        );
+        assert_eq!(
+            BitBoard::from_squares(&[square::C3, square::E3]),
+            get_pawn_attacks(square::D4, Color::Black)
+        );
+        assert_eq!(
+            BitBoard::new(square::B3),
+            get_pawn_attacks(square::A2, Color::White)
+        );
+        assert_eq!(
+            BitBoard::new(square::G1),
+            get_pawn_attacks(square::H2, Color::Black)
+        );
+    }
+
+    #[test]
+    fn test_get_xray_attacks() {
+        init_magic();
+
+        // The rook sees through the piece on d6 to d8, and not through the
+        // one on f4, which is not a blocker.
+        let occupancy = BitBoard::from_squares(&[square::D6, square::D8, square::F4]);
+        assert_eq!(
+            BitBoard::from_squares(&[square::D7, square::D8]),
+            get_rook_xray_attacks(square::D4, occupancy, BitBoard::new(square::D6))
+        );
+        assert_eq!(
+            BitBoard::from_squares(&[square::D7, square::D8, square::G4, square::H4]),
+            get_rook_xray_attacks(square::D4, occupancy, occupancy)
+        );
+
+        let occupancy = BitBoard::from_squares(&[square::B2, square::F6]);
+        assert_eq!(
+            BitBoard::new(square::A1),
+            get_bishop_xray_attacks(square::D4, occupancy, BitBoard::new(square::B2))
+        );
+    }
+
+    #[test]
+    fn test_lines() {
+        init();
+
+        assert_eq!(
+            BitBoard::from_squares(&[square::D5, square::D6, square::D7, square::D8]),
+            ray(square::D4, Direction::North)
+        );
+        assert_eq!(
+            BitBoard::from_squares(&[square::C3, square::B2, square::A1]),
+            ray(square::D4, Direction::SouthWest)
+        );
+        assert_eq!(BitBoard::EMPTY, ray(square::H4, Direction::East));
+
+        assert_eq!(
+            BitBoard::from_squares(&[square::C3, square::D4, square::E5]),
+            between(square::B2, square::F6)
+        );
+        assert_eq!(
+            between(square::B2, square::F6),
+            between(square::F6, square::B2)
+        );
+        assert_eq!(BitBoard::EMPTY, between(square::E1, square::E2));
+        assert_eq!(BitBoard::EMPTY, between(square::B1, square::C3));
+
+        assert_eq!(BitBoard::FILE_E, line(square::E2, square::E7));
+        assert_eq!(BitBoard::RANK_3, line(square::H3, square::B3));
+        assert_eq!(
+            BitBoard(0x8040_2010_0804_0201),
+            line(square::C3, square::F6)
+        );
+        assert_eq!(BitBoard::EMPTY, line(square::B1, square::C3));
+        assert_eq!(BitBoard::EMPTY, line(square::C3, square::C3));
+    }
 }
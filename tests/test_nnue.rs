@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod nnue_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{Board, Color, Move};
+    use rust_chess_engine::evaluation::{self, nnue::Accumulators, nnue::Network, PawnTable};
+    use std::iter::repeat_n;
+    use std::sync::{Arc, Once};
+
+    static START: Once = Once::new();
+
+    const HIDDEN: usize = 40;
+
+    fn random_network_bytes(hidden: usize) -> Vec<u8> {
+        let rng = fastrand::Rng::with_seed(0x5eed);
+
+        (0..hidden * 771 + 1)
+            .flat_map(|_| rng.i16(-64..64).to_le_bytes())
+            .collect()
+    }
+
+    fn random_network() -> Arc<Network> {
+        START.call_once(|| {
+            movegen::init();
+        });
+
+        Arc::new(Network::from_bytes(&random_network_bytes(HIDDEN)).unwrap())
+    }
+
+    fn assert_incremental(board: &mut Board, network: &Arc<Network>, depth: u8) {
+        assert_eq!(
+            Some(Accumulators::new(Arc::clone(network), board)),
+            board.nnue
+        );
+        if depth == 0 {
+            return;
+        }
+
+        let mut moves = [Move(0); 218];
+        let moves_count = board.get_moves(&mut moves);
+
+        for m in moves.iter().take(moves_count) {
+            board.make_move(m);
+            assert_incremental(board, network, depth - 1);
+            board.undo_move(m);
+        }
+    }
+
+    #[test]
+    fn test_network_file_size() {
+        assert_eq!(HIDDEN, random_network().hidden_size());
+        assert!(Network::from_bytes(&random_network_bytes(HIDDEN)[1..]).is_err());
+        assert!(Network::from_bytes(&[0; 2]).is_err());
+        assert!(Network::load("no/such/network.bin").is_err());
+    }
+
+    #[test]
+    fn test_network_load() {
+        let path = std::env::temp_dir().join("rust_chess_engine_test.nnue");
+        std::fs::write(&path, random_network_bytes(16)).unwrap();
+
+        let network = Network::load(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(16, network.hidden_size());
+    }
+
+    #[test]
+    fn test_accumulators_incremental() {
+        let network = random_network();
+
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        ] {
+            let mut board = Board::from(fen).unwrap();
+            board.set_network(Some(Arc::clone(&network)));
+            let original = board.clone();
+
+            assert_incremental(&mut board, &network, 2);
+            assert_eq!(original, board);
+        }
+    }
+
+    #[test]
+    fn test_large_hidden_layer() {
+        START.call_once(|| {
+            movegen::init();
+        });
+
+        // Every neuron is at its clipping value with the largest output
+        // weight, which overflows 32-bit sums of the whole layer.
+        const LARGE_HIDDEN: usize = 4096;
+        let values = repeat_n(0, 768 * LARGE_HIDDEN)
+            .chain(repeat_n(255, LARGE_HIDDEN))
+            .chain(repeat_n(i16::MAX, 2 * LARGE_HIDDEN))
+            .chain([0]);
+        let bytes: Vec<u8> = values.flat_map(|value: i16| value.to_le_bytes()).collect();
+        let network = Arc::new(Network::from_bytes(&bytes).unwrap());
+
+        let board = Board::new();
+        let accumulators = Accumulators::new(network, &board);
+        let sum = 2 * LARGE_HIDDEN as i64 * 255 * i16::MAX as i64;
+        assert_eq!(
+            (sum * 400 / (255 * 64)) as i32,
+            accumulators.evaluate(Color::White)
+        );
+    }
+
+    #[test]
+    fn test_evaluation_symmetry() {
+        let network = random_network();
+        let mut board =
+            Board::from("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .unwrap();
+        let mut mirrored =
+            Board::from("rnbqk2r/pppp1ppp/5n2/2b1p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R b KQkq - 4 4")
+                .unwrap();
+        board.set_network(Some(Arc::clone(&network)));
+        mirrored.set_network(Some(Arc::clone(&network)));
+
+        let mut pawn_table = PawnTable::default();
+        let nnue = board.nnue.as_ref().unwrap();
+        assert_eq!(
+            evaluation::evaluate(&board, &mut pawn_table),
+            nnue.evaluate(Color::White)
+        );
+        assert_eq!(
+            evaluation::evaluate(&board, &mut pawn_table),
+            evaluation::evaluate(&mirrored, &mut pawn_table)
+        );
+
+        board.set_network(None);
+        assert_eq!(None, board.nnue);
+    }
+}
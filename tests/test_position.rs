@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod position_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{Board, Move, Position};
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    // Plays every move up to `depth` plies on the board and the position,
+    // which must stay the same.
+    fn check_moves(board: &mut Board, depth: usize) {
+        let position = Position::from_board(board);
+        let mut moves = [Move(0); 218];
+        let moves_count = board.get_moves(&mut moves);
+
+        for m in &moves[..moves_count] {
+            board.make_move(m);
+
+            let next = position.make_move(m);
+            assert_eq!(Position::from_board(board), next, "{}", m.as_string());
+            assert_eq!(board.compute_hash(), next.hash);
+            assert_eq!(board.compute_pawn_hash(), next.pawn_hash);
+
+            let mut made = position;
+            let undo = made.make(m);
+            assert_eq!(next, made);
+            made.unmake(m, &undo);
+            assert_eq!(position, made);
+
+            if depth > 1 {
+                check_moves(board, depth - 1);
+            }
+            board.undo_move(m);
+        }
+    }
+
+    #[test]
+    fn test_position_moves() {
+        START.call_once(|| {
+            movegen::init();
+        });
+
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+
+        for fen in fens {
+            let mut board = Board::from(fen).unwrap();
+            check_moves(&mut board, 3);
+        }
+    }
+
+    #[test]
+    fn test_position_board() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let board = Board::from(fen).unwrap();
+        let position = Position::from_board(&board);
+
+        assert_eq!(board, position.to_board());
+        assert_eq!(fen, position.to_board().to_fen());
+    }
+}
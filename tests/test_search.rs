@@ -0,0 +1,352 @@
+#[cfg(test)]
+mod search_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{Board, Color, Move};
+    use rust_chess_engine::search::tt::{Bound, Entry};
+    use rust_chess_engine::search::{
+        self, SearchLimits, SearchOptions, TimeManager, TranspositionTable, MATE_SCORE,
+    };
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Once;
+    use std::time::Duration;
+
+    static START: Once = Once::new();
+
+    fn search(fen: &str, limits: SearchLimits) -> search::SearchResult {
+        search_with_threads(fen, limits, 1)
+    }
+
+    fn search_with_threads(
+        fen: &str,
+        limits: SearchLimits,
+        threads: usize,
+    ) -> search::SearchResult {
+        search_with_options(
+            fen,
+            limits,
+            SearchOptions {
+                threads,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn search_with_options(
+        fen: &str,
+        limits: SearchLimits,
+        options: SearchOptions,
+    ) -> search::SearchResult {
+        START.call_once(|| {
+            movegen::init();
+        });
+
+        search_board(&Board::from(fen).unwrap(), limits, options)
+    }
+
+    fn search_board(
+        board: &Board,
+        limits: SearchLimits,
+        options: SearchOptions,
+    ) -> search::SearchResult {
+        let time_manager = TimeManager::new(&limits, board.color_to_move);
+        let tt = TranspositionTable::new(1);
+        let stop = AtomicBool::new(false);
+
+        search::run(board, &limits, &options, &time_manager, &tt, &stop)
+    }
+
+    #[test]
+    fn test_time_manager_move_time() {
+        let limits = SearchLimits {
+            move_time: Some(1000),
+            ..Default::default()
+        };
+        let time_manager = TimeManager::new(&limits, Color::White);
+
+        assert_eq!(Some(Duration::from_millis(970)), time_manager.soft_limit());
+        assert_eq!(Some(Duration::from_millis(970)), time_manager.hard_limit());
+    }
+
+    #[test]
+    fn test_time_manager_clock() {
+        let limits = SearchLimits {
+            white_time: Some(60_000),
+            black_time: Some(1_000),
+            white_increment: Some(1_000),
+            moves_to_go: Some(20),
+            ..Default::default()
+        };
+        let white = TimeManager::new(&limits, Color::White);
+        let black = TimeManager::new(&limits, Color::Black);
+
+        let white_soft = white.soft_limit().unwrap();
+        let white_hard = white.hard_limit().unwrap();
+        assert!(white_soft < white_hard);
+        assert!(white_hard < Duration::from_millis(60_000));
+        assert!(black.hard_limit().unwrap() < Duration::from_millis(1_000));
+        assert!(black.soft_limit().unwrap() < white_soft);
+    }
+
+    #[test]
+    fn test_time_manager_infinite() {
+        let limits = SearchLimits {
+            white_time: Some(1),
+            infinite: true,
+            ..Default::default()
+        };
+        let time_manager = TimeManager::new(&limits, Color::White);
+
+        assert_eq!(None, time_manager.hard_limit());
+        assert!(!time_manager.hard_limit_reached());
+        assert!(!time_manager.soft_limit_reached(1.0));
+    }
+
+    #[test]
+    fn test_time_manager_ponderhit() {
+        let limits = SearchLimits {
+            move_time: Some(0),
+            ponder: true,
+            ..Default::default()
+        };
+        let time_manager = TimeManager::new(&limits, Color::White);
+
+        assert!(time_manager.is_pondering());
+        assert!(!time_manager.hard_limit_reached());
+
+        time_manager.ponderhit();
+        assert!(!time_manager.is_pondering());
+        assert!(time_manager.hard_limit_reached());
+    }
+
+    #[test]
+    fn test_search_depth_limit() {
+        let result = search(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            SearchLimits {
+                depth: Some(3),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(3, result.depth);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_search_nodes_limit() {
+        let result = search(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            SearchLimits {
+                nodes: Some(10_000),
+                ..Default::default()
+            },
+        );
+
+        assert!(result.nodes <= 10_000);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_search_move_time() {
+        let result = search(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            SearchLimits {
+                move_time: Some(200),
+                ..Default::default()
+            },
+        );
+
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_search_mate_in_one() {
+        let result = search(
+            "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+            SearchLimits {
+                mate: Some(1),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!("h5f7", result.best_move.unwrap().as_string());
+        assert_eq!(MATE_SCORE - 1, result.score);
+    }
+
+    #[test]
+    fn test_search_checkmated() {
+        let result = search(
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+            SearchLimits {
+                depth: Some(2),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(None, result.best_move);
+    }
+
+    #[test]
+    fn test_search_draws() {
+        let limits = SearchLimits {
+            depth: Some(3),
+            ..Default::default()
+        };
+
+        // Every move reaches the 50-move rule, even a queen up.
+        let result = search("4k3/8/8/8/8/8/8/3QK3 w - - 99 80", limits.clone());
+        assert_eq!(0, result.score);
+
+        // Black saves the lost game by going back to where it was.
+        let mut board = Board::from("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        for m in ["e1f1", "e8f8", "f1e1"] {
+            let m = Move::from_string(m, &board).unwrap();
+            board.make_move(&m);
+        }
+        let result = search_board(&board, limits, SearchOptions::default());
+        assert_eq!(0, result.score);
+        assert_eq!("f8e8", result.best_move.unwrap().as_string());
+    }
+
+    #[test]
+    fn test_search_threads() {
+        let result = search_with_threads(
+            "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+            SearchLimits {
+                depth: Some(4),
+                ..Default::default()
+            },
+            4,
+        );
+
+        assert_eq!("h5f7", result.best_move.unwrap().as_string());
+        assert!(result.depth >= 4);
+    }
+
+    #[test]
+    fn test_search_threads_stopped() {
+        // No thread completes an iteration, so none of them has a result to
+        // replace the first legal move with.
+        let result = search_with_threads(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            SearchLimits {
+                nodes: Some(1),
+                ..Default::default()
+            },
+            4,
+        );
+
+        assert_eq!(0, result.depth);
+        assert!(result.lines.is_empty());
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        let tt = TranspositionTable::new(1);
+        let entry = Entry {
+            best_move: Some(Move(0x0c1c)),
+            score: -MATE_SCORE + 7,
+            depth: 5,
+            bound: Bound::Lower,
+        };
+
+        tt.store(0x1234_5678_9abc_def0, entry, 3);
+
+        assert_eq!(None, tt.probe(0x1234_5678_9abc_def1, 3));
+        assert_eq!(Some(entry), tt.probe(0x1234_5678_9abc_def0, 3));
+        assert_eq!(
+            -MATE_SCORE + 5,
+            tt.probe(0x1234_5678_9abc_def0, 1).unwrap().score
+        );
+
+        tt.clear();
+        assert_eq!(None, tt.probe(0x1234_5678_9abc_def0, 3));
+    }
+
+    #[test]
+    fn test_transposition_table_hashfull() {
+        let tt = TranspositionTable::new(1);
+        let entry = Entry {
+            best_move: None,
+            score: 0,
+            depth: 1,
+            bound: Bound::Exact,
+        };
+
+        tt.new_search();
+        assert_eq!(0, tt.hashfull());
+
+        for hash in 0..500 {
+            tt.store(hash, entry, 0);
+        }
+        assert_eq!(500, tt.hashfull());
+
+        tt.new_search();
+        assert_eq!(0, tt.hashfull());
+        assert!(tt.probe(42, 0).is_some());
+    }
+
+    #[test]
+    fn test_search_multi_pv() {
+        let result = search_with_options(
+            "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+            SearchLimits {
+                depth: Some(3),
+                ..Default::default()
+            },
+            SearchOptions {
+                multi_pv: 3,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(3, result.lines.len());
+        assert_eq!("h5f7", result.lines[0].pv[0].as_string());
+        assert_eq!(result.best_move, Some(result.lines[0].pv[0]));
+        assert_eq!(result.score, result.lines[0].score);
+        assert!(result.lines[0].score >= result.lines[1].score);
+        assert!(result.lines[1].score >= result.lines[2].score);
+        assert_ne!(result.lines[1].pv[0], result.lines[2].pv[0]);
+        assert_ne!(result.lines[0].pv[0], result.lines[1].pv[0]);
+    }
+
+    #[test]
+    fn test_search_multi_pv_stopped() {
+        // The first pass is cut short, so there is no line to report and the
+        // first legal move is played.
+        let result = search_with_options(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            SearchLimits {
+                nodes: Some(5),
+                ..Default::default()
+            },
+            SearchOptions {
+                multi_pv: 3,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.lines.is_empty());
+        assert_eq!(0, result.depth);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_search_multi_pv_few_moves() {
+        let result = search_with_options(
+            "7k/8/8/8/8/8/r7/K7 w - - 0 1",
+            SearchLimits {
+                depth: Some(2),
+                ..Default::default()
+            },
+            SearchOptions {
+                multi_pv: 5,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(2, result.lines.len());
+        assert_eq!("a1a2", result.best_move.unwrap().as_string());
+    }
+}
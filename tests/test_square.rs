@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod square_tests {
     use rust_chess_engine::core::square;
-    use rust_chess_engine::core::Square;
+    use rust_chess_engine::core::{Color, File, Rank, Square};
 
     #[test]
     fn test_square_from() {
@@ -35,4 +35,57 @@ mod square_tests {
         assert_eq!(Some(square::C8), square::C7.north());
         assert_eq!(None, square::C8.north());
     }
+
+    #[test]
+    fn test_square_distance() {
+        assert_eq!(0, square::D4.distance(square::D4));
+        assert_eq!(7, square::A1.distance(square::H8));
+        assert_eq!(3, square::B2.distance(square::E4));
+        assert_eq!(3, square::B2.file_distance(square::E4));
+        assert_eq!(2, square::B2.rank_distance(square::E4));
+    }
+
+    #[test]
+    fn test_square_is_aligned() {
+        assert!(Square::is_aligned(square::A1, square::C3, square::H8));
+        assert!(Square::is_aligned(square::E1, square::E8, square::E4));
+        assert!(Square::is_aligned(square::A3, square::C3, square::H3));
+        assert!(!Square::is_aligned(square::A1, square::C3, square::H7));
+        // The first two squares have to be on a line.
+        assert!(!Square::is_aligned(square::B1, square::C3, square::D5));
+    }
+
+    #[test]
+    fn test_square_new() {
+        assert_eq!(square::A1, Square::new(File::A, Rank::First));
+        assert_eq!(square::E4, Square::new(File::E, Rank::Fourth));
+        assert_eq!(square::H8, Square::new(File::H, Rank::Eighth));
+        assert_eq!(File::E, File::of(square::E4));
+        assert_eq!(Rank::Fourth, Rank::of(square::E4));
+    }
+
+    #[test]
+    fn test_square_parse() {
+        assert_eq!(Ok(square::D4), "d4".parse::<Square>());
+        assert_eq!(Ok(square::H8), Square::try_from(63));
+        assert!(Square::try_from(64).is_err());
+        assert!("i1".parse::<Square>().is_err());
+        assert!("a9".parse::<Square>().is_err());
+        assert!("a".parse::<Square>().is_err());
+        assert!("a1b".parse::<Square>().is_err());
+        assert_eq!(None, Square::from_string(""));
+        assert_eq!("e4", square::E4.to_string());
+
+        assert_eq!(Ok(File::H), File::try_from(7));
+        assert!(Rank::try_from(8).is_err());
+    }
+
+    #[test]
+    fn test_square_relative() {
+        assert_eq!(square::C6, square::C3.flip());
+        assert_eq!(square::C3, square::C3.relative_to(Color::White));
+        assert_eq!(square::C6, square::C3.relative_to(Color::Black));
+        assert_eq!(Rank::Seventh, square::A7.relative_rank(Color::White));
+        assert_eq!(Rank::Second, square::A7.relative_rank(Color::Black));
+    }
 }
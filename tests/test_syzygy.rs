@@ -0,0 +1,203 @@
+#[cfg(test)]
+mod syzygy_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::Board;
+    use rust_chess_engine::search::{self, SearchLimits, SearchOptions, TimeManager};
+    use rust_chess_engine::syzygy::{Tablebase, Wdl, MAX_DTZ};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Once};
+
+    static START: Once = Once::new();
+
+    // The 3-man tables listed in the README of this directory. Tests which
+    // need them report it and are skipped while they are missing.
+    const TABLEBASE_PATH: &str = "tests/data/syzygy";
+
+    fn init() {
+        START.call_once(|| {
+            movegen::init();
+        });
+    }
+
+    fn local_tablebase() -> Option<Tablebase> {
+        let tablebase = Tablebase::new(TABLEBASE_PATH)
+            .ok()
+            .filter(|tablebase| tablebase.max_pieces() >= 3);
+        if tablebase.is_none() {
+            eprintln!("No tables in {}, skipping", TABLEBASE_PATH);
+        }
+
+        tablebase
+    }
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("rust_chess_engine_syzygy_{}", name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn test_tablebase_files() {
+        init();
+
+        let path = temp_directory("files");
+        fs::write(path.join("KQvK.rtbw"), [0; 64]).unwrap();
+        fs::write(path.join("KQvK.rtbz"), [0; 64]).unwrap();
+        fs::write(path.join("KQQQQQvKRR.rtbw"), [0; 64]).unwrap();
+        fs::write(path.join("KXvK.rtbw"), [0; 64]).unwrap();
+        fs::write(path.join("README.txt"), "").unwrap();
+
+        let tablebase = Tablebase::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(1, tablebase.len());
+        assert_eq!(3, tablebase.max_pieces());
+
+        // The file is only read on the first probe, a corrupted one is
+        // treated as missing.
+        let board = Board::from("4k3/8/8/8/8/8/8/4KQ2 w - - 0 1").unwrap();
+        assert!(tablebase.can_probe(&board));
+        assert_eq!(None, tablebase.probe_wdl(&board));
+        assert_eq!(None, tablebase.probe_dtz(&board));
+        assert_eq!(None, tablebase.rank_root_moves(&board));
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_tablebase_without_tables() {
+        init();
+
+        assert!(Tablebase::new("tests/data/no_such_directory").is_err());
+
+        let path = temp_directory("empty");
+        let tablebase = Tablebase::new(path.to_str().unwrap()).unwrap();
+        assert!(tablebase.is_empty());
+
+        let board = Board::from("4k3/8/8/8/8/8/8/4KQ2 w - - 0 1").unwrap();
+        assert!(!tablebase.can_probe(&board));
+        assert_eq!(None, tablebase.probe_wdl(&board));
+
+        // The search runs as usual when the tables cannot be probed.
+        let limits = SearchLimits {
+            depth: Some(3),
+            ..Default::default()
+        };
+        let options = SearchOptions {
+            quiet: true,
+            tablebase: Some(Arc::new(tablebase)),
+            ..Default::default()
+        };
+        let time_manager = TimeManager::new(&limits, board.color_to_move);
+        let tt = search::TranspositionTable::new(1);
+        let result = search::run(
+            &board,
+            &limits,
+            &options,
+            &time_manager,
+            &tt,
+            &AtomicBool::new(false),
+        );
+        assert!(result.best_move.is_some());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_probe_wdl() {
+        init();
+
+        let Some(tablebase) = local_tablebase() else {
+            return;
+        };
+
+        let positions = [
+            ("4k3/8/8/8/8/8/8/4KQ2 w - - 0 1", Wdl::Win),
+            ("4k3/8/8/8/8/8/8/4KQ2 b - - 0 1", Wdl::Loss),
+            ("4kq2/8/8/8/8/8/8/4K3 w - - 0 1", Wdl::Loss),
+            ("4k3/8/8/8/8/8/8/4KN2 w - - 0 1", Wdl::Draw),
+            ("4k3/8/8/8/8/8/8/4KR2 b - - 0 1", Wdl::Loss),
+            // The queen is lost at once.
+            ("8/8/8/8/8/8/k7/1Q2K3 b - - 0 1", Wdl::Draw),
+        ];
+
+        for (fen, wdl) in positions {
+            let mut board = Board::from(fen).unwrap();
+            assert_eq!(Some(wdl), tablebase.probe_wdl(&board), "{}", fen);
+
+            let before = board.clone();
+            assert_eq!(
+                Some(wdl),
+                tablebase.probe_wdl_in_place(&mut board),
+                "{}",
+                fen
+            );
+            assert_eq!(before, board, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_probe_dtz() {
+        init();
+
+        let Some(tablebase) = local_tablebase() else {
+            return;
+        };
+
+        // Qa8 mates at once.
+        let board = Board::from("7k/8/6K1/8/8/8/8/Q7 w - - 0 1").unwrap();
+        assert_eq!(Some(1), tablebase.probe_dtz(&board));
+
+        let board = Board::from("4k3/8/8/8/8/8/8/4KN2 w - - 0 1").unwrap();
+        assert_eq!(Some(0), tablebase.probe_dtz(&board));
+
+        let board = Board::from("4k3/8/8/8/8/8/8/4KQ2 b - - 0 1").unwrap();
+        assert!(tablebase.probe_dtz(&board).unwrap() < 0);
+    }
+
+    #[test]
+    fn test_rank_root_moves() {
+        init();
+
+        let Some(tablebase) = local_tablebase() else {
+            return;
+        };
+
+        let board = Board::from("8/8/8/8/8/8/k7/1Q2K3 w - - 0 1").unwrap();
+        let ranks = tablebase.rank_root_moves(&board).unwrap();
+
+        let rank = |name: &str| {
+            ranks
+                .iter()
+                .find(|(m, _)| m.as_string() == name)
+                .map(|&(_, rank)| rank)
+                .unwrap()
+        };
+        assert_eq!(0, rank("b1b2"));
+        assert_eq!(MAX_DTZ, ranks.iter().map(|&(_, rank)| rank).max().unwrap());
+
+        // The search only plays a move which keeps the win.
+        let limits = SearchLimits {
+            depth: Some(4),
+            ..Default::default()
+        };
+        let options = SearchOptions {
+            quiet: true,
+            tablebase: Some(Arc::new(tablebase)),
+            ..Default::default()
+        };
+        let time_manager = TimeManager::new(&limits, board.color_to_move);
+        let tt = search::TranspositionTable::new(1);
+        let result = search::run(
+            &board,
+            &limits,
+            &options,
+            &time_manager,
+            &tt,
+            &AtomicBool::new(false),
+        );
+        assert_eq!(MAX_DTZ, rank(&result.best_move.unwrap().as_string()));
+    }
+}
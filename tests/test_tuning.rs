@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod tuning_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{Board, Move};
+    use rust_chess_engine::evaluation::{self, params::index, PawnTable, DEFAULT_PARAMS};
+    use rust_chess_engine::tuning::{dataset, sigmoid, Method, Tuner};
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    const POSITIONS: &str = "\
+r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3 [0.5]
+rnbqkb1r/pppp1ppp/5n2/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3 \"1/2-1/2\";
+4k3/8/8/8/8/8/4PPPP/4K3 w - - 1-0
+4k3/pppp4/8/8/8/8/8/4K3 w - - 0 1 0-1
+r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1 0.6
+4k3/8/8/8/8/8/8/3QK3 b - - c9 \"1-0\";
+# Comments and blank lines are skipped.
+
+3rk3/8/8/8/8/8/8/4K3 w - - 0 1 [0.0]
+6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1 [0.5]
+";
+
+    const PGN: &str = r#"[Event "Test"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bc4 {Italian} Bc5 (3... Nf6 4. Ng5) 4. O-O Nf6
+5. d3 O-O 6. Bg5 h6 7. Bxf6 Qxf6 $1 1-0
+
+[Event "Test"]
+[FEN "4k3/8/8/8/8/8/4P3/R3K3 w - - 0 1"]
+[Result "1/2-1/2"]
+
+1. Ra8+ Kd7 2. e4 Ke6 3. Ke2 1/2-1/2
+
+[Event "Unfinished"]
+[Result "*"]
+
+1. d4 d5 *
+"#;
+
+    fn init() {
+        START.call_once(|| {
+            movegen::init();
+        });
+    }
+
+    #[test]
+    fn test_params_vector() {
+        let mut params = DEFAULT_PARAMS;
+        let mut vector = params.to_vector();
+
+        vector[0] += 5;
+        params.set_vector(&vector);
+        assert_eq!(DEFAULT_PARAMS.piece_values[0] + 5, params.piece_values[0]);
+        assert_eq!(vector, params.to_vector());
+    }
+
+    #[test]
+    fn test_params_index() {
+        let mut params = DEFAULT_PARAMS;
+        params.bishop_pair = -1;
+        params.max_king_attack = -2;
+        params.king_open_file = -3;
+        let vector = params.to_vector();
+
+        assert_eq!(index::COUNT, vector.len());
+        assert_eq!(-1, vector[index::BISHOP_PAIR]);
+        assert_eq!(-2, vector[index::MAX_KING_ATTACK]);
+        assert_eq!(-3, vector[index::KING_OPEN_FILE]);
+    }
+
+    #[test]
+    fn test_default_params_source() {
+        assert_eq!(
+            include_str!("../src/evaluation/weights.rs"),
+            DEFAULT_PARAMS.to_rust_source()
+        );
+    }
+
+    #[test]
+    fn test_default_params_evaluation() {
+        init();
+        let board = Board::from("4k3/pp6/8/8/8/8/PP6/2B1KB2 w - - 0 1").unwrap();
+
+        let mut params = DEFAULT_PARAMS;
+        let default = evaluation::breakdown(&board, &mut PawnTable::new(16));
+        assert_eq!(
+            default,
+            evaluation::breakdown_with(&board, &mut PawnTable::new(16), &params)
+        );
+
+        params.bishop_pair += 100;
+        assert_eq!(
+            default.total() + 100,
+            evaluation::breakdown_with(&board, &mut PawnTable::new(16), &params).total()
+        );
+    }
+
+    #[test]
+    fn test_parse_fens() {
+        init();
+        let positions = dataset::parse_fens(POSITIONS).unwrap();
+        let results: Vec<f64> = positions.iter().map(|p| p.result).collect();
+
+        assert_eq!(vec![0.5, 0.5, 1.0, 0.0, 0.6, 1.0, 0.0, 0.5], results);
+        assert_eq!(3, positions[0].board.full_move_number);
+        assert!(dataset::parse_fens("4k3/8/8/8/8/8/8/4K3 w - - 0 1 2-0").is_err());
+    }
+
+    #[test]
+    fn test_parse_pgn() {
+        init();
+        let positions = dataset::parse_pgn(PGN).unwrap();
+
+        // 14 moves without the two captures, then 5 moves without the check.
+        assert_eq!(12 + 4, positions.len());
+        assert!(positions[..12].iter().all(|p| p.result == 1.0));
+        assert!(positions[12..].iter().all(|p| p.result == 0.5));
+        assert!(positions[11]
+            .board
+            .to_fen()
+            .starts_with("r1bq1rk1/pppp1pp1/2n2n1p/2b1p1B1/2B1P3/3P1N2/PPP2PPP/RN1Q1RK1 w"));
+    }
+
+    #[test]
+    fn test_from_san() {
+        init();
+        let board = Board::from("r3k2r/1P6/8/8/8/2N3N1/8/R3K2R w KQkq - 0 1").unwrap();
+
+        for (san, uci) in [
+            ("O-O", "e1g1"),
+            ("O-O-O", "e1c1"),
+            ("Nce4", "c3e4"),
+            ("Nge4", "g3e4"),
+            ("b8=Q", "b7b8q"),
+            ("bxa8=N+", "b7a8n"),
+            ("Rh8+", "h1h8"),
+        ] {
+            assert_eq!(
+                Move::from_string(uci, &board).unwrap(),
+                Move::from_san(san, &board).unwrap(),
+                "{}",
+                san
+            );
+        }
+
+        assert!(Move::from_san("Ne4", &board).is_err());
+        assert!(Move::from_san("Qd1", &board).is_err());
+    }
+
+    #[test]
+    fn test_fit_k() {
+        init();
+        let mut tuner = Tuner::new(dataset::parse_fens(POSITIONS).unwrap(), DEFAULT_PARAMS, 2);
+
+        let initial = tuner.error(&tuner.params);
+        let fitted = tuner.fit_k();
+        assert!(fitted <= initial);
+        assert!(tuner.k > 0.0);
+        assert_eq!(0.5, sigmoid(0.0, tuner.k));
+    }
+
+    #[test]
+    fn test_tune() {
+        init();
+        for method in [Method::LocalSearch, Method::GradientDescent(1.0)] {
+            let mut tuner = Tuner::new(dataset::parse_fens(POSITIONS).unwrap(), DEFAULT_PARAMS, 1);
+            tuner.fit_k();
+
+            let initial = tuner.error(&tuner.params);
+            let mut reports = 0;
+            let error = tuner.tune(method, 1, |_, _, _| reports += 1);
+
+            assert_eq!(1, reports);
+            assert!(error < initial, "{:?}", method);
+            assert_ne!(DEFAULT_PARAMS, tuner.params);
+        }
+    }
+
+    #[test]
+    fn test_gradient() {
+        init();
+        let mut tuner = Tuner::new(dataset::parse_fens(POSITIONS).unwrap(), DEFAULT_PARAMS, 2);
+        tuner.fit_k();
+
+        let mut trace = Vec::new();
+        for position in &tuner.positions {
+            assert_eq!(
+                evaluation::breakdown_with(&position.board, &mut PawnTable::new(16), &tuner.params),
+                evaluation::breakdown_traced(
+                    &position.board,
+                    &mut PawnTable::new(16),
+                    &tuner.params,
+                    &mut trace
+                )
+            );
+        }
+
+        // The king attack penalty is rounded, so only the other parameters
+        // match a central difference.
+        let gradient = tuner.gradient();
+        let vector = tuner.params.to_vector();
+        let mut params = tuner.params.clone();
+        for index in (0..index::COUNT)
+            .filter(|&i| !(index::KING_ATTACK_UNITS..=index::MAX_KING_ATTACK).contains(&i))
+        {
+            let mut changed = vector.clone();
+            changed[index] += 1;
+            params.set_vector(&changed);
+            let up = tuner.error(&params);
+
+            changed[index] -= 2;
+            params.set_vector(&changed);
+            let down = tuner.error(&params);
+
+            let difference = (up - down) / 2.0;
+            assert!(
+                (gradient[index] - difference).abs() <= 1e-3 * difference.abs() + 1e-12,
+                "{}: {} != {}",
+                index,
+                gradient[index],
+                difference
+            );
+        }
+    }
+}
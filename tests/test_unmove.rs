@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod unmove_tests {
+    use rust_chess_engine::core::board::CastlingRights;
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{Board, Move, MoveFlags};
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    fn init() {
+        START.call_once(|| {
+            movegen::init();
+        });
+    }
+
+    // The position without the move clocks, which unmaking does not restore.
+    fn position(board: &Board) -> String {
+        let fen = board.to_fen();
+        fen.split(' ').take(4).collect::<Vec<_>>().join(" ")
+    }
+
+    fn legal_moves(board: &mut Board) -> Vec<Move> {
+        let color = board.color_to_move;
+        let mut moves = [Move(0); 218];
+        let moves_count = board.get_moves(&mut moves);
+
+        moves[..moves_count]
+            .iter()
+            .copied()
+            .filter(|m| {
+                board.make_move(m);
+                let is_legal = !board.is_king_in_check(color);
+                board.undo_move(m);
+
+                is_legal
+            })
+            .collect()
+    }
+
+    // Every move played in the positions up to `depth` plies from `board` is
+    // found again from the position it leads to, and taking it back gives the
+    // position before it.
+    fn check_moves_are_unmoves(board: &mut Board, depth: usize) {
+        for m in legal_moves(board) {
+            let before = board.clone();
+            board.make_move(&m);
+
+            // The en passant square before a move is only known for a capture
+            // en passant.
+            if before.en_passant.is_empty() || m.flags() == MoveFlags::EN_PASSANT {
+                let unmoves = board.get_unmoves(before.castling_rights);
+                let found = unmoves.iter().any(|unmove| {
+                    let mut previous = board.clone();
+                    previous.unmake(unmove);
+
+                    unmove.m == m && position(&previous) == position(&before)
+                });
+                assert!(found, "{} {}", before.to_fen(), m.as_string());
+            }
+
+            if depth > 1 {
+                check_moves_are_unmoves(board, depth - 1);
+            }
+            board.undo_move(&m);
+        }
+    }
+
+    // Taking back every unmove and playing it again gives the position.
+    fn check_unmoves_are_moves(board: &Board) {
+        for unmove in board.get_unmoves(CastlingRights::ALL) {
+            let mut previous = board.clone();
+            previous.unmake(&unmove);
+            assert_eq!(previous.compute_hash(), previous.hash);
+
+            let moves = legal_moves(&mut previous);
+            assert!(moves.contains(&unmove.m), "{}", previous.to_fen());
+
+            previous.make_move(&unmove.m);
+            assert_eq!(position(board), position(&previous));
+            assert_eq!(board.hash, previous.hash);
+        }
+    }
+
+    #[test]
+    fn test_unmoves_of_moves() {
+        init();
+
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ];
+
+        for fen in fens {
+            check_moves_are_unmoves(&mut Board::from(fen).unwrap(), 2);
+        }
+    }
+
+    #[test]
+    fn test_moves_of_unmoves() {
+        init();
+
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r4rk1/8/8/8/8/8/8/2KR3R w - - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "rnbqkbnr/ppp1p1pp/3P4/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3",
+            "rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 2",
+        ];
+
+        for fen in fens {
+            check_unmoves_are_moves(&Board::from(fen).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_special_unmoves() {
+        init();
+
+        // Un-castling needs the castling right to be assumed.
+        let board = Board::from("4k3/8/8/8/8/8/8/5RK1 b - - 0 1").unwrap();
+        let castles = |rights: CastlingRights| {
+            board
+                .get_unmoves(rights)
+                .into_iter()
+                .filter(|unmove| unmove.m.flags() == MoveFlags::SHORT_CASTLE)
+                .collect::<Vec<_>>()
+        };
+        assert!(castles(CastlingRights::NONE).is_empty());
+        let unmoves = castles(CastlingRights::WHITE_CASTLE);
+        assert_eq!(1, unmoves.len());
+        assert_eq!(
+            CastlingRights::WHITE_SHORT_CASTLE,
+            unmoves[0].castling_rights
+        );
+
+        // A push and two captures, of any piece but a pawn or a king.
+        let board = Board::from("3Q3k/8/8/8/8/8/8/K7 b - - 0 1").unwrap();
+        let promotions = board
+            .get_unmoves(CastlingRights::NONE)
+            .into_iter()
+            .filter(|unmove| unmove.m.is_promotion())
+            .count();
+        assert_eq!(9, promotions);
+
+        // After a double push, only the push can be taken back.
+        let board = Board::from("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+        let unmoves = board.get_unmoves(CastlingRights::NONE);
+        assert_eq!(1, unmoves.len());
+        assert_eq!("e2e4", unmoves[0].m.as_string());
+
+        // The pawn on d6 took en passant, or took a piece on d6.
+        let board = Board::from("4k3/8/3P4/8/8/8/8/4K3 b - - 0 1").unwrap();
+        let unmoves = board.get_unmoves(CastlingRights::NONE);
+        let en_passant: Vec<_> = unmoves
+            .iter()
+            .filter(|unmove| unmove.m.flags() == MoveFlags::EN_PASSANT)
+            .collect();
+        assert_eq!(2, en_passant.len());
+        let mut previous = board.clone();
+        previous.unmake(en_passant[0]);
+        assert_eq!("4k3/8/8/2Pp4/8/8/8/4K3 w - d6", position(&previous));
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod validate_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{Board, Move};
+    use std::sync::Once;
+
+    static START: Once = Once::new();
+
+    fn init() {
+        START.call_once(|| {
+            movegen::init();
+        });
+    }
+
+    fn violations(fen: &str) -> Vec<String> {
+        init();
+
+        match Board::from_validated(fen) {
+            Ok(_) => Vec::new(),
+            Err(violations) => violations,
+        }
+    }
+
+    #[test]
+    fn test_legal_positions() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 2",
+            "8/8/8/8/8/8/k7/1Q2K3 b - - 0 1",
+            // A double check by a rook and a bishop, discovered by the rook.
+            "4k3/8/8/1B6/8/8/8/K3R3 b - - 0 1",
+            // Two rooks check after a capture promoting to a rook.
+            "3Rk3/8/8/8/8/8/8/K3R3 b - - 0 1",
+            "4k3/8/8/8/8/8/8/QQQQK3 w - - 0 1",
+        ];
+
+        for fen in fens {
+            assert_eq!(Vec::<String>::new(), violations(fen), "{}", fen);
+        }
+    }
+
+    // Every position reached by legal moves is valid.
+    fn check_played_positions(board: &mut Board, depth: usize) {
+        let color = board.color_to_move;
+        let mut moves = [Move(0); 218];
+        let moves_count = board.get_moves(&mut moves);
+
+        for m in &moves[..moves_count] {
+            board.make_move(m);
+            if !board.is_king_in_check(color) {
+                assert_eq!(Ok(()), board.validate(), "{}", board.to_fen());
+                if depth > 1 {
+                    check_played_positions(board, depth - 1);
+                }
+            }
+            board.undo_move(m);
+        }
+    }
+
+    #[test]
+    fn test_played_positions() {
+        init();
+
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        ];
+        for fen in fens {
+            check_played_positions(&mut Board::from(fen).unwrap(), 2);
+        }
+    }
+
+    #[test]
+    fn test_material() {
+        let fen = "rnbqkbnr/pppppppp/8/8/8/P7/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(vec!["White has more than eight pawns"], violations(fen));
+
+        let fen = "4k3/8/8/8/8/8/PPPPPPPP/QQ2K3 w - - 0 1";
+        assert_eq!(
+            vec!["White has 1 promoted pieces but only 0 missing pawns"],
+            violations(fen)
+        );
+
+        // Two bishops on dark squares need a promotion.
+        assert!(violations("4k3/8/8/8/8/8/PPPPPPPP/2B1KB2 w - - 0 1").is_empty());
+        assert!(!violations("4k3/8/8/8/8/8/PPPPPPPP/B1B1K3 w - - 0 1").is_empty());
+
+        assert_eq!(
+            vec!["Black has 0 kings"],
+            violations("8/8/8/8/8/8/8/4K3 w - - 0 1")
+        );
+        assert_eq!(
+            vec!["Pawns are on the first or last rank"],
+            violations("4k2P/8/8/8/8/8/8/4K3 b - - 0 1")
+        );
+    }
+
+    #[test]
+    fn test_checks() {
+        assert_eq!(
+            vec!["The kings are next to each other"],
+            violations("8/8/8/8/8/8/3k4/4K3 b - - 0 1")
+        );
+        assert_eq!(
+            vec!["Black is in check but not to move"],
+            violations("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1")
+        );
+        assert_eq!(
+            vec!["Black is checked by more than two pieces"],
+            violations("4k3/8/3N4/1B6/8/8/8/K3R3 b - - 0 1")
+        );
+        assert_eq!(
+            vec!["Black is checked by two pieces which do not slide"],
+            violations("4k3/3P4/3N4/8/8/8/8/K7 b - - 0 1")
+        );
+
+        // Neither check can have been discovered by the other piece.
+        assert_eq!(
+            vec!["No legal move leads to the position"],
+            violations("R3k3/8/8/7B/8/8/8/K7 b - - 0 1")
+        );
+    }
+
+    #[test]
+    fn test_castling_and_en_passant() {
+        assert_eq!(
+            vec!["Castling right K without the king and rook on their squares"],
+            violations("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1")
+        );
+        assert_eq!(
+            vec!["En passant square e3 without a pawn which has just passed it"],
+            violations("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e3 0 1")
+        );
+        assert_eq!(
+            vec!["En passant square d6 without a pawn which has just passed it"],
+            violations("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq d6 0 1")
+        );
+
+        let errors = Board::from_validated("not a fen").unwrap_err();
+        assert_eq!(1, errors.len());
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod zobrist_tests {
+    use rust_chess_engine::core::movegen;
+    use rust_chess_engine::core::{Board, Move};
+
+    fn play_and_undo(board: &mut Board, depth: u8) {
+        if depth == 0 {
+            return;
+        }
+
+        let mut moves = [Move(0); 218];
+        let moves_count = board.get_moves(&mut moves);
+
+        for m in moves.iter().take(moves_count) {
+            let hash = board.hash;
+            let pawn_hash = board.pawn_hash;
+
+            board.make_move(m);
+            assert_eq!(board.compute_hash(), board.hash);
+            assert_eq!(board.compute_pawn_hash(), board.pawn_hash);
+            play_and_undo(board, depth - 1);
+            board.undo_move(m);
+
+            assert_eq!(hash, board.hash);
+            assert_eq!(pawn_hash, board.pawn_hash);
+        }
+    }
+
+    #[test]
+    fn test_incremental_hash() {
+        movegen::init();
+
+        let mut board =
+            Board::from("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        play_and_undo(&mut board, 3);
+
+        let mut board = Board::from("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+        play_and_undo(&mut board, 4);
+    }
+
+    #[test]
+    fn test_hash_transposition() {
+        movegen::init();
+
+        let board1 = Board::from_moves(&["g1f3", "g8f6", "b1c3", "b8c6"]).unwrap();
+        let board2 = Board::from_moves(&["b1c3", "b8c6", "g1f3", "g8f6"]).unwrap();
+        let board3 = Board::from_moves(&["e2e4"]).unwrap();
+        let board4 =
+            Board::from("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+
+        assert_eq!(board1.hash, board2.hash);
+        assert_ne!(board3.hash, board4.hash);
+        assert_eq!(board3.hash, board3.compute_hash());
+        assert_ne!(Board::new().hash, board1.hash);
+        assert_eq!(Board::new().pawn_hash, board1.pawn_hash);
+        assert_ne!(board3.pawn_hash, board1.pawn_hash);
+        assert_eq!(board3.pawn_hash, board4.pawn_hash);
+    }
+}